@@ -839,82 +839,82 @@ pub type CIoVec = IoVec;
 
 
 #[derive(Default)]
-pub struct Funcs<T: Clone + 'static> {
+pub struct Funcs<T: 'static> {
     /// Return command-line argument data sizes.
     /// results:
     ///  - argc: The number of arguments.
     ///  - argv_buf_size: The size of the argument string data.
-    pub args_sizes_get: Option<fn(&T, &mut MemoryView) -> Result<(Errno, WasmSize, WasmSize), ()>>,
+    pub args_sizes_get: Option<fn(&mut T, &mut Memory) -> Result<(Errno, WasmSize, WasmSize), Error>>,
 
     /// Read command-line argument data.
     /// The size of the array should match that returned by args_sizes_get.
-    pub args_get: Option<fn(&T, &mut MemoryView, argv: WasmPtr<WasmPtr<u8>>, buf: WasmPtr<u8>) -> Result<Errno, ()>>,
+    pub args_get: Option<fn(&mut T, &mut Memory, argv: WasmPtr<WasmPtr<u8>>, buf: WasmPtr<u8>) -> Result<Errno, Error>>,
 
 
     /// Return environment variable data sizes.
     /// results:
     ///  - environc: The number of environment variable arguments.
     ///  - environ_buf_size: The size of the environment variable data.
-    pub environ_sizes_get: Option<fn(&T, &mut MemoryView) -> Result<(Errno, WasmSize, WasmSize), ()>>,
+    pub environ_sizes_get: Option<fn(&mut T, &mut Memory) -> Result<(Errno, WasmSize, WasmSize), Error>>,
 
     /// Read environment variable data. The sizes of the buffers should match that returned by environ_sizes_get.
-    pub environ_get: Option<fn(&T, &mut MemoryView, environ: WasmPtr<WasmPtr<u8>>, environ_buf: WasmPtr<u8>) -> Result<Errno, ()>>,
+    pub environ_get: Option<fn(&mut T, &mut Memory, environ: WasmPtr<WasmPtr<u8>>, environ_buf: WasmPtr<u8>) -> Result<Errno, Error>>,
 
 
     /// Return the resolution of a clock.
     /// Implementations are required to provide a non-zero value for supported clocks.
     /// For unsupported clocks, return errno::inval.
     /// Note: This is similar to clock_getres in POSIX.
-    pub clock_res_get: Option<fn(&T, &mut MemoryView, id: ClockId) -> Result<(Errno, Timestamp), ()>>,
+    pub clock_res_get: Option<fn(&mut T, &mut Memory, id: ClockId) -> Result<(Errno, Timestamp), Error>>,
 
     /// Return the time value of a clock. Note: This is similar to clock_gettime in POSIX.
     /// params:
     ///  - precision: timestamp The maximum lag (exclusive) that the returned time value may have,
     ///    compared to its actual value.
-    pub clock_time_get: Option<fn(&T, &mut MemoryView, id: ClockId, precision: Timestamp) -> Result<(Errno, Timestamp), ()>>,
+    pub clock_time_get: Option<fn(&mut T, &mut Memory, id: ClockId, precision: Timestamp) -> Result<(Errno, Timestamp), Error>>,
 
 
     /// Provide file advisory information on a file descriptor.
     /// Note: This is similar to posix_fadvise in POSIX.
-    pub fd_advise: Option<fn(&T, &mut MemoryView, Fd, offset: FileSize, len: FileSize, advice: Advice) -> Result<Errno, ()>>,
+    pub fd_advise: Option<fn(&mut T, &mut Memory, Fd, offset: FileSize, len: FileSize, advice: Advice) -> Result<Errno, Error>>,
 
     /// Force the allocation of space in a file.
     /// Note: This is similar to posix_fallocate in POSIX.
-    pub fd_allocate: Option<fn(&T, &mut MemoryView, Fd, offset: FileSize, len: FileSize) -> Result<Errno, ()>>,
+    pub fd_allocate: Option<fn(&mut T, &mut Memory, Fd, offset: FileSize, len: FileSize) -> Result<Errno, Error>>,
 
     /// Close a file descriptor. Note: This is similar to close in POSIX.
-    pub fd_close: Option<fn(&T, &mut MemoryView, Fd) -> Result<Errno, ()>>,
+    pub fd_close: Option<fn(&mut T, &mut Memory, Fd) -> Result<Errno, Error>>,
 
     /// Synchronize the data of a file to disk.
     /// Note: This is similar to fdatasync in POSIX.
-    pub fd_datasync: Option<fn (&T, &mut MemoryView, Fd) -> Result<Errno, ()>>,
+    pub fd_datasync: Option<fn(&mut T, &mut Memory, Fd) -> Result<Errno, Error>>,
 
     /// Get the attributes of a file descriptor.
     /// Note: This returns similar flags to fsync(fd, F_GETFL) in POSIX, as well as additional fields.
-    pub fd_fdstat_get: Option<fn(&T, &mut MemoryView, Fd) -> Result<(Errno, FdStat), ()>>,
+    pub fd_fdstat_get: Option<fn(&mut T, &mut Memory, Fd) -> Result<(Errno, FdStat), Error>>,
 
     /// Adjust the flags associated with a file descriptor.
     /// Note: This is similar to fcntl(fd, F_SETFL, flags) in POSIX.
-    pub fd_fdstat_set_flags: Option<fn(&T, &mut MemoryView, Fd, flags: FdFlags) -> Result<Errno, ()>>,
+    pub fd_fdstat_set_flags: Option<fn(&mut T, &mut Memory, Fd, flags: FdFlags) -> Result<Errno, Error>>,
 
     /// Adjust the rights associated with a file descriptor.
     /// This can only be used to remove rights, and returns errno::notcapable
     /// if called in a way that would attempt to add rights.
-    pub fd_fdstat_set_rights: Option<fn(&T, &mut MemoryView, Fd, rights_base: Rights, rights_inheriting: Rights) -> Result<Errno, ()>>,
+    pub fd_fdstat_set_rights: Option<fn(&mut T, &mut Memory, Fd, rights_base: Rights, rights_inheriting: Rights) -> Result<Errno, Error>>,
 
     /// Return the attributes of an open file.
-    pub fd_filestat_get: Option<fn(&T, &mut MemoryView, Fd) -> Result<(Errno, FileStat), ()>>,
+    pub fd_filestat_get: Option<fn(&mut T, &mut Memory, Fd) -> Result<(Errno, FileStat), Error>>,
 
     /// Adjust the size of an open file.
     /// If this increases the file's size, the extra bytes are filled with zeros.
     /// Note: This is similar to ftruncate in POSIX.
-    pub fd_filestat_set_size: Option<fn(&T, &mut MemoryView, Fd, size: FileSize) -> Result<Errno, ()>>,
+    pub fd_filestat_set_size: Option<fn(&mut T, &mut Memory, Fd, size: FileSize) -> Result<Errno, Error>>,
 
     /// Adjust the timestamps of an open file or directory.
     /// Note: This is similar to futimens in POSIX.
     /// params:
     ///  - fst_flags: A bitmask indicating which timestamps to adjust.
-    pub fd_filestat_set_times: Option<fn(&T, &mut MemoryView, Fd, accessed: Timestamp, modified: Timestamp, fst_flags: FstFlags) -> Result<Errno, ()>>,
+    pub fd_filestat_set_times: Option<fn(&mut T, &mut Memory, Fd, accessed: Timestamp, modified: Timestamp, fst_flags: FstFlags) -> Result<Errno, Error>>,
 
     /// Read from a file descriptor, without using and updating the file descriptor's offset.
     /// Note: This is similar to preadv in POSIX.
@@ -922,15 +922,15 @@ pub struct Funcs<T: Clone + 'static> {
     ///  - offset: The offset within the file at which to read.
     /// results:
     ///  - nread: The number of bytes read.
-    pub fd_pread: Option<fn(&T, &mut MemoryView, Fd, iovs: WasmPtr<CIoVec>, iovs_len: WasmSize, offset: FileSize) -> Result<(Errno, WasmSize), ()>>,
+    pub fd_pread: Option<fn(&mut T, &mut Memory, Fd, iovs: WasmPtr<CIoVec>, iovs_len: WasmSize, offset: FileSize) -> Result<(Errno, WasmSize), Error>>,
 
     /// Return a description of the given preopened file descriptor.
-    pub fd_prestat_get: Option<fn(&T, &mut MemoryView, Fd) -> Result<(Errno, PreStat), ()>>,
+    pub fd_prestat_get: Option<fn(&mut T, &mut Memory, Fd) -> Result<(Errno, PreStat), Error>>,
 
     /// Return a description of the given preopened file descriptor.
     /// params:
     ///  - path: A buffer into which to write the preopened directory name.
-    pub fd_prestat_dir_name: Option<fn(&T, &mut MemoryView, Fd, path: WasmPtr<u8>, path_len: WasmSize) -> Result<Errno, ()>>,
+    pub fd_prestat_dir_name: Option<fn(&mut T, &mut Memory, Fd, path: WasmPtr<u8>, path_len: WasmSize) -> Result<Errno, Error>>,
 
     /// Write to a file descriptor, without using and updating the file descriptor's offset.
     /// Note: This is similar to pwritev in POSIX.
@@ -938,12 +938,12 @@ pub struct Funcs<T: Clone + 'static> {
     ///  - offset: The offset within the file at which to write.
     /// results:
     ///  - nwritten: The number of bytes written.
-    pub fd_pwrite: Option<fn(&T, &mut MemoryView, Fd, iovs: WasmPtr<IoVec>, iovs_len: WasmSize, offset: FileSize) -> Result<(Errno, WasmSize), ()>>,
+    pub fd_pwrite: Option<fn(&mut T, &mut Memory, Fd, iovs: WasmPtr<IoVec>, iovs_len: WasmSize, offset: FileSize) -> Result<(Errno, WasmSize), Error>>,
 
     /// Read from a file descriptor. Note: This is similar to readv in POSIX.
     /// results:
     ///  - nread: The number of bytes read.
-    pub fd_read: Option<fn(&T, &mut MemoryView, Fd, iovs: WasmPtr<CIoVec>, iovs_len: WasmSize) -> Result<(Errno, WasmSize), ()>>,
+    pub fd_read: Option<fn(&mut T, &mut Memory, Fd, iovs: WasmPtr<CIoVec>, iovs_len: WasmSize) -> Result<(Errno, WasmSize), Error>>,
 
     /// Read directory entries from a directory.
     /// When successful, the contents of the output buffer consist of a sequence of directory entries.
@@ -956,7 +956,7 @@ pub struct Funcs<T: Clone + 'static> {
     /// results:
     ///  - bufused: The number of bytes stored in the read buffer.
     ///    If less than the size of the read buffer, the end of the directory has been reached.
-    pub fd_readdir: Option<fn(&T, &mut MemoryView, Fd, buf: WasmPtr<u8>, buf_len: WasmSize, cookie: DirCookie) -> Result<(Errno, WasmSize), ()>>,
+    pub fd_readdir: Option<fn(&mut T, &mut Memory, Fd, buf: WasmPtr<u8>, buf_len: WasmSize, cookie: DirCookie) -> Result<(Errno, WasmSize), Error>>,
 
     /// Atomically replace a file descriptor by renumbering another file descriptor.
     /// Due to the strong focus on thread safety, this environment does not provide a mechanism to
@@ -965,7 +965,7 @@ pub struct Funcs<T: Clone + 'static> {
     /// could be allocated by a different thread at the same time.
     /// This function provides a way to atomically renumber file descriptors,
     /// which would disappear if dup2() were to be removed entirely.
-    pub fd_renumber: Option<fn(&T, &mut MemoryView, from: Fd, to: Fd) -> Result<Errno, ()>>,
+    pub fd_renumber: Option<fn(&mut T, &mut Memory, from: Fd, to: Fd) -> Result<Errno, Error>>,
 
     /// Move the offset of a file descriptor.
     /// Note: This is similar to lseek in POSIX.
@@ -974,41 +974,41 @@ pub struct Funcs<T: Clone + 'static> {
     ///  - whence: The base from which the offset is relative.
     /// results:
     ///  - newoffset: filesize The new offset of the file descriptor, relative to the start of the file.
-    pub fd_seek: Option<fn(&T, &mut MemoryView, Fd, delta: FileDelta, whence: Whence) -> Result<(Errno, FileSize), ()>>,
+    pub fd_seek: Option<fn(&mut T, &mut Memory, Fd, delta: FileDelta, whence: Whence) -> Result<(Errno, FileSize), Error>>,
 
     /// Synchronize the data and metadata of a file to disk.
     /// Note: This is similar to fsync in POSIX.
-    pub fd_sync: Option<fn(&T, &mut MemoryView, Fd) -> Result<Errno, ()>>,
+    pub fd_sync: Option<fn(&mut T, &mut Memory, Fd) -> Result<Errno, Error>>,
 
     /// Return the current offset of a file descriptor.
     /// Note: This is similar to lseek(fd, 0, SEEK_CUR) in POSIX.
     /// results:
     ///  - offset: The current offset of the file descriptor, relative to the start of the file.
-    pub fd_tell: Option<fn(&T, &mut MemoryView, Fd) -> Result<(Errno, FileSize), ()>>,
+    pub fd_tell: Option<fn(&mut T, &mut Memory, Fd) -> Result<(Errno, FileSize), Error>>,
 
     /// Write to a file descriptor. Note: This is similar to writev in POSIX.
     /// results:
     ///  - nwritten: The number of bytes written.
-    pub fd_write: Option<fn(&T, &mut MemoryView, Fd, iovs: WasmPtr<IoVec>, iovs_len: WasmSize) -> Result<(Errno, WasmSize), ()>>,
+    pub fd_write: Option<fn(&mut T, &mut Memory, Fd, iovs: WasmPtr<IoVec>, iovs_len: WasmSize) -> Result<(Errno, WasmSize), Error>>,
 
 
     /// Create a directory.
     /// Note: This is similar to mkdirat in POSIX.
-    pub path_create_directory: Option<fn(&T, &mut MemoryView, Fd, path: WasmPtr<u8>, path_len: WasmSize) -> Result<Errno, ()>>,
+    pub path_create_directory: Option<fn(&mut T, &mut Memory, Fd, path: WasmPtr<u8>, path_len: WasmSize) -> Result<Errno, Error>>,
 
     /// Return the attributes of a file or directory.
     /// Note: This is similar to stat in POSIX.
-    pub path_filestat_get: Option<fn(&T, &mut MemoryView, Fd, flags: LookupFlags, path: WasmPtr<u8>, path_len: WasmSize) -> Result<(Errno, FileStat), ()>>,
+    pub path_filestat_get: Option<fn(&mut T, &mut Memory, Fd, flags: LookupFlags, path: WasmPtr<u8>, path_len: WasmSize) -> Result<(Errno, FileStat), Error>>,
 
     /// Adjust the timestamps of a file or directory.
     /// Note: This is similar to utimensat in POSIX.
     /// params:
     ///  - fst_flags: A bitmask indicating which timestamps to adjust.
-    pub path_filestat_set_times: Option<fn(&T, &mut MemoryView, Fd, flags: LookupFlags, path: WasmPtr<u8>, path_len: WasmSize, accessed: Timestamp, modified: Timestamp, fst_flags: FstFlags) -> Result<Errno, ()>>,
+    pub path_filestat_set_times: Option<fn(&mut T, &mut Memory, Fd, flags: LookupFlags, path: WasmPtr<u8>, path_len: WasmSize, accessed: Timestamp, modified: Timestamp, fst_flags: FstFlags) -> Result<Errno, Error>>,
 
     /// Create a hard link.
     /// Note: This is similar to linkat in POSIX.
-    pub path_link: Option<fn(&T, &mut MemoryView, old_fd: Fd, old_flags: LookupFlags, old_path: WasmPtr<u8>, old_path_len: WasmSize, new_fd: Fd, new_path: WasmPtr<u8>, new_path_len: WasmSize) -> Result<Errno, ()>>,
+    pub path_link: Option<fn(&mut T, &mut Memory, old_fd: Fd, old_flags: LookupFlags, old_path: WasmPtr<u8>, old_path_len: WasmSize, new_fd: Fd, new_path: WasmPtr<u8>, new_path_len: WasmSize) -> Result<Errno, Error>>,
 
     /// Open a file or directory.
     /// The returned file descriptor is not guaranteed to be the lowest-numbered file descriptor
@@ -1022,7 +1022,7 @@ pub struct Funcs<T: Clone + 'static> {
     ///    if and only if those rights do not apply to the type of file being opened.
     ///    The base rights are rights that will apply to operations using the file descriptor itself,
     ///    while the inheriting rights are rights that apply to file descriptors derived from it.
-    pub path_open: Option<fn(&T, &mut MemoryView, Fd, flags: LookupFlags, path: WasmPtr<u8>, path_len: WasmSize, open_flags: OpenFlags, rights_base: Rights, rights_inheriting: Rights, fd_flags: FdFlags) -> Result<(Errno, Fd), ()>>,
+    pub path_open: Option<fn(&mut T, &mut Memory, Fd, flags: LookupFlags, path: WasmPtr<u8>, path_len: WasmSize, open_flags: OpenFlags, rights_base: Rights, rights_inheriting: Rights, fd_flags: FdFlags) -> Result<(Errno, Fd), Error>>,
 
     /// Read the contents of a symbolic link.
     /// Note: This is similar to readlinkat in POSIX.
@@ -1031,28 +1031,28 @@ pub struct Funcs<T: Clone + 'static> {
     ///  - buf: The buffer to which to write the contents of the symbolic link.
     /// results:
     ///  - buf_used: The number of bytes placed in the buffer.
-    pub path_readlink: Option<fn(&T, &mut MemoryView, Fd, path: WasmPtr<u8>, path_len: WasmSize, buf: WasmPtr<u8>, buf_len: WasmSize) -> Result<(Errno, WasmSize), ()>>,
+    pub path_readlink: Option<fn(&mut T, &mut Memory, Fd, path: WasmPtr<u8>, path_len: WasmSize, buf: WasmPtr<u8>, buf_len: WasmSize) -> Result<(Errno, WasmSize), Error>>,
 
     /// Remove a directory.
     /// Return errno::notempty if the directory is not empty.
     /// Note: This is similar to unlinkat(fd, path, AT_REMOVEDIR) in POSIX.
-    pub path_remove_directory: Option<fn(&T, &mut MemoryView, Fd, path: WasmPtr<u8>, path_len: WasmSize) -> Result<Errno, ()>>,
+    pub path_remove_directory: Option<fn(&mut T, &mut Memory, Fd, path: WasmPtr<u8>, path_len: WasmSize) -> Result<Errno, Error>>,
 
     /// Rename a file or directory.
     /// Note: This is similar to renameat in POSIX.
-    pub path_rename: Option<fn(&T, &mut MemoryView, old_base: Fd, old_path: WasmPtr<u8>, old_path_len: WasmSize, new_base: Fd, new_path: WasmPtr<u8>, new_path_len: WasmSize) -> Result<Errno, ()>>,
+    pub path_rename: Option<fn(&mut T, &mut Memory, old_base: Fd, old_path: WasmPtr<u8>, old_path_len: WasmSize, new_base: Fd, new_path: WasmPtr<u8>, new_path_len: WasmSize) -> Result<Errno, Error>>,
 
     /// Create a symbolic link.
     /// Note: This is similar to symlinkat in POSIX.
     /// params:
     ///  - old_path: The contents of the symbolic link.
     ///  - new_path: The destination path at which to create the symbolic link.
-    pub path_symlink: Option<fn(&T, &mut MemoryView, old_path: WasmPtr<u8>, old_path_len: WasmSize, base: Fd, new_path: WasmPtr<u8>, new_path_len: WasmSize) -> Result<Errno, ()>>,
+    pub path_symlink: Option<fn(&mut T, &mut Memory, old_path: WasmPtr<u8>, old_path_len: WasmSize, base: Fd, new_path: WasmPtr<u8>, new_path_len: WasmSize) -> Result<Errno, Error>>,
 
     /// Unlink a file.
     /// Return errno::isdir if the path refers to a directory.
     /// Note: This is similar to unlinkat(fd, path, 0) in POSIX.
-    pub path_unlink_file: Option<fn(&T, &mut MemoryView, base: Fd, path: WasmPtr<u8>, WasmSize) -> Result<Errno, ()>>,
+    pub path_unlink_file: Option<fn(&mut T, &mut Memory, base: Fd, path: WasmPtr<u8>, WasmSize) -> Result<Errno, Error>>,
 
 
     /// Concurrently poll for the occurrence of a set of events.
@@ -1062,22 +1062,22 @@ pub struct Funcs<T: Clone + 'static> {
     ///  - num_subs: Both the number of subscriptions and events.
     /// results:
     ///  - num_events: The number of events stored.
-    pub poll_oneoff: Option<fn(&T, &mut MemoryView, subs: WasmPtr<Subscription>, out: WasmPtr<Event>, num_subs: WasmSize) -> Result<(Errno, WasmSize), ()>>,
+    pub poll_oneoff: Option<fn(&mut T, &mut Memory, subs: WasmPtr<Subscription>, out: WasmPtr<Event>, num_subs: WasmSize) -> Result<(Errno, WasmSize), Error>>,
 
 
     /// Terminate the process normally.
     /// An exit code of 0 indicates successful termination of the program.
     /// The meanings of other values is dependent on the environment.
-    pub proc_exit: Option<fn(&T, &mut MemoryView, exit_code: ExitCode) -> Result<(), ()>>,
+    pub proc_exit: Option<fn(&mut T, &mut Memory, exit_code: ExitCode) -> Result<(), Error>>,
 
     /// Send a signal to the process of the calling thread.
     /// Note: This is similar to raise in POSIX.
-    pub proc_raise: Option<fn(&T, &mut MemoryView, Signal) -> Result<Errno, ()>>,
+    pub proc_raise: Option<fn(&mut T, &mut Memory, Signal) -> Result<Errno, Error>>,
 
 
     /// Temporarily yield execution of the calling thread.
     /// Note: This is similar to sched_yield in POSIX.
-    pub sched_yield: Option<fn(&T, &mut MemoryView) -> Result<Errno, ()>>,
+    pub sched_yield: Option<fn(&mut T, &mut Memory) -> Result<Errno, Error>>,
 
 
     /// Write high-quality random data into a buffer.
@@ -1086,29 +1086,29 @@ pub struct Funcs<T: Clone + 'static> {
     /// This function may execute slowly, so when large mounts of random data are required,
     /// it's advisable to use this function to seed a pseudo-random number generator,
     /// rather than to provide the random data directly.
-    pub random_get: Option<fn(&T, &mut MemoryView, buf: WasmPtr<u8>, buf_len: WasmSize) -> Result<Errno, ()>>,
+    pub random_get: Option<fn(&mut T, &mut Memory, buf: WasmPtr<u8>, buf_len: WasmSize) -> Result<Errno, Error>>,
 
 
     /// Accept a new incoming connection.
     /// Note: This is similar to `accept` in POSIX.
-    pub sock_accept: Option<fn(&T, &mut MemoryView, Fd, flags: FdFlags) -> Result<(Errno, Fd), ()>>,
+    pub sock_accept: Option<fn(&mut T, &mut Memory, Fd, flags: FdFlags) -> Result<(Errno, Fd), Error>>,
 
     /// Receive a message from a socket. 
     /// Note: This is similar to recv in POSIX, though it also supports reading the data into 
     /// multiple buffers in the manner of readv.
-    pub sock_recv: Option<fn(&T, &mut MemoryView, Fd, iovs: WasmPtr<IoVec>, iovs_len: WasmSize, flags: SockRecvInFlags) -> Result<(Errno, WasmSize, SockRecvOutFlags), ()>>,
+    pub sock_recv: Option<fn(&mut T, &mut Memory, Fd, iovs: WasmPtr<IoVec>, iovs_len: WasmSize, flags: SockRecvInFlags) -> Result<(Errno, WasmSize, SockRecvOutFlags), Error>>,
 
     /// Send a message on a socket. 
     /// Note: This is similar to send in POSIX, though it also supports writing the data from 
     /// multiple buffers in the manner of writev.
-    pub sock_send: Option<fn(&T, &mut MemoryView, Fd, iovs: WasmPtr<CIoVec>, iovs_len: WasmSize, flags: SockSendFlags) -> Result<(Errno, WasmSize), ()>>,
+    pub sock_send: Option<fn(&mut T, &mut Memory, Fd, iovs: WasmPtr<CIoVec>, iovs_len: WasmSize, flags: SockSendFlags) -> Result<(Errno, WasmSize), Error>>,
 
     /// Shut down socket send and receive channels. 
     /// Note: This is similar to shutdown in POSIX.
-    pub sock_shutdown: Option<fn(&T, &mut MemoryView, Fd, flags: SockShutdownFlags) -> Result<Errno, ()>>,
+    pub sock_shutdown: Option<fn(&mut T, &mut Memory, Fd, flags: SockShutdownFlags) -> Result<Errno, Error>>,
 }
 
-impl<T: Clone> Funcs<T> {
+impl<T> Funcs<T> {
     pub fn unimplemented() -> Funcs<T> {
         Funcs {
             args_sizes_get: Some(|_, _| { unimplemented!() }),
@@ -1173,419 +1173,422 @@ impl<T: Clone> Funcs<T> {
 
 #[derive(Default)]
 pub struct StoreFuncs {
-    pub args_sizes_get: Option<Func>,
-    pub args_get: Option<Func>,
-
-    pub environ_sizes_get: Option<Func>,
-    pub environ_get: Option<Func>,
-
-    pub clock_res_get: Option<Func>,
-    pub clock_time_get: Option<Func>,
-
-    pub fd_advise: Option<Func>,
-    pub fd_allocate: Option<Func>,
-    pub fd_close: Option<Func>,
-    pub fd_datasync: Option<Func>,
-    pub fd_fdstat_get: Option<Func>,
-    pub fd_fdstat_set_flags: Option<Func>,
-    pub fd_fdstat_set_rights: Option<Func>,
-    pub fd_filestat_get: Option<Func>,
-    pub fd_filestat_set_size: Option<Func>,
-    pub fd_filestat_set_times: Option<Func>,
-    pub fd_pread: Option<Func>,
-    pub fd_prestat_get: Option<Func>,
-    pub fd_prestat_dir_name: Option<Func>,
-    pub fd_pwrite: Option<Func>,
-    pub fd_read: Option<Func>,
-    pub fd_readdir: Option<Func>,
-    pub fd_renumber: Option<Func>,
-    pub fd_seek: Option<Func>,
-    pub fd_sync: Option<Func>,
-    pub fd_tell: Option<Func>,
-    pub fd_write: Option<Func>,
-
-    pub path_create_directory: Option<Func>,
-    pub path_filestat_get: Option<Func>,
-    pub path_filestat_set_times: Option<Func>,
-    pub path_link: Option<Func>,
-    pub path_open: Option<Func>,
-    pub path_readlink: Option<Func>,
-    pub path_remove_directory: Option<Func>,
-    pub path_rename: Option<Func>,
-    pub path_symlink: Option<Func>,
-    pub path_unlink_file: Option<Func>,
-
-    pub poll_oneoff: Option<Func>,
-
-    pub proc_exit: Option<Func>,
-    pub proc_raise: Option<Func>,
-
-    pub sched_yield: Option<Func>,
-
-    pub random_get: Option<Func>,
-
-    pub sock_accept: Option<Func>,
-    pub sock_recv: Option<Func>,
-    pub sock_send: Option<Func>,
-    pub sock_shutdown: Option<Func>,
+    pub args_sizes_get: Option<FuncId>,
+    pub args_get: Option<FuncId>,
+
+    pub environ_sizes_get: Option<FuncId>,
+    pub environ_get: Option<FuncId>,
+
+    pub clock_res_get: Option<FuncId>,
+    pub clock_time_get: Option<FuncId>,
+
+    pub fd_advise: Option<FuncId>,
+    pub fd_allocate: Option<FuncId>,
+    pub fd_close: Option<FuncId>,
+    pub fd_datasync: Option<FuncId>,
+    pub fd_fdstat_get: Option<FuncId>,
+    pub fd_fdstat_set_flags: Option<FuncId>,
+    pub fd_fdstat_set_rights: Option<FuncId>,
+    pub fd_filestat_get: Option<FuncId>,
+    pub fd_filestat_set_size: Option<FuncId>,
+    pub fd_filestat_set_times: Option<FuncId>,
+    pub fd_pread: Option<FuncId>,
+    pub fd_prestat_get: Option<FuncId>,
+    pub fd_prestat_dir_name: Option<FuncId>,
+    pub fd_pwrite: Option<FuncId>,
+    pub fd_read: Option<FuncId>,
+    pub fd_readdir: Option<FuncId>,
+    pub fd_renumber: Option<FuncId>,
+    pub fd_seek: Option<FuncId>,
+    pub fd_sync: Option<FuncId>,
+    pub fd_tell: Option<FuncId>,
+    pub fd_write: Option<FuncId>,
+
+    pub path_create_directory: Option<FuncId>,
+    pub path_filestat_get: Option<FuncId>,
+    pub path_filestat_set_times: Option<FuncId>,
+    pub path_link: Option<FuncId>,
+    pub path_open: Option<FuncId>,
+    pub path_readlink: Option<FuncId>,
+    pub path_remove_directory: Option<FuncId>,
+    pub path_rename: Option<FuncId>,
+    pub path_symlink: Option<FuncId>,
+    pub path_unlink_file: Option<FuncId>,
+
+    pub poll_oneoff: Option<FuncId>,
+
+    pub proc_exit: Option<FuncId>,
+    pub proc_raise: Option<FuncId>,
+
+    pub sched_yield: Option<FuncId>,
+
+    pub random_get: Option<FuncId>,
+
+    pub sock_accept: Option<FuncId>,
+    pub sock_recv: Option<FuncId>,
+    pub sock_send: Option<FuncId>,
+    pub sock_shutdown: Option<FuncId>,
 }
 
-impl<T: Clone + 'static> Funcs<T> {
-    pub fn register(&self, this: T, store: &mut Store) -> StoreFuncs {
+impl<T: 'static> Funcs<T> {
+    /// creates host functions for the implemented wasi functions.
+    /// - the functions access the store's data, which must be a `T`.
+    ///   see `Store::set_data`.
+    pub fn register(&self, store: &mut Store) -> StoreFuncs {
         let mut result = StoreFuncs::default();
 
         if let Some(args_sizes_get) = self.args_sizes_get {
-            let this = this.clone();
-            result.args_sizes_get = Some(store.add_func(move |mem: &mut MemoryView, argc_ptr: WasmPtr<WasmSize>, argv_buf_size_ptr: WasmPtr<WasmSize>| {
-                let (errno, argc, argv_buf_size) = args_sizes_get(&this, mem)?;
+            result.args_sizes_get = Some(store.new_host_func(move |store: &mut Store, argc_ptr: WasmPtr<WasmSize>, argv_buf_size_ptr: WasmPtr<WasmSize>| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, argc, argv_buf_size) = args_sizes_get(this, &mut mem)?;
                 mem.write(argc_ptr, argc)?;
                 mem.write(argv_buf_size_ptr, argv_buf_size)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(args_get) = self.args_get {
-            let this = this.clone();
-            result.args_get = Some(store.add_func(move |mem: &mut MemoryView, argv, buf| {
-                return Ok(args_get(&this, mem, argv, buf)?.0 as u32);
-            }).func());
+            result.args_get = Some(store.new_host_func(move |store: &mut Store, argv, buf| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                return Ok(args_get(this, &mut mem, argv, buf)?.0 as u32);
+            }).func_id());
         }
 
         if let Some(environ_sizes_get) = self.environ_sizes_get {
-            let this = this.clone();
-            result.environ_sizes_get = Some(store.add_func(move |mem: &mut MemoryView, environc_ptr: WasmPtr<WasmSize>, environ_buf_size_ptr: WasmPtr<WasmSize>| {
-                let (errno, environc, environ_buf_size) = environ_sizes_get(&this, mem)?;
+            result.environ_sizes_get = Some(store.new_host_func(move |store: &mut Store, environc_ptr: WasmPtr<WasmSize>, environ_buf_size_ptr: WasmPtr<WasmSize>| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, environc, environ_buf_size) = environ_sizes_get(this, &mut mem)?;
                 mem.write(environc_ptr, environc)?;
                 mem.write(environ_buf_size_ptr, environ_buf_size)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(environ_get) = self.environ_get {
-            let this = this.clone();
-            result.environ_get = Some(store.add_func(move |mem: &mut MemoryView, environ, environ_buf| {
-                let errno = environ_get(&this, mem, environ, environ_buf)?;
+            result.environ_get = Some(store.new_host_func(move |store: &mut Store, environ, environ_buf| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = environ_get(this, &mut mem, environ, environ_buf)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
 
         if let Some(clock_res_get) = self.clock_res_get {
-            let this = this.clone();
-            result.clock_res_get = Some(store.add_func(move |mem: &mut MemoryView, clock: u32, res_ptr| {
-                let (errno, res) = clock_res_get(&this, mem, ClockId(clock))?;
+            result.clock_res_get = Some(store.new_host_func(move |store: &mut Store, clock: u32, res_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, res) = clock_res_get(this, &mut mem, ClockId(clock))?;
                 mem.write(res_ptr, res)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(clock_time_get) = self.clock_time_get {
-            let this = this.clone();
-            result.clock_time_get = Some(store.add_func(move |mem: &mut MemoryView, clock: u32, precision: u64, time_ptr| {
-                let (errno, time) = clock_time_get(&this, mem, ClockId(clock), Timestamp { nanos: precision })?;
+            result.clock_time_get = Some(store.new_host_func(move |store: &mut Store, clock: u32, precision: u64, time_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, time) = clock_time_get(this, &mut mem, ClockId(clock), Timestamp { nanos: precision })?;
                 mem.write(time_ptr, time)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
 
         if let Some(fd_advise) = self.fd_advise {
-            let this = this.clone();
-            result.fd_advise = Some(store.add_func(move |mem: &mut MemoryView, fd, offset, len, advice: u32| {
-                let errno = fd_advise(&this, mem, Fd(fd), FileSize(offset), FileSize(len), Advice(advice as u8))?;
+            result.fd_advise = Some(store.new_host_func(move |store: &mut Store, fd, offset, len, advice: u32| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_advise(this, &mut mem, Fd(fd), FileSize(offset), FileSize(len), Advice(advice as u8))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_allocate) = self.fd_allocate {
-            let this = this.clone();
-            result.fd_allocate = Some(store.add_func(move |mem: &mut MemoryView, fd, offset, len| {
-                let errno = fd_allocate(&this, mem, Fd(fd), FileSize(offset), FileSize(len))?;
+            result.fd_allocate = Some(store.new_host_func(move |store: &mut Store, fd, offset, len| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_allocate(this, &mut mem, Fd(fd), FileSize(offset), FileSize(len))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_close) = self.fd_close {
-            let this = this.clone();
-            result.fd_close = Some(store.add_func(move |mem: &mut MemoryView, fd| {
-                let errno = fd_close(&this, mem, Fd(fd))?;
+            result.fd_close = Some(store.new_host_func(move |store: &mut Store, fd| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_close(this, &mut mem, Fd(fd))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_datasync) = self.fd_datasync {
-            let this = this.clone();
-            result.fd_datasync = Some(store.add_func(move |mem: &mut MemoryView, fd| {
-                let errno = fd_datasync(&this, mem, Fd(fd))?;
+            result.fd_datasync = Some(store.new_host_func(move |store: &mut Store, fd| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_datasync(this, &mut mem, Fd(fd))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_fdstat_get) = self.fd_fdstat_get {
-            let this = this.clone();
-            result.fd_fdstat_get = Some(store.add_func(move |mem: &mut MemoryView, fd, fdstat_ptr| {
-                let (errno, fdstat) = fd_fdstat_get(&this, mem, Fd(fd))?;
+            result.fd_fdstat_get = Some(store.new_host_func(move |store: &mut Store, fd, fdstat_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, fdstat) = fd_fdstat_get(this, &mut mem, Fd(fd))?;
                 mem.write(fdstat_ptr, fdstat)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_fdstat_set_flags) = self.fd_fdstat_set_flags {
-            let this = this.clone();
-            result.fd_fdstat_set_flags = Some(store.add_func(move |mem: &mut MemoryView, fd, flags: u32| {
-                let errno = fd_fdstat_set_flags(&this, mem, Fd(fd), FdFlags(flags as u16))?;
+            result.fd_fdstat_set_flags = Some(store.new_host_func(move |store: &mut Store, fd, flags: u32| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_fdstat_set_flags(this, &mut mem, Fd(fd), FdFlags(flags as u16))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_fdstat_set_rights) = self.fd_fdstat_set_rights {
-            let this = this.clone();
-            result.fd_fdstat_set_rights = Some(store.add_func(move |mem: &mut MemoryView, fd, rights_base, rights_inheriting| {
-                let errno = fd_fdstat_set_rights(&this, mem, Fd(fd), Rights(rights_base), Rights(rights_inheriting))?;
+            result.fd_fdstat_set_rights = Some(store.new_host_func(move |store: &mut Store, fd, rights_base, rights_inheriting| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_fdstat_set_rights(this, &mut mem, Fd(fd), Rights(rights_base), Rights(rights_inheriting))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_filestat_get) = self.fd_filestat_get {
-            let this = this.clone();
-            result.fd_filestat_get = Some(store.add_func(move |mem: &mut MemoryView, fd, stat_ptr| {
-                let (errno, stat) = fd_filestat_get(&this, mem, Fd(fd))?;
+            result.fd_filestat_get = Some(store.new_host_func(move |store: &mut Store, fd, stat_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, stat) = fd_filestat_get(this, &mut mem, Fd(fd))?;
                 mem.write(stat_ptr, stat)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_filestat_set_size) = self.fd_filestat_set_size {
-            let this = this.clone();
-            result.fd_filestat_set_size = Some(store.add_func(move |mem: &mut MemoryView, fd, size| {
-                let errno = fd_filestat_set_size(&this, mem, Fd(fd), FileSize(size))?;
+            result.fd_filestat_set_size = Some(store.new_host_func(move |store: &mut Store, fd, size| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_filestat_set_size(this, &mut mem, Fd(fd), FileSize(size))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_filestat_set_times) = self.fd_filestat_set_times {
-            let this = this.clone();
-            result.fd_filestat_set_times = Some(store.add_func(move |mem: &mut MemoryView, fd, accessed, modified, fst_flags: u32| {
-                let errno = fd_filestat_set_times(&this, mem, Fd(fd), Timestamp { nanos: accessed }, Timestamp { nanos: modified }, FstFlags(fst_flags as u16))?;
+            result.fd_filestat_set_times = Some(store.new_host_func(move |store: &mut Store, fd, accessed, modified, fst_flags: u32| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_filestat_set_times(this, &mut mem, Fd(fd), Timestamp { nanos: accessed }, Timestamp { nanos: modified }, FstFlags(fst_flags as u16))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_pread) = self.fd_pread {
-            let this = this.clone();
-            result.fd_pread = Some(store.add_func(move |mem: &mut MemoryView, fd, iovs, iovs_len, offset, nread_ptr| {
-                let (errno, nread) = fd_pread(&this, mem, Fd(fd), iovs, iovs_len, FileSize(offset))?;
+            result.fd_pread = Some(store.new_host_func(move |store: &mut Store, fd, iovs, iovs_len, offset, nread_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, nread) = fd_pread(this, &mut mem, Fd(fd), iovs, iovs_len, FileSize(offset))?;
                 mem.write(nread_ptr, nread)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_prestat_get) = self.fd_prestat_get {
-            let this = this.clone();
-            result.fd_prestat_get = Some(store.add_func(move |mem: &mut MemoryView, fd, prestat_ptr| {
-                let (errno, prestat) = fd_prestat_get(&this, mem, Fd(fd))?;
+            result.fd_prestat_get = Some(store.new_host_func(move |store: &mut Store, fd, prestat_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, prestat) = fd_prestat_get(this, &mut mem, Fd(fd))?;
                 mem.write(prestat_ptr, prestat)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_prestat_dir_name) = self.fd_prestat_dir_name {
-            let this = this.clone();
-            result.fd_prestat_dir_name = Some(store.add_func(move |mem: &mut MemoryView, fd, path, path_len| {
-                let errno = fd_prestat_dir_name(&this, mem, Fd(fd), path, path_len)?;
+            result.fd_prestat_dir_name = Some(store.new_host_func(move |store: &mut Store, fd, path, path_len| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_prestat_dir_name(this, &mut mem, Fd(fd), path, path_len)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_pwrite) = self.fd_pwrite {
-            let this = this.clone();
-            result.fd_pwrite = Some(store.add_func(move |mem: &mut MemoryView, fd, iovs, iovs_len, offset, nwritten_ptr| {
-                let (errno, nwritten) = fd_pwrite(&this, mem, Fd(fd), iovs, iovs_len, FileSize(offset))?;
+            result.fd_pwrite = Some(store.new_host_func(move |store: &mut Store, fd, iovs, iovs_len, offset, nwritten_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, nwritten) = fd_pwrite(this, &mut mem, Fd(fd), iovs, iovs_len, FileSize(offset))?;
                 mem.write(nwritten_ptr, nwritten)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_read) = self.fd_read {
-            let this = this.clone();
-            result.fd_read = Some(store.add_func(move |mem: &mut MemoryView, fd, iovs, iovs_len, nread_ptr| {
-                let (errno, nread) = fd_read(&this, mem, Fd(fd), iovs, iovs_len)?;
+            result.fd_read = Some(store.new_host_func(move |store: &mut Store, fd, iovs, iovs_len, nread_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, nread) = fd_read(this, &mut mem, Fd(fd), iovs, iovs_len)?;
                 mem.write(nread_ptr, nread)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_readdir) = self.fd_readdir {
-            let this = this.clone();
-            result.fd_readdir = Some(store.add_func(move |mem: &mut MemoryView, fd, buf, buf_len, cookie, bufused_ptr| {
-                let (errno, bufused) = fd_readdir(&this, mem, Fd(fd), buf, buf_len, DirCookie(cookie))?;
+            result.fd_readdir = Some(store.new_host_func(move |store: &mut Store, fd, buf, buf_len, cookie, bufused_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, bufused) = fd_readdir(this, &mut mem, Fd(fd), buf, buf_len, DirCookie(cookie))?;
                 mem.write(bufused_ptr, bufused)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_renumber) = self.fd_renumber {
-            let this = this.clone();
-            result.fd_renumber = Some(store.add_func(move |mem: &mut MemoryView, from, to| {
-                let errno = fd_renumber(&this, mem, Fd(from), Fd(to))?;
+            result.fd_renumber = Some(store.new_host_func(move |store: &mut Store, from, to| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_renumber(this, &mut mem, Fd(from), Fd(to))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_seek) = self.fd_seek {
-            let this = this.clone();
-            result.fd_seek = Some(store.add_func(move |mem: &mut MemoryView, fd, delta, whence: u32, newoffset_ptr| {
-                let (errno, newoffset) = fd_seek(&this, mem, Fd(fd), FileDelta(delta), Whence(whence as u8))?;
+            result.fd_seek = Some(store.new_host_func(move |store: &mut Store, fd, delta, whence: u32, newoffset_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, newoffset) = fd_seek(this, &mut mem, Fd(fd), FileDelta(delta), Whence(whence as u8))?;
                 mem.write(newoffset_ptr, newoffset)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_sync) = self.fd_sync {
-            let this = this.clone();
-            result.fd_sync = Some(store.add_func(move |mem: &mut MemoryView, fd| {
-                let errno = fd_sync(&this, mem, Fd(fd))?;
+            result.fd_sync = Some(store.new_host_func(move |store: &mut Store, fd| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = fd_sync(this, &mut mem, Fd(fd))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_tell) = self.fd_tell {
-            let this = this.clone();
-            result.fd_tell = Some(store.add_func(move |mem: &mut MemoryView, fd, offset_ptr| {
-                let (errno, offset) = fd_tell(&this, mem, Fd(fd))?;
+            result.fd_tell = Some(store.new_host_func(move |store: &mut Store, fd, offset_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, offset) = fd_tell(this, &mut mem, Fd(fd))?;
                 mem.write(offset_ptr, offset)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(fd_write) = self.fd_write {
-            let this = this.clone();
-            result.fd_write = Some(store.add_func(move |mem: &mut MemoryView, fd, iovs, iovs_len, nwritten_ptr| {
-                let (errno, nwritten) = fd_write(&this, mem, Fd(fd), iovs, iovs_len)?;
+            result.fd_write = Some(store.new_host_func(move |store: &mut Store, fd, iovs, iovs_len, nwritten_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, nwritten) = fd_write(this, &mut mem, Fd(fd), iovs, iovs_len)?;
                 mem.write(nwritten_ptr, nwritten)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
 
         if let Some(path_create_directory) = self.path_create_directory {
-            let this = this.clone();
-            result.path_create_directory = Some(store.add_func(move |mem: &mut MemoryView, fd, path, path_len| {
-                let errno = path_create_directory(&this, mem, Fd(fd), path, path_len)?;
+            result.path_create_directory = Some(store.new_host_func(move |store: &mut Store, fd, path, path_len| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = path_create_directory(this, &mut mem, Fd(fd), path, path_len)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(path_filestat_get) = self.path_filestat_get {
-            let this = this.clone();
-            result.path_filestat_get = Some(store.add_func(move |mem: &mut MemoryView, fd: u32, flags: u32, path: WasmPtr<u8>, path_len: WasmSize, filestat_ptr| {
-                let (errno, filestat) = path_filestat_get(&this, mem, Fd(fd), LookupFlags(flags), path, path_len)?;
+            result.path_filestat_get = Some(store.new_host_func(move |store: &mut Store, fd: u32, flags: u32, path: WasmPtr<u8>, path_len: WasmSize, filestat_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, filestat) = path_filestat_get(this, &mut mem, Fd(fd), LookupFlags(flags), path, path_len)?;
                 mem.write(filestat_ptr, filestat)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(path_filestat_set_times) = self.path_filestat_set_times {
-            let this = this.clone();
-            result.path_filestat_set_times = Some(store.add_func(move |mem: &mut MemoryView, fd, flags, path, path_len, accessed, modified, fst_flags: u32| {
-                let errno = path_filestat_set_times(&this, mem, Fd(fd), LookupFlags(flags), path, path_len, Timestamp { nanos: accessed }, Timestamp { nanos: modified }, FstFlags(fst_flags as u16))?;
+            result.path_filestat_set_times = Some(store.new_host_func(move |store: &mut Store, fd, flags, path, path_len, accessed, modified, fst_flags: u32| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = path_filestat_set_times(this, &mut mem, Fd(fd), LookupFlags(flags), path, path_len, Timestamp { nanos: accessed }, Timestamp { nanos: modified }, FstFlags(fst_flags as u16))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(path_link) = self.path_link {
-            let this = this.clone();
-            result.path_link = Some(store.add_func(move |mem: &mut MemoryView, old_fd, old_flags, old_path, old_path_len, new_fd, new_path, new_path_len| {
-                let errno = path_link(&this, mem, Fd(old_fd), LookupFlags(old_flags), old_path, old_path_len, Fd(new_fd), new_path, new_path_len)?;
+            result.path_link = Some(store.new_host_func(move |store: &mut Store, old_fd, old_flags, old_path, old_path_len, new_fd, new_path, new_path_len| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = path_link(this, &mut mem, Fd(old_fd), LookupFlags(old_flags), old_path, old_path_len, Fd(new_fd), new_path, new_path_len)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(path_open) = self.path_open {
-            let this = this.clone();
-            result.path_open = Some(store.add_func(move |mem: &mut MemoryView, fd, flags, path, path_len, open_flags: u32, rights_base, rights_inheriting, fd_flags: u32, fd_ptr| {
-                let (errno, fd) = path_open(&this, mem, Fd(fd), LookupFlags(flags), path, path_len, OpenFlags(open_flags as u16), Rights(rights_base), Rights(rights_inheriting), FdFlags(fd_flags as u16))?;
+            result.path_open = Some(store.new_host_func(move |store: &mut Store, fd, flags, path, path_len, open_flags: u32, rights_base, rights_inheriting, fd_flags: u32, fd_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, fd) = path_open(this, &mut mem, Fd(fd), LookupFlags(flags), path, path_len, OpenFlags(open_flags as u16), Rights(rights_base), Rights(rights_inheriting), FdFlags(fd_flags as u16))?;
                 mem.write(fd_ptr, fd)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(path_readlink) = self.path_readlink {
-            let this = this.clone();
-            result.path_readlink = Some(store.add_func(move |mem: &mut MemoryView, fd, path, path_len, buf, buf_len, bufused_ptr| {
-                let (errno, bufused) = path_readlink(&this, mem, Fd(fd), path, path_len, buf, buf_len)?;
+            result.path_readlink = Some(store.new_host_func(move |store: &mut Store, fd, path, path_len, buf, buf_len, bufused_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, bufused) = path_readlink(this, &mut mem, Fd(fd), path, path_len, buf, buf_len)?;
                 mem.write(bufused_ptr, bufused)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(path_remove_directory) = self.path_remove_directory {
-            let this = this.clone();
-            result.path_remove_directory = Some(store.add_func(move |mem: &mut MemoryView, fd, path, path_len| {
-                let errno = path_remove_directory(&this, mem, Fd(fd), path, path_len)?;
+            result.path_remove_directory = Some(store.new_host_func(move |store: &mut Store, fd, path, path_len| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = path_remove_directory(this, &mut mem, Fd(fd), path, path_len)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(path_rename) = self.path_rename {
-            let this = this.clone();
-            result.path_rename = Some(store.add_func(move |mem: &mut MemoryView, old_base: u32, old_path: WasmPtr<u8>, old_path_len: WasmSize, new_base: u32, new_path: WasmPtr<u8>, new_path_len: WasmSize| {
-                let errno = path_rename(&this, mem, Fd(old_base), old_path, old_path_len, Fd(new_base), new_path, new_path_len)?;
+            result.path_rename = Some(store.new_host_func(move |store: &mut Store, old_base: u32, old_path: WasmPtr<u8>, old_path_len: WasmSize, new_base: u32, new_path: WasmPtr<u8>, new_path_len: WasmSize| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = path_rename(this, &mut mem, Fd(old_base), old_path, old_path_len, Fd(new_base), new_path, new_path_len)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(path_symlink) = self.path_symlink {
-            let this = this.clone();
-            result.path_symlink = Some(store.add_func(move |mem: &mut MemoryView, old_path, old_path_len, base, new_path, new_path_len| {
-                let errno = path_symlink(&this, mem, old_path, old_path_len, Fd(base), new_path, new_path_len)?;
+            result.path_symlink = Some(store.new_host_func(move |store: &mut Store, old_path, old_path_len, base, new_path, new_path_len| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = path_symlink(this, &mut mem, old_path, old_path_len, Fd(base), new_path, new_path_len)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(path_unlink_file) = self.path_unlink_file {
-            let this = this.clone();
-            result.path_unlink_file = Some(store.add_func(move |mem: &mut MemoryView, base, path, path_len| {
-                let errno = path_unlink_file(&this, mem, Fd(base), path, path_len)?;
+            result.path_unlink_file = Some(store.new_host_func(move |store: &mut Store, base, path, path_len| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = path_unlink_file(this, &mut mem, Fd(base), path, path_len)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
 
         if let Some(poll_oneoff) = self.poll_oneoff {
-            let this = this.clone();
-            result.poll_oneoff = Some(store.add_func(move |mem: &mut MemoryView, subs, out, num_subs, numevents_ptr| {
-                let (errno, numevents) = poll_oneoff(&this, mem, subs, out, num_subs)?;
+            result.poll_oneoff = Some(store.new_host_func(move |store: &mut Store, subs, out, num_subs, numevents_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, numevents) = poll_oneoff(this, &mut mem, subs, out, num_subs)?;
                 mem.write(numevents_ptr, numevents)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
 
         if let Some(proc_exit) = self.proc_exit {
-            let this = this.clone();
-            result.proc_exit = Some(store.add_func(move |mem: &mut MemoryView, exit_code| {
-                proc_exit(&this, mem, ExitCode(exit_code))
-            }).func());
+            result.proc_exit = Some(store.new_host_func(move |store: &mut Store, exit_code| -> Result<(), Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                proc_exit(this, &mut mem, ExitCode(exit_code))
+            }).func_id());
         }
         if let Some(proc_raise) = self.proc_raise {
-            let this = this.clone();
-            result.proc_raise = Some(store.add_func(move |mem: &mut MemoryView, signal: u32| {
-                let errno = proc_raise(&this, mem, Signal(signal as u8))?;
+            result.proc_raise = Some(store.new_host_func(move |store: &mut Store, signal: u32| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = proc_raise(this, &mut mem, Signal(signal as u8))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
 
         if let Some(sched_yield) = self.sched_yield {
-            let this = this.clone();
-            result.sched_yield = Some(store.add_func(move |mem: &mut MemoryView| {
-                let errno = sched_yield(&this, mem)?;
+            result.sched_yield = Some(store.new_host_func(move |store: &mut Store| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = sched_yield(this, &mut mem)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
 
         if let Some(random_get) = self.random_get {
-            let this = this.clone();
-            result.random_get = Some(store.add_func(move |mem: &mut MemoryView, buf, buf_len| {
-                let errno = random_get(&this, mem, buf, buf_len)?;
+            result.random_get = Some(store.new_host_func(move |store: &mut Store, buf, buf_len| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = random_get(this, &mut mem, buf, buf_len)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
 
         if let Some(sock_accept) = self.sock_accept {
-            let this = this.clone();
-            result.sock_accept = Some(store.add_func(move |mem: &mut MemoryView, fd, flags: u32, outfd_ptr| {
-                let (errno, outfd) = sock_accept(&this, mem, Fd(fd), FdFlags(flags as u16))?;
+            result.sock_accept = Some(store.new_host_func(move |store: &mut Store, fd, flags: u32, outfd_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, outfd) = sock_accept(this, &mut mem, Fd(fd), FdFlags(flags as u16))?;
                 mem.write(outfd_ptr, outfd)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(sock_recv) = self.sock_recv {
-            let this = this.clone();
-            result.sock_recv = Some(store.add_func(move |mem: &mut MemoryView, fd, iovs, iovs_len, flags: u32, nrecv_ptr, outflags_ptr| {
-                let (errno, nrecv, outflags) = sock_recv(&this, mem, Fd(fd), iovs, iovs_len, SockRecvInFlags(flags as u16))?;
+            result.sock_recv = Some(store.new_host_func(move |store: &mut Store, fd, iovs, iovs_len, flags: u32, nrecv_ptr, outflags_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, nrecv, outflags) = sock_recv(this, &mut mem, Fd(fd), iovs, iovs_len, SockRecvInFlags(flags as u16))?;
                 mem.write(nrecv_ptr, nrecv)?;
                 mem.write(outflags_ptr, outflags)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(sock_send) = self.sock_send {
-            let this = this.clone();
-            result.sock_send = Some(store.add_func(move |mem: &mut MemoryView, fd, iovs, iovs_len, flags: u32, nsent_ptr| {
-                let (errno, nsent) = sock_send(&this, mem, Fd(fd), iovs, iovs_len, SockSendFlags(flags as u16))?;
+            result.sock_send = Some(store.new_host_func(move |store: &mut Store, fd, iovs, iovs_len, flags: u32, nsent_ptr| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let (errno, nsent) = sock_send(this, &mut mem, Fd(fd), iovs, iovs_len, SockSendFlags(flags as u16))?;
                 mem.write(nsent_ptr, nsent)?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
         if let Some(sock_shutdown) = self.sock_shutdown {
-            let this = this.clone();
-            result.sock_shutdown = Some(store.add_func(move |mem: &mut MemoryView, fd, flags: u32| {
-                let errno = sock_shutdown(&this, mem, Fd(fd), SockShutdownFlags(flags as u8))?;
+            result.sock_shutdown = Some(store.new_host_func(move |store: &mut Store, fd, flags: u32| -> Result<u32, Error> {
+                let (mut mem, this) = store.caller_memory_and_data::<T>()?;
+                let errno = sock_shutdown(this, &mut mem, Fd(fd), SockShutdownFlags(flags as u8))?;
                 return Ok(errno.0 as u32);
-            }).func());
+            }).func_id());
         }
 
         return result;
@@ -1593,155 +1596,156 @@ impl<T: Clone + 'static> Funcs<T> {
 }
 
 impl StoreFuncs {
-    pub fn add_imports(&self, imports: &mut Imports) {
+    /// defines the functions in `linker`, under `wasi_snapshot_preview1`.
+    pub fn define(&self, linker: &mut Linker) {
         let module = "wasi_snapshot_preview1";
 
         if let Some(args_sizes_get) = self.args_sizes_get {
-            imports.add(module, "args_sizes_get", args_sizes_get.into());
+            linker.define(module, "args_sizes_get", args_sizes_get);
         }
         if let Some(args_get) = self.args_get {
-            imports.add(module, "args_get", args_get.into());
+            linker.define(module, "args_get", args_get);
         }
 
         if let Some(environ_sizes_get) = self.environ_sizes_get {
-            imports.add(module, "environ_sizes_get", environ_sizes_get.into());
+            linker.define(module, "environ_sizes_get", environ_sizes_get);
         }
         if let Some(environ_get) = self.environ_get {
-            imports.add(module, "environ_get", environ_get.into());
+            linker.define(module, "environ_get", environ_get);
         }
 
         if let Some(clock_res_get) = self.clock_res_get {
-            imports.add(module, "clock_res_get", clock_res_get.into());
+            linker.define(module, "clock_res_get", clock_res_get);
         }
         if let Some(clock_time_get) = self.clock_time_get {
-            imports.add(module, "clock_time_get", clock_time_get.into());
+            linker.define(module, "clock_time_get", clock_time_get);
         }
 
         if let Some(fd_advise) = self.fd_advise {
-            imports.add(module, "fd_advise", fd_advise.into());
+            linker.define(module, "fd_advise", fd_advise);
         }
         if let Some(fd_allocate) = self.fd_allocate {
-            imports.add(module, "fd_allocate", fd_allocate.into());
+            linker.define(module, "fd_allocate", fd_allocate);
         }
         if let Some(fd_close) = self.fd_close {
-            imports.add(module, "fd_close", fd_close.into());
+            linker.define(module, "fd_close", fd_close);
         }
         if let Some(fd_datasync) = self.fd_datasync {
-            imports.add(module, "fd_datasync", fd_datasync.into());
+            linker.define(module, "fd_datasync", fd_datasync);
         }
         if let Some(fd_fdstat_get) = self.fd_fdstat_get {
-            imports.add(module, "fd_fdstat_get", fd_fdstat_get.into());
+            linker.define(module, "fd_fdstat_get", fd_fdstat_get);
         }
         if let Some(fd_fdstat_set_flags) = self.fd_fdstat_set_flags {
-            imports.add(module, "fd_fdstat_set_flags", fd_fdstat_set_flags.into());
+            linker.define(module, "fd_fdstat_set_flags", fd_fdstat_set_flags);
         }
         if let Some(fd_fdstat_set_rights) = self.fd_fdstat_set_rights {
-            imports.add(module, "fd_fdstat_set_rights", fd_fdstat_set_rights.into());
+            linker.define(module, "fd_fdstat_set_rights", fd_fdstat_set_rights);
         }
         if let Some(fd_filestat_get) = self.fd_filestat_get {
-            imports.add(module, "fd_filestat_get", fd_filestat_get.into());
+            linker.define(module, "fd_filestat_get", fd_filestat_get);
         }
         if let Some(fd_filestat_set_size) = self.fd_filestat_set_size {
-            imports.add(module, "fd_filestat_set_size", fd_filestat_set_size.into());
+            linker.define(module, "fd_filestat_set_size", fd_filestat_set_size);
         }
         if let Some(fd_filestat_set_times) = self.fd_filestat_set_times {
-            imports.add(module, "fd_filestat_set_times", fd_filestat_set_times.into());
+            linker.define(module, "fd_filestat_set_times", fd_filestat_set_times);
         }
         if let Some(fd_pread) = self.fd_pread {
-            imports.add(module, "fd_pread", fd_pread.into());
+            linker.define(module, "fd_pread", fd_pread);
         }
         if let Some(fd_prestat_get) = self.fd_prestat_get {
-            imports.add(module, "fd_prestat_get", fd_prestat_get.into());
+            linker.define(module, "fd_prestat_get", fd_prestat_get);
         }
         if let Some(fd_prestat_dir_name) = self.fd_prestat_dir_name {
-            imports.add(module, "fd_prestat_dir_name", fd_prestat_dir_name.into());
+            linker.define(module, "fd_prestat_dir_name", fd_prestat_dir_name);
         }
         if let Some(fd_pwrite) = self.fd_pwrite {
-            imports.add(module, "fd_pwrite", fd_pwrite.into());
+            linker.define(module, "fd_pwrite", fd_pwrite);
         }
         if let Some(fd_read) = self.fd_read {
-            imports.add(module, "fd_read", fd_read.into());
+            linker.define(module, "fd_read", fd_read);
         }
         if let Some(fd_readdir) = self.fd_readdir {
-            imports.add(module, "fd_readdir", fd_readdir.into());
+            linker.define(module, "fd_readdir", fd_readdir);
         }
         if let Some(fd_renumber) = self.fd_renumber {
-            imports.add(module, "fd_renumber", fd_renumber.into());
+            linker.define(module, "fd_renumber", fd_renumber);
         }
         if let Some(fd_seek) = self.fd_seek {
-            imports.add(module, "fd_seek", fd_seek.into());
+            linker.define(module, "fd_seek", fd_seek);
         }
         if let Some(fd_sync) = self.fd_sync {
-            imports.add(module, "fd_sync", fd_sync.into());
+            linker.define(module, "fd_sync", fd_sync);
         }
         if let Some(fd_tell) = self.fd_tell {
-            imports.add(module, "fd_tell", fd_tell.into());
+            linker.define(module, "fd_tell", fd_tell);
         }
         if let Some(fd_write) = self.fd_write {
-            imports.add(module, "fd_write", fd_write.into());
+            linker.define(module, "fd_write", fd_write);
         }
 
         if let Some(path_create_directory) = self.path_create_directory {
-            imports.add(module, "path_create_directory", path_create_directory.into());
+            linker.define(module, "path_create_directory", path_create_directory);
         }
         if let Some(path_filestat_get) = self.path_filestat_get {
-            imports.add(module, "path_filestat_get", path_filestat_get.into());
+            linker.define(module, "path_filestat_get", path_filestat_get);
         }
         if let Some(path_filestat_set_times) = self.path_filestat_set_times {
-            imports.add(module, "path_filestat_set_times", path_filestat_set_times.into());
+            linker.define(module, "path_filestat_set_times", path_filestat_set_times);
         }
         if let Some(path_link) = self.path_link {
-            imports.add(module, "path_link", path_link.into());
+            linker.define(module, "path_link", path_link);
         }
         if let Some(path_open) = self.path_open {
-            imports.add(module, "path_open", path_open.into());
+            linker.define(module, "path_open", path_open);
         }
         if let Some(path_readlink) = self.path_readlink {
-            imports.add(module, "path_readlink", path_readlink.into());
+            linker.define(module, "path_readlink", path_readlink);
         }
         if let Some(path_remove_directory) = self.path_remove_directory {
-            imports.add(module, "path_remove_directory", path_remove_directory.into());
+            linker.define(module, "path_remove_directory", path_remove_directory);
         }
         if let Some(path_rename) = self.path_rename {
-            imports.add(module, "path_rename", path_rename.into());
+            linker.define(module, "path_rename", path_rename);
         }
         if let Some(path_symlink) = self.path_symlink {
-            imports.add(module, "path_symlink", path_symlink.into());
+            linker.define(module, "path_symlink", path_symlink);
         }
         if let Some(path_unlink_file) = self.path_unlink_file {
-            imports.add(module, "path_unlink_file", path_unlink_file.into());
+            linker.define(module, "path_unlink_file", path_unlink_file);
         }
 
         if let Some(poll_oneoff) = self.poll_oneoff {
-            imports.add(module, "poll_oneoff", poll_oneoff.into());
+            linker.define(module, "poll_oneoff", poll_oneoff);
         }
 
         if let Some(proc_exit) = self.proc_exit {
-            imports.add(module, "proc_exit", proc_exit.into());
+            linker.define(module, "proc_exit", proc_exit);
         }
         if let Some(proc_raise) = self.proc_raise {
-            imports.add(module, "proc_raise", proc_raise.into());
+            linker.define(module, "proc_raise", proc_raise);
         }
 
         if let Some(sched_yield) = self.sched_yield {
-            imports.add(module, "sched_yield", sched_yield.into());
+            linker.define(module, "sched_yield", sched_yield);
         }
 
         if let Some(random_get) = self.random_get {
-            imports.add(module, "random_get", random_get.into());
+            linker.define(module, "random_get", random_get);
         }
 
         if let Some(sock_accept) = self.sock_accept {
-            imports.add(module, "sock_accept", sock_accept.into());
+            linker.define(module, "sock_accept", sock_accept);
         }
         if let Some(sock_recv) = self.sock_recv {
-            imports.add(module, "sock_recv", sock_recv.into());
+            linker.define(module, "sock_recv", sock_recv);
         }
         if let Some(sock_send) = self.sock_send {
-            imports.add(module, "sock_send", sock_send.into());
+            linker.define(module, "sock_send", sock_send);
         }
         if let Some(sock_shutdown) = self.sock_shutdown {
-            imports.add(module, "sock_shutdown", sock_shutdown.into());
+            linker.define(module, "sock_shutdown", sock_shutdown);
         }
     }
 }
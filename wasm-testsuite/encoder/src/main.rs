@@ -47,7 +47,13 @@ fn convert(wast: &str) -> (Vec<u8>, Vec<Vec<u8>>) {
         return Ok(());
     }
 
+    // an empty module name refers to the most recent module.
+    fn push_module_name(output: &mut Vec<u8>, id: Option<wast::token::Id>) {
+        push_bytes(output, id.map(|id| id.name()).unwrap_or("").as_bytes());
+    }
+
     fn push_invoke(output: &mut Vec<u8>, invoke: &wast::WastInvoke) -> Result<(), ()> {
+        push_module_name(output, invoke.module);
         push_bytes(output, invoke.name.as_bytes());
         push_args(output, &invoke.args)
     }
@@ -104,9 +110,14 @@ fn convert(wast: &str) -> (Vec<u8>, Vec<Vec<u8>>) {
 
         match op {
             WD::Wat(wat) => {
+                let id = match wat {
+                    wast::QuoteWat::Wat(wast::Wat::Module(module)) => module.id,
+                    _ => None,
+                };
                 let wasm = wat.encode().unwrap();
                 output.push(0x01);
                 push_bytes(&mut output, &wasm);
+                push_module_name(&mut output, id);
                 modules.push(wasm);
             }
 
@@ -126,8 +137,10 @@ fn convert(wast: &str) -> (Vec<u8>, Vec<Vec<u8>>) {
                 push_bytes(&mut output, message.as_bytes());
             }
 
-            WD::Register { span: _, name: _, module: _ } => {
-                println!("skip register");
+            WD::Register { span: _, name, module } => {
+                output.push(0x04);
+                push_bytes(&mut output, name.as_bytes());
+                push_module_name(&mut output, *module);
             }
 
             WD::Invoke(invoke) => {
@@ -141,22 +154,20 @@ fn convert(wast: &str) -> (Vec<u8>, Vec<Vec<u8>>) {
             WD::AssertTrap { span: _, exec, message } => {
                 match exec {
                     wast::WastExecute::Invoke(invoke) => {
-                        if invoke.module.is_some() {
-                            println!("skip assert trap (module id)");
-                        }
-                        else {
-                            let mut cmd = vec![];
-                            cmd.push(0x06);
-                            if push_invoke(&mut cmd, invoke).is_ok() {
-                                push_bytes(&mut cmd, message.as_bytes());
-                                output.extend_from_slice(&cmd);
-                            }
+                        let mut cmd = vec![];
+                        cmd.push(0x06);
+                        if push_invoke(&mut cmd, invoke).is_ok() {
+                            push_bytes(&mut cmd, message.as_bytes());
+                            output.extend_from_slice(&cmd);
                         }
                     }
 
-                    wast::WastExecute::Wat(_) => {
-                        println!("skip assert trap (wat)");
-                        continue;
+                    wast::WastExecute::Wat(wat) => {
+                        let wasm = wat.encode().unwrap();
+                        output.push(0x08);
+                        push_bytes(&mut output, &wasm);
+                        push_bytes(&mut output, message.as_bytes());
+                        modules.push(wasm);
                     }
 
                     wast::WastExecute::Get { module: _, global: _ } => {
@@ -169,16 +180,11 @@ fn convert(wast: &str) -> (Vec<u8>, Vec<Vec<u8>>) {
             WD::AssertReturn { span: _, exec, results } => {
                 match exec {
                     wast::WastExecute::Invoke(invoke) => {
-                        if invoke.module.is_some() {
-                            println!("skip assert return (module id)");
-                        }
-                        else {
-                            let mut cmd = vec![];
-                            cmd.push(0x07);
-                            if push_invoke(&mut cmd, invoke).is_ok()
-                            && push_rets(&mut cmd, results).is_ok() {
-                                output.extend_from_slice(&cmd);
-                            }
+                        let mut cmd = vec![];
+                        cmd.push(0x07);
+                        if push_invoke(&mut cmd, invoke).is_ok()
+                        && push_rets(&mut cmd, results).is_ok() {
+                            output.extend_from_slice(&cmd);
                         }
                     }
 
@@ -194,12 +200,21 @@ fn convert(wast: &str) -> (Vec<u8>, Vec<Vec<u8>>) {
                 }
             }
 
-            WD::AssertExhaustion { span: _, call: _, message: _ } => {
-                println!("skip assert exhaustion");
+            WD::AssertExhaustion { span: _, call, message } => {
+                let mut cmd = vec![];
+                cmd.push(0x0a);
+                if push_invoke(&mut cmd, call).is_ok() {
+                    push_bytes(&mut cmd, message.as_bytes());
+                    output.extend_from_slice(&cmd);
+                }
             }
 
-            WD::AssertUnlinkable { span: _, module: _, message: _ } => {
-                println!("skip assert unlinkable");
+            WD::AssertUnlinkable { span: _, module, message } => {
+                let wasm = module.encode().unwrap();
+                output.push(0x09);
+                push_bytes(&mut output, &wasm);
+                push_bytes(&mut output, message.as_bytes());
+                modules.push(wasm);
             }
 
             WD::AssertException { span: _, exec: _ } => {
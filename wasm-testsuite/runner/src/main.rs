@@ -1,5 +1,5 @@
 use sti::reader::Reader;
-use wenjin::{Store, Value};
+use wenjin::{Store, Value, wasm};
 
 
 fn main() {
@@ -16,7 +16,7 @@ fn main() {
         ("br.wast", &include_bytes!("../../testsuite-bin/br.wast")[..]),
         ("br_if.wast", &include_bytes!("../../testsuite-bin/br_if.wast")[..]),
         ("br_table.wast", &include_bytes!("../../testsuite-bin/br_table.wast")[..]),
-        //("bulk.wast", &include_bytes!("../../testsuite-bin/bulk.wast")[..]),
+        ("bulk.wast", &include_bytes!("../../testsuite-bin/bulk.wast")[..]),
         ("call.wast", &include_bytes!("../../testsuite-bin/call.wast")[..]),
         ("call_indirect.wast", &include_bytes!("../../testsuite-bin/call_indirect.wast")[..]),
         ("comments.wast", &include_bytes!("../../testsuite-bin/comments.wast")[..]),
@@ -52,7 +52,7 @@ fn main() {
         ("int_literals.wast", &include_bytes!("../../testsuite-bin/int_literals.wast")[..]),
         ("labels.wast", &include_bytes!("../../testsuite-bin/labels.wast")[..]),
         ("left-to-right.wast", &include_bytes!("../../testsuite-bin/left-to-right.wast")[..]),
-        //("linking.wast", &include_bytes!("../../testsuite-bin/linking.wast")[..]),
+        ("linking.wast", &include_bytes!("../../testsuite-bin/linking.wast")[..]),
         ("load.wast", &include_bytes!("../../testsuite-bin/load.wast")[..]),
         ("local_get.wast", &include_bytes!("../../testsuite-bin/local_get.wast")[..]),
         ("local_set.wast", &include_bytes!("../../testsuite-bin/local_set.wast")[..]),
@@ -133,9 +133,9 @@ fn main() {
         ("simd_store64_lane.wast", &include_bytes!("../../testsuite-bin/simd_store64_lane.wast")[..]),
         ("simd_store8_lane.wast", &include_bytes!("../../testsuite-bin/simd_store8_lane.wast")[..]),
         */
-        //("skip-stack-guard-page.wast", &include_bytes!("../../testsuite-bin/skip-stack-guard-page.wast")[..]),
+        ("skip-stack-guard-page.wast", &include_bytes!("../../testsuite-bin/skip-stack-guard-page.wast")[..]),
         ("stack.wast", &include_bytes!("../../testsuite-bin/stack.wast")[..]),
-        //("start.wast", &include_bytes!("../../testsuite-bin/start.wast")[..]),
+        ("start.wast", &include_bytes!("../../testsuite-bin/start.wast")[..]),
         ("store.wast", &include_bytes!("../../testsuite-bin/store.wast")[..]),
         ("switch.wast", &include_bytes!("../../testsuite-bin/switch.wast")[..]),
         ("table-sub.wast", &include_bytes!("../../testsuite-bin/table-sub.wast")[..]),
@@ -173,6 +173,10 @@ fn main() {
 
         let st_global_i32 = store.new_global(false, Value::I32(666));
         let st_global_i64 = store.new_global(false, Value::I64(666));
+        let st_global_f32 = store.new_global(false, Value::F32(666.6));
+        let st_global_f64 = store.new_global(false, Value::F64(666.6));
+        let st_table = store.new_table(wasm::RefType::FuncRef, wasm::Limits { min: 10, max: Some(20) }).unwrap();
+        let st_memory = store.new_memory(wasm::Limits { min: 1, max: Some(2) }).unwrap();
         let st_print_i32 = store.new_host_func(|_: i32| ());
         let st_print_i64 = store.new_host_func(|_: i64| ());
         let st_print_f32 = store.new_host_func(|_: f32| ());
@@ -180,16 +184,19 @@ fn main() {
         let st_print_i32_f32 = store.new_host_func(|_: i32, _: f32| ());
         let st_print_f64_f64 = store.new_host_func(|_: f64, _: f64| ());
 
-        let imports = &[
-            ("spectest", "global_i32", st_global_i32.into()),
-            ("spectest", "global_i64", st_global_i64.into()),
-            ("spectest", "print_i32", st_print_i32.into()),
-            ("spectest", "print_i64", st_print_i64.into()),
-            ("spectest", "print_f32", st_print_f32.into()),
-            ("spectest", "print_f64", st_print_f64.into()),
-            ("spectest", "print_i32_f32", st_print_i32_f32.into()),
-            ("spectest", "print_f64_f64", st_print_f64_f64.into()),
-        ];
+        let mut linker = wenjin::Linker::new();
+        linker.define("spectest", "global_i32", st_global_i32);
+        linker.define("spectest", "global_i64", st_global_i64);
+        linker.define("spectest", "global_f32", st_global_f32);
+        linker.define("spectest", "global_f64", st_global_f64);
+        linker.define("spectest", "table", st_table);
+        linker.define("spectest", "memory", st_memory);
+        linker.define("spectest", "print_i32", st_print_i32);
+        linker.define("spectest", "print_i64", st_print_i64);
+        linker.define("spectest", "print_f32", st_print_f32);
+        linker.define("spectest", "print_f64", st_print_f64);
+        linker.define("spectest", "print_i32_f32", st_print_i32_f32);
+        linker.define("spectest", "print_f64_f64", st_print_f64_f64);
 
         let mut reader = Reader::new(bytes);
 
@@ -229,6 +236,7 @@ fn main() {
                         ("i32 constant", E::Leb128Overflow) |
                         ("unexpected end" | "length out of bounds", E::UnexpectedEof) |
                         ("function and code section have inconsistent lengths", E::NumCodesNeNumFuncs) |
+                        ("data count and data section have inconsistent lengths", E::NumDatasNeDataCount) |
                         ("malformed section id", E::InvalidSectionType) |
                         ("malformed mutability", E::InvalidGlobalType) |
                         ("malformed UTF-8 encoding", E::StringNotUtf8) |
@@ -257,11 +265,22 @@ fn main() {
                         ("unknown global", E::InvalidGlobalIdx) |
                         ("unknown local", E::InvalidLocalIdx) |
                         ("global is immutable", E::GlobalNotMutable) |
-                        ("constant expression required", E::InvalidGlobalInit)
+                        ("start function", E::InvalidStartFunc) |
+                        ("undeclared function reference", E::UndeclaredFuncRef) |
+                        ("constant expression required", E::InvalidGlobalInit) |
+                        ("data count section required", E::DataCountRequired)
                         => {
                             return true;
                         }
 
+                        (m, E::InvalidDataIdx) if m.starts_with("unknown data segment") => {
+                            return true;
+                        }
+
+                        (m, E::InvalidElemIdx) if m.starts_with("unknown elem segment") => {
+                            return true;
+                        }
+
                         _ => {
                             println!("failure: incorrect error, {kind} {idx}");
                             println!("  {e:?}");
@@ -279,12 +298,34 @@ fn main() {
             }
         }
 
+        fn check_trap(e: &wenjin::Error, message: &str) -> bool {
+            use wenjin::Error as E;
+            match (message, e.inner()) {
+                ("unreachable", E::TrapUnreachable) |
+                ("out of bounds memory access", E::TrapMemoryBounds) |
+                ("integer divide by zero", E::TrapDivZero) |
+                ("undefined element" | "out of bounds table access", E::TrapTableBounds) |
+                ("indirect call type mismatch", E::TrapCallIndirectTypeMismatch) |
+                ("uninitialized element", E::TrapCallIndirectRefNull) |
+                ("call stack exhausted", E::TrapStackOverflow)
+                => true,
+
+                _ => false,
+            }
+        }
+
         let mut module_idx = 0;
         let mut malformed_idx = 0;
         let mut invalid_idx = 0;
         let mut instance = None;
+        let mut named_instances = std::collections::HashMap::new();
 
         while let Some(op) = reader.next() {
+            let get_instance = |module_name: &str| {
+                if module_name.len() == 0 { instance }
+                else { named_instances.get(module_name).copied().flatten() }
+            };
+
             match op {
                 0x01 => {
                     let idx = module_idx;
@@ -294,8 +335,10 @@ fn main() {
                     let wasm = read_bytes(&mut reader);
                     module_size += wasm.len();
 
+                    let module_name = read_string(&mut reader);
+
                     num_tests += 1;
-                    let inst = store.new_instance(wasm, imports);
+                    let inst = linker.instantiate(&mut store, wasm);
                     match inst {
                         Ok(inst) => {
                             num_successes += 1;
@@ -307,6 +350,10 @@ fn main() {
                             instance = None;
                         }
                     }
+
+                    if module_name.len() > 0 {
+                        named_instances.insert(module_name, instance);
+                    }
                 }
 
                 0x02 => {
@@ -325,7 +372,7 @@ fn main() {
                     }
 
                     num_tests += 1;
-                    let result = store.new_instance(wasm, imports).map(|_| ());
+                    let result = linker.instantiate(&mut store, wasm).map(|_| ());
                     if check_error(result, message, idx, "malformed") {
                         num_successes += 1;
                     }
@@ -341,19 +388,32 @@ fn main() {
                     let message = read_string(&mut reader);
 
                     num_tests += 1;
-                    let result = store.new_instance(wasm, imports).map(|_| ());
+                    let result = linker.instantiate(&mut store, wasm).map(|_| ());
                     if check_error(result, message, idx, "invalid") {
                         num_successes += 1;
                     }
                 }
 
+                0x04 => {
+                    let name = read_string(&mut reader);
+                    let module_name = read_string(&mut reader);
+
+                    let Some(inst) = get_instance(module_name) else {
+                        println!("skipping register {name:?} (missing instance)");
+                        num_skipped += 1;
+                        continue;
+                    };
+                    linker.define_instance(&store, name, inst);
+                }
+
                 0x05 => {
+                    let module_name = read_string(&mut reader);
                     let name = read_string(&mut reader);
 
                     let num_args = read_usize(&mut reader);
                     let args = Vec::from_iter((0..num_args).map(|_| { read_value(&mut reader) }));
 
-                    let Some(inst) = instance else {
+                    let Some(inst) = get_instance(module_name) else {
                         println!("skipping invoke (missing instance)");
                         num_skipped += 1;
                         continue;
@@ -371,6 +431,7 @@ fn main() {
                 }
 
                 0x06 => {
+                    let module_name = read_string(&mut reader);
                     let name = read_string(&mut reader);
 
                     let num_args = read_usize(&mut reader);
@@ -378,7 +439,7 @@ fn main() {
 
                     let message = read_string(&mut reader);
 
-                    let Some(inst) = instance else {
+                    let Some(inst) = get_instance(module_name) else {
                         println!("skipping assert_trap (missing instance)");
                         num_skipped += 1;
                         continue;
@@ -392,27 +453,51 @@ fn main() {
                         continue;
                     };
 
-                    use wenjin::Error as E;
-                    match (message, e) {
-                        ("unreachable", E::TrapUnreachable) |
-                        ("out of bounds memory access", E::TrapMemoryBounds) |
-                        ("integer divide by zero", E::TrapDivZero) |
-                        ("undefined element", E::TrapTableBounds) |
-                        ("indirect call type mismatch", E::TrapCallIndirectTypeMismatch) |
-                        ("uninitialized element", E::TrapCallIndirectRefNull)
-                        => {
-                            num_successes += 1;
-                        }
+                    if check_trap(&e, message) {
+                        num_successes += 1;
+                    }
+                    else {
+                        println!("failure: incorrect trap for {name}({args:?})");
+                        println!("  {e:?}");
+                        println!("  expected {message:?}");
+                    }
+                }
 
-                        _ => {
-                            println!("failure: incorrect trap for {name}({args:?})");
-                            println!("  {e:?}");
-                            println!("  expected {message:?}");
-                        }
+                0x0a => {
+                    let module_name = read_string(&mut reader);
+                    let name = read_string(&mut reader);
+
+                    let num_args = read_usize(&mut reader);
+                    let args = Vec::from_iter((0..num_args).map(|_| { read_value(&mut reader) }));
+
+                    let message = read_string(&mut reader);
+
+                    let Some(inst) = get_instance(module_name) else {
+                        println!("skipping assert_exhaustion (missing instance)");
+                        num_skipped += 1;
+                        continue;
+                    };
+                    let func = store.get_export_func_dyn(inst, name).unwrap();
+
+                    num_tests += 1;
+                    let result = store.call_dyn_ex(func, &args, &mut [], true);
+                    let Err(e) = result else {
+                        println!("failure: exhaustion expected for {name}({args:?}) with error {message:?}");
+                        continue;
+                    };
+
+                    if check_trap(&e, message) {
+                        num_successes += 1;
+                    }
+                    else {
+                        println!("failure: incorrect exhaustion error for {name}({args:?})");
+                        println!("  {e:?}");
+                        println!("  expected {message:?}");
                     }
                 }
 
                 0x07 => {
+                    let module_name = read_string(&mut reader);
                     let name = read_string(&mut reader);
 
                     let num_args = read_usize(&mut reader);
@@ -422,7 +507,7 @@ fn main() {
                     let rets = Vec::from_iter((0..num_rets).map(|_| { read_value(&mut reader) }));
 
                     let mut actual_rets = Vec::from_iter((0..num_rets).map(|_| Value::I32(0)));
-                    let Some(inst) = instance else {
+                    let Some(inst) = get_instance(module_name) else {
                         println!("skipping assert_return (missing instance)");
                         num_skipped += 1;
                         continue;
@@ -459,6 +544,60 @@ fn main() {
                     }
                 }
 
+                0x08 => {
+                    let idx = module_idx;
+                    module_idx += 1;
+                    println!("module {idx} (trapping)");
+
+                    let wasm = read_bytes(&mut reader);
+                    module_size += wasm.len();
+
+                    let message = read_string(&mut reader);
+
+                    num_tests += 1;
+                    let result = linker.instantiate(&mut store, wasm);
+                    let Err(e) = result else {
+                        println!("failure: module {idx} should trap with error {message:?}");
+                        continue;
+                    };
+
+                    if check_trap(&e, message) {
+                        num_successes += 1;
+                    }
+                    else {
+                        println!("failure: incorrect trap for module {idx}");
+                        println!("  {e:?}");
+                        println!("  expected {message:?}");
+                    }
+                }
+
+                0x09 => {
+                    let idx = module_idx;
+                    module_idx += 1;
+                    println!("module {idx} (unlinkable)");
+
+                    let wasm = read_bytes(&mut reader);
+                    module_size += wasm.len();
+
+                    let message = read_string(&mut reader);
+
+                    num_tests += 1;
+                    match linker.instantiate(&mut store, wasm) {
+                        Err(wenjin::Error::Link(_)) => {
+                            num_successes += 1;
+                        }
+
+                        Err(e) => {
+                            println!("failure: module {idx} should be unlinkable with error {message:?}");
+                            println!("  but got error {e:?}");
+                        }
+
+                        Ok(_) => {
+                            println!("failure: module {idx} should be unlinkable with error {message:?}");
+                        }
+                    }
+                }
+
                 _ => unimplemented!()
             }
         }
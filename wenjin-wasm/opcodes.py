@@ -192,8 +192,16 @@ opcodes = [
     ["ref_null",                [0xd0],         [],                 [],                     [],         "c"         ],
     ["ref_is_null",             [0xd1],         [],                 [],                     [],         "c"         ],
     ["ref_func",                [0xd2],         ["func"],           [],                     [],         "c"         ],
+    ["memory_init",             [0xfc,   8],    ["data", "mem"],    [],                     [],         "c#"        ],
+    ["data_drop",               [0xfc,   9],    ["data"],           [],                     [],         "c"         ],
     ["memory_copy",             [0xfc,  10],    ["mem", "mem"],     [],                     [],         "c"         ],
     ["memory_fill",             [0xfc,  11],    ["mem"],            [],                     [],         "c"         ],
+    ["table_init",              [0xfc,  12],    ["elem", "tab"],    [],                     [],         "c#"        ],
+    ["elem_drop",               [0xfc,  13],    ["elem"],           [],                     [],         "c"         ],
+    ["table_copy",              [0xfc,  14],    ["tab", "tab"],     [],                     [],         "c#"        ],
+    ["table_grow",              [0xfc,  15],    ["tab"],            [],                     [],         "c"         ],
+    ["table_size",              [0xfc,  16],    ["tab"],            [],                     [],         "c"         ],
+    ["table_fill",              [0xfc,  17],    ["tab"],            [],                     [],         "c#"        ],
 ]
 
 
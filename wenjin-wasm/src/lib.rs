@@ -52,8 +52,10 @@ pub enum ErrorKind {
     ElementSectionLimit,
     DataSectionLimit,
     CustomSectionLimit,
+    InvalidNameSection,
     FuncSectionNotBeforeCode,
     NumCodesNeNumFuncs,
+    NumDatasNeDataCount,
     TooManyLocals,
     UnsupportedOperator,
 
@@ -74,6 +76,9 @@ pub enum ErrorKind {
     InvalidMemoryIdx,
     InvalidGlobalIdx,
     InvalidLocalIdx,
+    InvalidElemIdx,
+    InvalidDataIdx,
+    DataCountRequired,
     InvalidGlobalInit,
     NonIdIfWithoutElse,
     BrTableInvalidTargetTypes { label: u32 },
@@ -82,7 +87,10 @@ pub enum ErrorKind {
     SelectTypeMismatch(ValueType, ValueType),
     GlobalNotMutable,
     AlignTooLarge,
+    OffsetTooLarge,
     LoadStoreRefType,
+    InvalidStartFunc,
+    UndeclaredFuncRef,
 
     Todo,
 }
@@ -123,6 +131,20 @@ impl ValueType {
         })
     }
 
+    pub fn to_u8(self) -> u8 {
+        match self {
+            ValueType::I32 => 0x7f,
+            ValueType::I64 => 0x7e,
+            ValueType::F32 => 0x7d,
+            ValueType::F64 => 0x7c,
+
+            ValueType::V128 => 0x7b,
+
+            ValueType::FuncRef   => 0x70,
+            ValueType::ExternRef => 0x6f,
+        }
+    }
+
     pub fn is_ref(self) -> bool {
         use ValueType::*;
         match self {
@@ -207,8 +229,25 @@ pub struct FuncType<'a> {
 
 #[derive(Clone, Copy, Debug)]
 pub struct Limits {
-    pub min: u32,
-    pub max: Option<u32>,
+    pub min: u64,
+    pub max: Option<u64>,
+}
+
+impl Limits {
+    /// whether an extern with limits `self` can be used
+    /// for an import with limits `expected`.
+    /// - per the spec, `self.min` should be the extern's current size.
+    pub fn matches(self, expected: Limits) -> bool {
+        if self.min < expected.min {
+            return false;
+        }
+
+        match (self.max, expected.max) {
+            (_, None) => true,
+            (Some(max), Some(expected_max)) => max <= expected_max,
+            (None, Some(_)) => false,
+        }
+    }
 }
 
 impl Default for Limits {
@@ -229,6 +268,17 @@ pub struct TableType {
 #[derive(Clone, Copy, Debug)]
 pub struct MemoryType {
     pub limits: Limits,
+    /// whether the memory has `i64` addresses.
+    /// - from the memory64 proposal.
+    pub is64: bool,
+}
+
+impl MemoryType {
+    /// the type of the memory's addresses & sizes.
+    #[inline]
+    pub fn addr_type(self) -> ValueType {
+        if self.is64 { ValueType::I64 } else { ValueType::I32 }
+    }
 }
 
 
@@ -274,6 +324,7 @@ pub enum ConstExpr {
     F64(f64),
     Global(GlobalIdx),
     RefNull(RefType),
+    RefFunc(FuncIdx),
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -302,23 +353,37 @@ pub enum ExportKind {
 pub struct Element<'a> {
     pub ty: RefType,
     pub kind: ElementKind,
-    pub values: &'a [u32],
+    /// `ref.func`, `ref.null` or `global.get` expressions.
+    /// - segments of function indices have `ref.func` values.
+    pub values: &'a [ConstExpr],
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum ElementKind {
     Passive,
-    Active { table: TableIdx, offset: u32 },
+    /// - `offset` is an `i32.const` or `global.get`.
+    Active { table: TableIdx, offset: ConstExpr },
     Declarative,
 }
 
 
 #[derive(Clone, Copy, Debug)]
 pub struct Code<'a> {
+    /// the function body, starting at the local declarations.
+    pub body: SubSection,
     pub locals: &'a [ValueType],
     pub expr: SubSection,
 }
 
+/// the immediates of loads & stores.
+/// - `align` is the alignment's log2.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MemArg {
+    pub align: u32,
+    pub mem: MemoryIdx,
+    pub offset: u64,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct BrTable<'a> {
     pub num_labels: u32,
@@ -342,7 +407,8 @@ pub struct Data<'a> {
 #[derive(Clone, Copy, Debug)]
 pub enum DataKind {
     Passive,
-    Active { mem: MemoryIdx, offset: u32 },
+    /// - `offset` is an `i32.const` or `global.get`.
+    Active { mem: MemoryIdx, offset: ConstExpr },
 }
 
 
@@ -414,6 +480,72 @@ impl<'a> core::fmt::Debug for CustomSection<'a> {
 }
 
 
+/// the decoded `name` custom section.
+/// - name maps are sorted by index.
+/// - label, type, element, field & tag names aren't decoded.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NameSection<'a> {
+    pub module:   Option<&'a str>,
+    pub funcs:    &'a [Name<'a>],
+    pub locals:   &'a [IndirectNames<'a>],
+    pub tables:   &'a [Name<'a>],
+    pub memories: &'a [Name<'a>],
+    pub globals:  &'a [Name<'a>],
+    pub datas:    &'a [Name<'a>],
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Name<'a> {
+    pub idx:  u32,
+    pub name: &'a str,
+}
+
+/// the names of the locals of a function.
+#[derive(Clone, Copy, Debug)]
+pub struct IndirectNames<'a> {
+    pub idx:   u32,
+    pub names: &'a [Name<'a>],
+}
+
+impl<'a> NameSection<'a> {
+    #[inline]
+    pub fn find(names: &[Name<'a>], idx: u32) -> Option<&'a str> {
+        let i = names.binary_search_by_key(&idx, |n| n.idx).ok()?;
+        return Some(names[i].name);
+    }
+
+    #[inline]
+    pub fn func(&self, idx: FuncIdx) -> Option<&'a str> {
+        Self::find(self.funcs, idx)
+    }
+
+    pub fn local(&self, func: FuncIdx, idx: u32) -> Option<&'a str> {
+        let i = self.locals.binary_search_by_key(&func, |n| n.idx).ok()?;
+        return Self::find(self.locals[i].names, idx);
+    }
+
+    #[inline]
+    pub fn table(&self, idx: TableIdx) -> Option<&'a str> {
+        Self::find(self.tables, idx)
+    }
+
+    #[inline]
+    pub fn memory(&self, idx: MemoryIdx) -> Option<&'a str> {
+        Self::find(self.memories, idx)
+    }
+
+    #[inline]
+    pub fn global(&self, idx: GlobalIdx) -> Option<&'a str> {
+        Self::find(self.globals, idx)
+    }
+
+    #[inline]
+    pub fn data(&self, idx: u32) -> Option<&'a str> {
+        Self::find(self.datas, idx)
+    }
+}
+
+
 #[derive(Clone, Copy, Debug)]
 pub struct ModuleLimits {
     pub max_types:    u32,
@@ -479,8 +611,18 @@ pub struct Module<'a> {
     pub start:      Option<FuncIdx>,
     pub elements:   &'a [Element<'a>],
     pub codes:      &'a [Code<'a>],
+    /// the contents of the code section.
+    /// - dwarf addresses are relative to its start.
+    pub code_section: Option<SubSection>,
     pub datas:      &'a [Data<'a>],
+    /// from the data count section.
+    /// - required by `memory.init` & `data.drop`.
+    pub data_count: Option<u32>,
     pub customs:    &'a [CustomSection<'a>],
+    pub names:      NameSection<'a>,
+    /// the first error in the `name` section.
+    /// - malformed names don't make the module invalid.
+    pub names_error: Option<Error>,
 }
 
 impl<'a> Module<'a> {
@@ -526,6 +668,16 @@ impl<'a> Module<'a> {
             }
         }
     }
+
+    /// whether `ref.func` may refer to the function in code.
+    /// - functions are declared by exports, element segments & global initializers.
+    pub fn is_func_declared(&self, idx: FuncIdx) -> bool {
+        let is_ref = |expr: &ConstExpr| matches!(expr, ConstExpr::RefFunc(i) if *i == idx);
+
+        return self.exports.iter().any(|e| matches!(e.kind, ExportKind::Func(i) if i == idx))
+            || self.elements.iter().any(|e| e.values.iter().any(is_ref))
+            || self.globals.iter().any(|g| is_ref(&g.init));
+    }
 }
 
 
@@ -215,10 +215,18 @@ pub enum Opcode {
     RefNull,
     RefIsNull,
     RefFunc,
+    MemoryInit,
+    DataDrop,
     MemoryCopy,
     MemoryFill,
+    TableInit,
+    ElemDrop,
+    TableCopy,
+    TableGrow,
+    TableSize,
+    TableFill,
 }
-const NUM_OPCODES: usize = 185;
+const NUM_OPCODES: usize = 193;
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Prefix {
@@ -485,8 +493,16 @@ const PARSE: &[ParseResult; 256] = &[
 #[inline]
 fn parse_xfc(v: u32) -> Option<Opcode> {
     Some(match v {
+        8 => Opcode::MemoryInit,
+        9 => Opcode::DataDrop,
         10 => Opcode::MemoryCopy,
         11 => Opcode::MemoryFill,
+        12 => Opcode::TableInit,
+        13 => Opcode::ElemDrop,
+        14 => Opcode::TableCopy,
+        15 => Opcode::TableGrow,
+        16 => Opcode::TableSize,
+        17 => Opcode::TableFill,
         _ => return None
     })
 }
@@ -531,8 +547,16 @@ pub enum OpcodeClass {
     RefNull,
     RefIsNull,
     RefFunc,
+    MemoryInit,
+    DataDrop,
     MemoryCopy,
     MemoryFill,
+    TableInit,
+    ElemDrop,
+    TableCopy,
+    TableGrow,
+    TableSize,
+    TableFill,
 }
 const CLASS: &[OpcodeClass; NUM_OPCODES] = &[
     OpcodeClass::Unreachable,
@@ -718,7 +742,15 @@ const CLASS: &[OpcodeClass; NUM_OPCODES] = &[
     OpcodeClass::RefNull,
     OpcodeClass::RefIsNull,
     OpcodeClass::RefFunc,
+    OpcodeClass::MemoryInit,
+    OpcodeClass::DataDrop,
     OpcodeClass::MemoryCopy,
     OpcodeClass::MemoryFill,
+    OpcodeClass::TableInit,
+    OpcodeClass::ElemDrop,
+    OpcodeClass::TableCopy,
+    OpcodeClass::TableGrow,
+    OpcodeClass::TableSize,
+    OpcodeClass::TableFill,
 ];
 
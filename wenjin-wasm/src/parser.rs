@@ -2,10 +2,10 @@ use sti::reader::Reader;
 use sti::arena::Arena;
 use sti::vec::Vec;
 
-use crate::{leb128, BrTable, Error, ErrorKind, Result, TypeIdx, FuncIdx, TableIdx, MemoryIdx, GlobalIdx};
+use crate::{leb128, BrTable, MemArg, Error, ErrorKind, Result, TypeIdx, FuncIdx, TableIdx, MemoryIdx, GlobalIdx};
 use crate::{ValueType, RefType, FuncType, BlockType, Limits, TableType, MemoryType, GlobalType};
 use crate::{Import, ImportKind, Imports, Global, Export, ExportKind, Element, ElementKind, Code, Data, DataKind};
-use crate::{SubSection, Section, SectionKind, CustomSection};
+use crate::{SubSection, Section, SectionKind, CustomSection, NameSection, Name, IndirectNames};
 use crate::ConstExpr;
 use crate::{ModuleLimits, Module};
 use crate::opcode::{self, Opcode};
@@ -89,6 +89,12 @@ impl<'a> Parser<'a> {
             .map_err(|e| self.error(e.into()))
     }
 
+    #[inline]
+    pub fn parse_u64(&mut self) -> Result<u64> {
+        leb128::decode_u64(&mut self.reader)
+            .map_err(|e| self.error(e.into()))
+    }
+
     #[inline]
     pub fn parse_length(&mut self) -> Result<usize> {
         return Ok(self.parse_u32()? as usize);
@@ -176,6 +182,24 @@ impl<'a> Parser<'a> {
 
     #[inline] pub fn parse_global_idx(&mut self) -> Result<GlobalIdx> { self.parse_u32() }
 
+    #[inline] pub fn parse_elem_idx(&mut self) -> Result<u32> { self.parse_u32() }
+
+    #[inline] pub fn parse_data_idx(&mut self) -> Result<u32> { self.parse_u32() }
+
+    /// - the memory index is only encoded,
+    ///   if bit 6 of the alignment is set.
+    /// - the offset is a `u64`, as memories can have `i64` addresses.
+    pub fn parse_memarg(&mut self) -> Result<MemArg> {
+        let mut align = self.parse_u32()?;
+        let mut mem = 0;
+        if align & 0x40 != 0 {
+            align &= !0x40;
+            mem = self.parse_memory_idx()?;
+        }
+        let offset = self.parse_u64()?;
+        return Ok(MemArg { align, mem, offset });
+    }
+
     pub fn parse_br_table(&mut self) -> Result<BrTable> {
         let num_labels = self.parse_u32()?;
 
@@ -200,8 +224,8 @@ impl<'a> Parser<'a> {
 
     pub fn parse_limits(&mut self) -> Result<Limits> {
         return Ok(match self.next()? {
-            0x00 => Limits { min: self.parse_u32()?, max: None },
-            0x01 => Limits { min: self.parse_u32()?, max: Some(self.parse_u32()?) },
+            0x00 => Limits { min: self.parse_u32()? as u64, max: None },
+            0x01 => Limits { min: self.parse_u32()? as u64, max: Some(self.parse_u32()? as u64) },
 
             _ => return Err(self.error(ErrorKind::InvalidLimits))
         });
@@ -213,8 +237,23 @@ impl<'a> Parser<'a> {
         return Ok(TableType { ty, limits });
     }
 
+    /// - bit 2 of the limits' flags marks memories with `i64` addresses.
+    ///   their limits are encoded as `u64`s.
     pub fn parse_memory_type(&mut self) -> Result<MemoryType> {
-        return Ok(MemoryType { limits: self.parse_limits()? });
+        let flags = self.next()?;
+        if flags & !0x05 != 0 {
+            return Err(self.error(ErrorKind::InvalidLimits));
+        }
+
+        let is64 = flags & 0x04 != 0;
+        let min = self.parse_addr(is64)?;
+        let max = if flags & 0x01 != 0 { Some(self.parse_addr(is64)?) } else { None };
+        return Ok(MemoryType { limits: Limits { min, max }, is64 });
+    }
+
+    #[inline]
+    fn parse_addr(&mut self, is64: bool) -> Result<u64> {
+        if is64 { self.parse_u64() } else { Ok(self.parse_u32()? as u64) }
     }
 
     pub fn parse_global_type(&mut self) -> Result<GlobalType> {
@@ -264,6 +303,96 @@ impl<'a> Parser<'a> {
         return Ok(CustomSection { name, data });
     }
 
+    /// parses the contents of the `name` custom section.
+    /// - a subsection with an error is skipped.
+    ///   the first error is returned with the names that could be decoded.
+    pub fn parse_name_section(&mut self, alloc: &'a Arena) -> (NameSection<'a>, Option<Error>) {
+        let mut names = NameSection::default();
+        let mut error = None;
+
+        let mut prev_id = None;
+        while !self.is_done() {
+            let sub = self.next().and_then(|id| Ok((id, self.parse_sub_section()?)));
+            let (id, sub) = match sub {
+                Ok(sub) => sub,
+                Err(e) => {
+                    // can't find the next subsection.
+                    error.get_or_insert(e);
+                    break;
+                }
+            };
+
+            let mut sp = self.sub_parser(sub);
+            let result = sp.parse_name_subsection(id, prev_id, &mut names, alloc);
+            if let Err(e) = result {
+                error.get_or_insert(e);
+            }
+            prev_id = Some(id);
+        }
+
+        return (names, error);
+    }
+
+    fn parse_name_subsection(&mut self, id: u8, prev_id: Option<u8>, names: &mut NameSection<'a>, alloc: &'a Arena) -> Result<()> {
+        if prev_id.is_some_and(|prev| id <= prev) {
+            return Err(self.error(ErrorKind::InvalidNameSection));
+        }
+
+        match id {
+            0 => names.module   = Some(self.parse_string()?),
+            1 => names.funcs    = self.parse_name_map(alloc)?,
+            2 => names.locals   = self.parse_indirect_name_map(alloc)?,
+            5 => names.tables   = self.parse_name_map(alloc)?,
+            6 => names.memories = self.parse_name_map(alloc)?,
+            7 => names.globals  = self.parse_name_map(alloc)?,
+            9 => names.datas    = self.parse_name_map(alloc)?,
+
+            // labels, types, elements, fields, tags, or unknown.
+            _ => {
+                self.reader.consume(self.reader.len());
+            }
+        }
+
+        if self.reader.len() != 0 {
+            return Err(self.error(ErrorKind::SectionTrailingData));
+        }
+        return Ok(());
+    }
+
+    pub fn parse_name_map(&mut self, alloc: &'a Arena) -> Result<&'a [Name<'a>]> {
+        let len = self.parse_u32()?;
+
+        let mut names: Vec<Name, _> = Vec::new_in(alloc);
+        for _ in 0..len {
+            let idx = self.parse_u32()?;
+            if names.last().is_some_and(|prev| idx <= prev.idx) {
+                return Err(self.error(ErrorKind::InvalidNameSection));
+            }
+
+            let name = self.parse_string()?;
+            names.push(Name { idx, name });
+        }
+
+        return Ok(names.leak());
+    }
+
+    pub fn parse_indirect_name_map(&mut self, alloc: &'a Arena) -> Result<&'a [IndirectNames<'a>]> {
+        let len = self.parse_u32()?;
+
+        let mut maps: Vec<IndirectNames, _> = Vec::new_in(alloc);
+        for _ in 0..len {
+            let idx = self.parse_u32()?;
+            if maps.last().is_some_and(|prev| idx <= prev.idx) {
+                return Err(self.error(ErrorKind::InvalidNameSection));
+            }
+
+            let names = self.parse_name_map(alloc)?;
+            maps.push(IndirectNames { idx, names });
+        }
+
+        return Ok(maps.leak());
+    }
+
     pub fn parse_import(&mut self) -> Result<Import<'a>> {
         let module = self.parse_string()?;
         let name = self.parse_string()?;
@@ -300,58 +429,54 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_element<'out>(&mut self, alloc: &'out Arena) -> Result<Element<'out>> {
-        return Ok(match self.parse_u32()? {
-            0 => {
-                let ConstExpr::I32(offset) = self.parse_const_expr()? else {
-                    return Err(self.error(ErrorKind::Todo));
-                };
-
-                let num_values = self.parse_length()?;
-                let mut values = Vec::with_cap_in(alloc, num_values);
-                for _ in 0..num_values {
-                    values.push(self.parse_u32()?);
-                }
+        // bit 0: passive or declarative.
+        // bit 1: explicit table index, or declarative.
+        // bit 2: element expressions instead of function indices.
+        let flags = self.parse_u32()?;
+        if flags > 7 {
+            return Err(self.error(ErrorKind::InvalidElement));
+        }
 
-                Element {
-                    ty: RefType::FuncRef,
-                    kind: ElementKind::Active { table: 0, offset: offset as u32 },
-                    values: values.leak(),
-                }
+        let kind =
+            if flags & 1 == 0 {
+                let table = if flags & 2 != 0 { self.parse_table_idx()? } else { 0 };
+                let offset = self.parse_const_expr()?;
+                ElementKind::Active { table, offset }
             }
+            else if flags & 2 == 0 { ElementKind::Passive }
+            else { ElementKind::Declarative };
 
-            2 => {
-                let table = self.parse_u32()?;
-
-                let ConstExpr::I32(offset) = self.parse_const_expr()? else {
-                    return Err(self.error(ErrorKind::Todo));
-                };
+        let exprs = flags & 4 != 0;
 
+        let ty =
+            if flags & 3 == 0 { RefType::FuncRef }
+            else if exprs { self.parse_ref_type()? }
+            else {
                 // funcref.
                 if self.reader.expect(0x00).is_err() {
                     return Err(self.error(ErrorKind::InvalidElement));
                 }
-
-                let num_values = self.parse_length()?;
-                let mut values = Vec::with_cap_in(alloc, num_values);
-                for _ in 0..num_values {
-                    values.push(self.parse_u32()?);
-                }
-
-                Element {
-                    ty: RefType::FuncRef,
-                    kind: ElementKind::Active { table, offset: offset as u32 },
-                    values: values.leak(),
-                }
+                RefType::FuncRef
+            };
+
+        let num_values = self.parse_length()?;
+        let mut values = Vec::with_cap_in(alloc, num_values);
+        for _ in 0..num_values {
+            if exprs {
+                values.push(self.parse_const_expr()?);
+            }
+            else {
+                values.push(ConstExpr::RefFunc(self.parse_func_idx()?));
             }
+        }
 
-            _ => return Err(self.error(ErrorKind::Todo))
-        });
+        return Ok(Element { ty, kind, values: values.leak() });
     }
 
     pub fn parse_code<'out>(&mut self, max_locals: u32, alloc: &'out Arena) -> Result<Code<'out>> {
-        let sub = self.parse_sub_section()?;
+        let body = self.parse_sub_section()?;
 
-        let mut p = self.sub_parser(sub);
+        let mut p = self.sub_parser(body);
 
         let num_local_groups = p.parse_u32()?;
 
@@ -372,22 +497,41 @@ impl<'a> Parser<'a> {
             len:    p.reader.len(),
         };
 
-        return Ok(Code { locals: locals.leak(), expr });
+        return Ok(Code { body, locals: locals.leak(), expr });
     }
 
     pub fn parse_data(&mut self) -> Result<Data<'a>> {
         return Ok(match self.parse_u32()? {
             0 => {
                 let offset = self.parse_const_expr()?;
-                let ConstExpr::I32(offset) = offset else {
-                    return Err(self.error(ErrorKind::Todo));
-                };
 
                 let len = self.parse_length()?;
                 let values = self.reader.next_n(len)
                     .ok_or_else(|| self.error(ErrorKind::UnexpectedEof))?;
 
-                let kind = DataKind::Active { mem: 0, offset: offset as u32 };
+                let kind = DataKind::Active { mem: 0, offset };
+
+                Data { kind, values }
+            }
+
+            1 => {
+                let len = self.parse_length()?;
+                let values = self.reader.next_n(len)
+                    .ok_or_else(|| self.error(ErrorKind::UnexpectedEof))?;
+
+                Data { kind: DataKind::Passive, values }
+            }
+
+            2 => {
+                let mem = self.parse_memory_idx()?;
+
+                let offset = self.parse_const_expr()?;
+
+                let len = self.parse_length()?;
+                let values = self.reader.next_n(len)
+                    .ok_or_else(|| self.error(ErrorKind::UnexpectedEof))?;
+
+                let kind = DataKind::Active { mem, offset };
 
                 Data { kind, values }
             }
@@ -405,6 +549,7 @@ impl<'a> Parser<'a> {
             Opcode::F64Const => ConstExpr::F64(self.parse_f64()?),
             Opcode::GlobalGet => ConstExpr::Global(self.parse_global_idx()?),
             Opcode::RefNull  => ConstExpr::RefNull(self.parse_ref_type()?),
+            Opcode::RefFunc  => ConstExpr::RefFunc(self.parse_func_idx()?),
 
             _ => return Err(self.error(ErrorKind::InvalidConstExpr))
         };
@@ -416,6 +561,40 @@ impl<'a> Parser<'a> {
         return Ok(result);
     }
 
+    /// the type of a constant expression.
+    /// - `global.get` can only refer to immutable imported globals.
+    fn const_expr_type(&self, module: &Module, expr: ConstExpr) -> Result<ValueType> {
+        return Ok(match expr {
+            ConstExpr::I32(_) => ValueType::I32,
+            ConstExpr::I64(_) => ValueType::I64,
+            ConstExpr::F32(_) => ValueType::F32,
+            ConstExpr::F64(_) => ValueType::F64,
+            ConstExpr::Global(idx) => {
+                let g = module.imports.globals.get(idx as usize).copied()
+                    .ok_or_else(|| self.error(ErrorKind::InvalidGlobalIdx))?;
+                if g.mutable {
+                    return Err(self.error(ErrorKind::InvalidGlobalInit));
+                }
+                g.ty
+            }
+            ConstExpr::RefNull(ty) => ty.to_value_type(),
+            ConstExpr::RefFunc(idx) => {
+                if module.get_func(idx).is_none() {
+                    return Err(self.error(ErrorKind::InvalidFuncIdx));
+                }
+                ValueType::FuncRef
+            }
+        });
+    }
+
+    fn expect_const_expr_type(&self, module: &Module, expr: ConstExpr, expected: ValueType) -> Result<()> {
+        let found = self.const_expr_type(module, expr)?;
+        if found != expected {
+            return Err(self.error(ErrorKind::TypeMismatch { expected, found }));
+        }
+        return Ok(());
+    }
+
     pub fn parse_opcode(&mut self) -> Result<Opcode> {
         let at = self.next()?;
         match Opcode::parse(at) {
@@ -453,6 +632,7 @@ impl<'a> Parser<'a> {
         let mut module = Module::default();
 
         let mut customs = Vec::new_in(alloc);
+        let mut has_names = false;
 
         while !p.is_done() {
             let section = p.parse_section()?;
@@ -470,7 +650,17 @@ impl<'a> Parser<'a> {
                         return Err(sp.error(ErrorKind::CustomSectionLimit));
                     }
 
-                    customs.push(sp.parse_custom_section()?);
+                    let custom = sp.parse_custom_section()?;
+                    if custom.name == "name" && !has_names {
+                        has_names = true;
+
+                        let mut np = sp.sub_parser(SubSection {
+                            offset: section.sub.offset + section.sub.len - custom.data.len(),
+                            len: custom.data.len(),
+                        });
+                        (module.names, module.names_error) = np.parse_name_section(alloc);
+                    }
+                    customs.push(custom);
                 }
 
                 SectionKind::Type => {
@@ -599,22 +789,7 @@ impl<'a> Parser<'a> {
                     for _ in 0..num_globals {
                         let global = sp.parse_global()?;
 
-                        let init_ty = match global.init {
-                            ConstExpr::I32(_) => ValueType::I32,
-                            ConstExpr::I64(_) => ValueType::I64,
-                            ConstExpr::F32(_) => ValueType::F32,
-                            ConstExpr::F64(_) => ValueType::F64,
-                            ConstExpr::Global(idx) => {
-                                let idx = idx as usize;
-                                let g = module.imports.globals.get(idx).copied()
-                                    .ok_or_else(|| sp.error(ErrorKind::InvalidGlobalIdx))?;
-                                if g.mutable {
-                                    return Err(sp.error(ErrorKind::InvalidGlobalInit));
-                                }
-                                g.ty
-                            }
-                            ConstExpr::RefNull(ty) => ty.to_value_type(),
-                        };
+                        let init_ty = sp.const_expr_type(&module, global.init)?;
                         if init_ty != global.ty.ty {
                             return Err(sp.error(ErrorKind::InvalidGlobalInit));
                         }
@@ -666,7 +841,15 @@ impl<'a> Parser<'a> {
                 }
 
                 SectionKind::Start => {
-                    module.start = Some(sp.parse_u32()?);
+                    let idx = sp.parse_u32()?;
+                    let Some(ty) = module.get_func(idx) else {
+                        return Err(sp.error(ErrorKind::InvalidFuncIdx));
+                    };
+                    let ty = module.types[ty as usize];
+                    if ty.params.len() != 0 || ty.rets.len() != 0 {
+                        return Err(sp.error(ErrorKind::InvalidStartFunc));
+                    }
+                    module.start = Some(idx);
                 }
 
                 SectionKind::Element => {
@@ -680,22 +863,28 @@ impl<'a> Parser<'a> {
                         let elem = sp.parse_element(alloc)?;
                         match elem.kind {
                             ElementKind::Passive => (),
-                            ElementKind::Active { table, offset: _ } => {
-                                if module.get_table(table).is_none() {
+                            ElementKind::Active { table, offset } => {
+                                let Some(table) = module.get_table(table) else {
                                     return Err(sp.error(ErrorKind::InvalidTableIdx));
+                                };
+                                if table.ty != elem.ty {
+                                    return Err(sp.error(ErrorKind::TypeMismatch {
+                                        expected: table.ty.to_value_type(),
+                                        found: elem.ty.to_value_type() }));
                                 }
+                                sp.expect_const_expr_type(&module, offset, ValueType::I32)?;
                             }
                             ElementKind::Declarative => (),
                         }
-                        match elem.ty {
-                            RefType::FuncRef => {
-                                for idx in elem.values {
-                                    if module.get_func(*idx).is_none() {
-                                        return Err(sp.error(ErrorKind::InvalidFuncIdx));
-                                    }
-                                }
+                        for value in elem.values {
+                            match value {
+                                ConstExpr::Global(_) |
+                                ConstExpr::RefNull(_) |
+                                ConstExpr::RefFunc(_) => (),
+
+                                _ => return Err(sp.error(ErrorKind::InvalidConstExpr)),
                             }
-                            RefType::ExternRef => (),
+                            sp.expect_const_expr_type(&module, *value, elem.ty.to_value_type())?;
                         }
                         elements.push(elem);
                     }
@@ -715,6 +904,7 @@ impl<'a> Parser<'a> {
                     }
 
                     module.codes = codes.leak();
+                    module.code_section = Some(section.sub);
                 }
 
                 SectionKind::Data => {
@@ -729,10 +919,11 @@ impl<'a> Parser<'a> {
                         match data.kind {
                             DataKind::Passive => (),
 
-                            DataKind::Active { mem, offset: _ } => {
-                                if module.get_memory(mem).is_none() {
+                            DataKind::Active { mem, offset } => {
+                                let Some(mem) = module.get_memory(mem) else {
                                     return Err(sp.error(ErrorKind::InvalidMemoryIdx));
-                                }
+                                };
+                                sp.expect_const_expr_type(&module, offset, mem.addr_type())?;
                             }
                         }
                         datas.push(data);
@@ -742,8 +933,7 @@ impl<'a> Parser<'a> {
                 }
 
                 SectionKind::DataCount => {
-                    // @todo: validate.
-                    sp.parse_u32()?;
+                    module.data_count = Some(sp.parse_u32()?);
                 }
             }
 
@@ -756,6 +946,12 @@ impl<'a> Parser<'a> {
             return Err(p.error(ErrorKind::NumCodesNeNumFuncs));
         }
 
+        if let Some(data_count) = module.data_count {
+            if data_count as usize != module.datas.len() {
+                return Err(p.error(ErrorKind::NumDatasNeDataCount));
+            }
+        }
+
         module.customs = customs.leak();
 
         return Ok(module);
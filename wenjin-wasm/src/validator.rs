@@ -3,7 +3,7 @@ use sti::vec::Vec;
 use sti::hash::HashMap;
 
 use crate::{Result, Error, ErrorKind};
-use crate::{ValueType, BlockType, TypeIdx, FuncIdx, TableIdx, MemoryIdx, GlobalIdx, Module, TableType, FuncType, RefType, GlobalType, MemoryType};
+use crate::{ValueType, BlockType, TypeIdx, FuncIdx, TableIdx, MemoryIdx, GlobalIdx, Module, TableType, FuncType, RefType, GlobalType, MemoryType, Element};
 
 
 pub const DEFAULT_STACK_LIMIT: u32 = 1024;
@@ -16,6 +16,9 @@ pub struct Validator<'a> {
     pub module: &'a Module<'a>,
 
     pos: usize,
+    stop_at: usize,
+    record_offsets: bool,
+    offsets: Vec<u32>,
 
     locals: Vec<ValueType>,
     stack: Vec<ValueType>,
@@ -54,6 +57,9 @@ impl<'a> Validator<'a> {
             frame_limit: DEFAULT_FRAME_LIMIT,
             module,
             pos: 0,
+            stop_at: usize::MAX,
+            record_offsets: false,
+            offsets: Vec::new(),
             locals: Vec::new(),
             stack: Vec::new(),
             max_stack: 0,
@@ -231,6 +237,23 @@ impl<'a> Validator<'a> {
             .ok_or_else(|| self.error(ErrorKind::InvalidGlobalIdx))
     }
 
+    fn elem(&self, idx: u32) -> Result<Element<'a>> {
+        self.module.elements.get(idx as usize).copied()
+            .ok_or_else(|| self.error(ErrorKind::InvalidElemIdx))
+    }
+
+    // the data count section makes the data segments
+    // known before the code section.
+    fn data(&self, idx: u32) -> Result<()> {
+        let Some(data_count) = self.module.data_count else {
+            return Err(self.error(ErrorKind::DataCountRequired));
+        };
+        if idx >= data_count {
+            return Err(self.error(ErrorKind::InvalidDataIdx));
+        }
+        return Ok(());
+    }
+
 
     #[inline]
     fn block_begin_types(&self, ty: BlockType) -> &'a [ValueType] {
@@ -323,6 +346,13 @@ impl<'a> Validator<'a> {
 
         while !parser.is_done() {
             self.pos = parser.reader.offset();
+            if self.pos == self.stop_at {
+                return Ok(());
+            }
+            if self.record_offsets {
+                self.offsets.push(self.pos as u32);
+            }
+
             let opcode = parser.parse_opcode()?;
 
             use crate::opcode::OpcodeClass;
@@ -333,18 +363,22 @@ impl<'a> Validator<'a> {
                 }
 
                 OpcodeClass::Mem { max_align, pop, push } => {
-                    let align = parser.parse_u32()?;
-                    let _offset = parser.parse_u32()?;
+                    let memarg = parser.parse_memarg()?;
 
-                    self.memory(0)?;
+                    let mem = self.memory(memarg.mem)?;
+                    if !mem.is64 && memarg.offset > u32::MAX as u64 {
+                        return Err(self.error(ErrorKind::OffsetTooLarge));
+                    }
 
-                    let align = 1u32.checked_shl(align)
+                    let align = 1u32.checked_shl(memarg.align)
                         .ok_or_else(|| self.error(ErrorKind::AlignTooLarge))?;
                     if align > max_align as u32 {
                         return Err(self.error(ErrorKind::AlignTooLarge));
                     }
 
-                    self.expect_n(pop)?;
+                    // the address is the first operand.
+                    self.expect_n(&pop[1..])?;
+                    self.expect(mem.addr_type())?;
                     self.push_n(push)?;
                 }
 
@@ -589,28 +623,30 @@ impl<'a> Validator<'a> {
                 }
 
                 OpcodeClass::TableGet => {
-                    let idx = parser.parse_u32()?;
-                    let _ = idx;
-                    return Err(self.error(ErrorKind::Todo));
+                    let idx = parser.parse_table_idx()?;
+                    let table = self.table(idx)?;
+                    self.expect(ValueType::I32)?;
+                    self.push(table.ty.to_value_type())?;
                 }
 
                 OpcodeClass::TableSet => {
-                    let idx = parser.parse_u32()?;
-                    let _ = idx;
-                    return Err(self.error(ErrorKind::Todo));
+                    let idx = parser.parse_table_idx()?;
+                    let table = self.table(idx)?;
+                    self.expect(table.ty.to_value_type())?;
+                    self.expect(ValueType::I32)?;
                 }
 
                 OpcodeClass::MemorySize => {
                     let mem = parser.parse_memory_idx()?;
-                    self.memory(mem)?;
-                    self.push(ValueType::I32)?;
+                    let mem = self.memory(mem)?;
+                    self.push(mem.addr_type())?;
                 }
 
                 OpcodeClass::MemoryGrow => {
                     let mem = parser.parse_memory_idx()?;
-                    self.memory(mem)?;
-                    self.expect(ValueType::I32)?;
-                    self.push(ValueType::I32)?;
+                    let mem = self.memory(mem)?;
+                    self.expect(mem.addr_type())?;
+                    self.push(mem.addr_type())?;
                 }
 
                 OpcodeClass::I32Const => {
@@ -658,26 +694,105 @@ impl<'a> Validator<'a> {
                 }
 
                 OpcodeClass::RefFunc => {
-                    return Err(self.error(ErrorKind::Todo));
+                    let idx = parser.parse_func_idx()?;
+                    self.func(idx)?;
+                    if !self.module.is_func_declared(idx) {
+                        return Err(self.error(ErrorKind::UndeclaredFuncRef));
+                    }
+                    self.push(ValueType::FuncRef)?;
                 }
 
                 OpcodeClass::MemoryCopy => {
                     let dst = parser.parse_memory_idx()?;
                     let src = parser.parse_memory_idx()?;
-                    self.memory(dst)?;
-                    self.memory(src)?;
+                    let dst = self.memory(dst)?;
+                    let src = self.memory(src)?;
+                    // the length fits both memories.
+                    let len = if dst.is64 && src.is64 { ValueType::I64 } else { ValueType::I32 };
+                    self.expect(len)?;
+                    self.expect(src.addr_type())?;
+                    self.expect(dst.addr_type())?;
+                }
+
+                OpcodeClass::MemoryFill => {
+                    let mem = parser.parse_memory_idx()?;
+                    let mem = self.memory(mem)?;
+                    self.expect(mem.addr_type())?;
                     self.expect(ValueType::I32)?;
+                    self.expect(mem.addr_type())?;
+                }
+
+                OpcodeClass::MemoryInit => {
+                    let data = parser.parse_data_idx()?;
+                    let mem = parser.parse_memory_idx()?;
+                    let mem = self.memory(mem)?;
+                    self.data(data)?;
                     self.expect(ValueType::I32)?;
                     self.expect(ValueType::I32)?;
+                    self.expect(mem.addr_type())?;
                 }
 
-                OpcodeClass::MemoryFill => {
-                    let mem = parser.parse_memory_idx()?;
-                    self.memory(mem)?;
+                OpcodeClass::DataDrop => {
+                    let data = parser.parse_data_idx()?;
+                    self.data(data)?;
+                }
+
+                OpcodeClass::TableInit => {
+                    let elem = parser.parse_elem_idx()?;
+                    let table = parser.parse_table_idx()?;
+                    let table = self.table(table)?;
+                    let elem = self.elem(elem)?;
+                    if elem.ty != table.ty {
+                        return Err(self.error(ErrorKind::TypeMismatch {
+                            expected: table.ty.to_value_type(),
+                            found: elem.ty.to_value_type() }));
+                    }
                     self.expect(ValueType::I32)?;
                     self.expect(ValueType::I32)?;
                     self.expect(ValueType::I32)?;
                 }
+
+                OpcodeClass::ElemDrop => {
+                    let elem = parser.parse_elem_idx()?;
+                    self.elem(elem)?;
+                }
+
+                OpcodeClass::TableCopy => {
+                    let dst = parser.parse_table_idx()?;
+                    let src = parser.parse_table_idx()?;
+                    let dst = self.table(dst)?;
+                    let src = self.table(src)?;
+                    if src.ty != dst.ty {
+                        return Err(self.error(ErrorKind::TypeMismatch {
+                            expected: dst.ty.to_value_type(),
+                            found: src.ty.to_value_type() }));
+                    }
+                    self.expect(ValueType::I32)?;
+                    self.expect(ValueType::I32)?;
+                    self.expect(ValueType::I32)?;
+                }
+
+                OpcodeClass::TableGrow => {
+                    let idx = parser.parse_table_idx()?;
+                    let table = self.table(idx)?;
+                    self.expect(ValueType::I32)?;
+                    self.expect(table.ty.to_value_type())?;
+                    self.push(ValueType::I32)?;
+                }
+
+                OpcodeClass::TableSize => {
+                    let idx = parser.parse_table_idx()?;
+                    self.table(idx)?;
+                    self.push(ValueType::I32)?;
+                }
+
+                OpcodeClass::TableFill => {
+                    let idx = parser.parse_table_idx()?;
+                    let table = self.table(idx)?;
+                    self.expect(ValueType::I32)?;
+                    self.expect(table.ty.to_value_type())?;
+                    self.expect(ValueType::I32)?;
+                }
             }
         }
         self.pos = parser.reader.offset();
@@ -694,6 +809,42 @@ impl<'a> Validator<'a> {
         return Ok(());
     }
 
+    /// validates a function up to the instruction at `offset`.
+    /// - returns whether `offset` is the start of an instruction.
+    ///   if so, `stack` holds the operand types before that instruction.
+    /// - `offset` is relative to the parser's input, like `Error::pos`.
+    pub fn validate_func_until
+        (&mut self,
+         parser: &mut crate::Parser,
+         func_ty: TypeIdx,
+         locals: &[ValueType],
+         offset: usize)
+        -> Result<bool>
+    {
+        self.stop_at = offset;
+        let result = self.validate_func(parser, func_ty, locals, None);
+        self.stop_at = usize::MAX;
+        result?;
+        return Ok(self.pos == offset);
+    }
+
+    /// validates a function & returns the offsets of its instructions.
+    /// - the offsets are relative to the parser's input, like `Error::pos`.
+    pub fn validate_func_offsets
+        (&mut self,
+         parser: &mut crate::Parser,
+         func_ty: TypeIdx,
+         locals: &[ValueType])
+        -> Result<&[u32]>
+    {
+        self.offsets.truncate(0);
+        self.record_offsets = true;
+        let result = self.validate_func(parser, func_ty, locals, None);
+        self.record_offsets = false;
+        result?;
+        return Ok(&self.offsets);
+    }
+
     #[inline]
     fn error(&self, kind: ErrorKind) -> Error {
         Error { pos: self.pos, kind }
@@ -50,6 +50,92 @@ fn lua_wasm() {
     assert_eq!(module.codes.len(), 452);
     assert_eq!(module.datas.len(), 2);
     assert_eq!(module.customs.len(), 3);
+
+    assert!(module.names_error.is_none());
+    assert_eq!(module.names.funcs.len(), 479);
+    assert_eq!(module.names.func(0), Some("js_unimplemented"));
+    assert_eq!(module.names.func(38), Some("malloc"));
+    assert_eq!(module.names.global(0), Some("__stack_pointer"));
+    assert_eq!(module.names.datas.len(), 2);
+}
+
+#[test]
+fn malformed_names() {
+    let wasm = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+        // custom section "name".
+        0x00, 20, 4, b'n', b'a', b'm', b'e',
+            // module name "m".
+            0, 2, 1, b'm',
+            // function names, out of order.
+            1, 7, 2, 1, 1, b'b', 0, 1, b'a',
+            // memory names, truncated.
+            6, 0,
+    ];
+    let alloc = Arena::new();
+    let module = parse_and_validate(&wasm, &alloc);
+
+    // the module is still valid.
+    assert_eq!(module.customs.len(), 1);
+    assert_eq!(module.names.module, Some("m"));
+    assert_eq!(module.names.funcs.len(), 0);
+    assert!(matches!(module.names_error, Some(Error { pos: _, kind: ErrorKind::InvalidNameSection })));
 }
 
 
+
+#[test]
+fn data_count() {
+    let header = [0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+    // a function type, a function.
+    let func = [0x01, 4, 1, 0x60, 0, 0, 0x03, 2, 1, 0];
+    // data.drop 0.
+    let code = [0x0a, 7, 1, 5, 0, 0xfc, 0x09, 0x00, 0x0b];
+    // a passive segment.
+    let data = [0x0b, 3, 1, 0x01, 0];
+
+    let wasm = [&header[..], &func, &code, &data].concat();
+    let alloc = Arena::new();
+    let module = Parser::parse_module(&wasm, ModuleLimits::DEFAULT, &alloc).unwrap();
+    let mut p = Parser::from_sub_section(&wasm, module.codes[0].expr);
+    let e = Validator::new(&module).validate_func(&mut p, module.funcs[0], module.codes[0].locals, None).unwrap_err();
+    assert!(matches!(e.kind, ErrorKind::DataCountRequired));
+
+    let wasm = [&header[..], &func, &[0x0c, 1, 1], &code, &data].concat();
+    let module = parse_and_validate(&wasm, &alloc);
+    assert_eq!(module.data_count, Some(1));
+
+    let wasm = [&header[..], &func, &[0x0c, 1, 2], &code, &data].concat();
+    let e = Parser::parse_module(&wasm, ModuleLimits::DEFAULT, &alloc).unwrap_err();
+    assert!(matches!(e.kind, ErrorKind::NumDatasNeDataCount));
+}
+
+#[test]
+fn memarg() {
+    let mut p = Parser::new(&[0x02, 0x10]);
+    assert_eq!(p.parse_memarg().unwrap(), MemArg { align: 2, mem: 0, offset: 16 });
+
+    // bit 6 of the alignment adds a memory index.
+    let mut p = Parser::new(&[0x42, 0x01, 0x10]);
+    assert_eq!(p.parse_memarg().unwrap(), MemArg { align: 2, mem: 1, offset: 16 });
+}
+
+#[test]
+fn memory64() {
+    let mut p = Parser::new(&[0x01, 0x01, 0x02]);
+    let ty = p.parse_memory_type().unwrap();
+    assert!(!ty.is64 && ty.limits.min == 1 && ty.limits.max == Some(2));
+
+    // bit 2 of the flags marks `i64` addresses, with `u64` limits.
+    let mut p = Parser::new(&[0x05, 0x01, 0x80, 0x80, 0x80, 0x80, 0x10]);
+    let ty = p.parse_memory_type().unwrap();
+    assert!(ty.is64 && ty.limits.min == 1 && ty.limits.max == Some(1 << 32));
+    assert_eq!(ty.addr_type(), ValueType::I64);
+
+    let mut p = Parser::new(&[0x02, 0x01]);
+    assert!(matches!(p.parse_memory_type().unwrap_err().kind, ErrorKind::InvalidLimits));
+
+    // tables don't have `i64` limits.
+    let mut p = Parser::new(&[0x04, 0x01]);
+    assert!(matches!(p.parse_limits().unwrap_err().kind, ErrorKind::InvalidLimits));
+}
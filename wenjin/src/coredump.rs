@@ -0,0 +1,288 @@
+use sti::hash::HashMap;
+
+use crate::{Value, Memory, Global};
+use crate::store::{Store, StackFrame, StackValue, InstanceId, FuncId};
+
+
+pub(crate) struct Coredumps {
+    executable_name: String,
+    last: Option<Vec<u8>>,
+}
+
+impl Store {
+    /// writes a core dump when wasm traps.
+    /// - the dump is a wasm module in the tool-conventions coredump format.
+    ///   its memories, globals & data segments hold the state
+    ///   of all instances of the store, and its `corestack` section
+    ///   holds the frames of the trapping call with their locals & operands.
+    /// - the frames end at the nearest host function.
+    /// - traps returned by host functions don't write core dumps.
+    pub fn enable_coredumps(&mut self, executable_name: &str) {
+        self.coredumps = Some(Coredumps { executable_name: executable_name.into(), last: None });
+    }
+
+    pub fn disable_coredumps(&mut self) {
+        self.coredumps = None;
+    }
+
+    /// the core dump of the last trap, unless it was taken already.
+    pub fn take_coredump(&mut self) -> Option<Vec<u8>> {
+        self.coredumps.as_mut()?.last.take()
+    }
+
+    /// - `bp` & `sp` are the trapping function's frame, `pc` its trapping instruction.
+    #[inline(never)]
+    pub(crate) fn write_coredump(&mut self, instance: InstanceId, func: FuncId, pc: *const u8, bp: *mut StackValue, sp: *mut StackValue) {
+        // save the trapping function like `debug_stop`,
+        // so the debugger's stack walk can be used.
+        let depth = self.thread.frames.len();
+        let sp = unsafe {
+            let stack = &mut self.thread.stack;
+            let stack_ptr = stack.as_ptr();
+            let bp = bp.offset_from(stack_ptr) as usize;
+            let sp = sp.offset_from(stack_ptr) as usize;
+            stack.set_len(sp);
+
+            self.thread.frames.push(Some(StackFrame {
+                instance,
+                func,
+                pc: core::ptr::NonNull::new_unchecked(pc as *mut u8),
+                bp_offset: (sp - bp) as u32,
+            }));
+            sp
+        };
+        let prev_stop = self.thread.debug_stop.replace((depth, sp));
+
+        let dump = self.encode_coredump();
+
+        self.thread.debug_stop = prev_stop;
+        self.thread.frames.pop();
+
+        self.coredumps.as_mut().unwrap().last = Some(dump);
+    }
+
+    fn encode_coredump(&self) -> Vec<u8> {
+        let mut memories = Vec::new();
+        let mut globals = Vec::new();
+        let mut memory_indices = HashMap::new();
+        let mut global_indices = HashMap::new();
+
+        let mut modules = Vec::new();
+        let mut instances = Vec::new();
+        for (i, inst) in self.instances.inner().iter().enumerate() {
+            let inst = unsafe { &*inst.get() };
+
+            let name = inst.module.names.module.map_or_else(|| format!("instance{i}.wasm"), |name| name.into());
+            modules.push(name);
+
+            // memories & globals can be shared by instances.
+            let inst_memories: Vec<u32> = inst.memories.inner().iter().map(|mem| {
+                let mem = Memory::new(mem);
+                if let Some(idx) = memory_indices.get(&mem.id().inner()) {
+                    return *idx;
+                }
+                let idx = memories.len() as u32;
+                memory_indices.insert(mem.id().inner(), idx);
+                memories.push(mem);
+                return idx;
+            }).collect();
+            let inst_globals: Vec<u32> = inst.globals.inner().iter().map(|global| {
+                let global = Global::new(global);
+                if let Some(idx) = global_indices.get(&global.id().inner()) {
+                    return *idx;
+                }
+                let idx = globals.len() as u32;
+                global_indices.insert(global.id().inner(), idx);
+                globals.push(global);
+                return idx;
+            }).collect();
+            instances.push((inst_memories, inst_globals));
+        }
+
+
+        let mut out = b"\0asm\x01\0\0\0".to_vec();
+
+        let executable_name = &self.coredumps.as_ref().unwrap().executable_name;
+        section(&mut out, 0, |s| {
+            name(s, "core");
+            s.push(0x00);
+            name(s, executable_name);
+        });
+
+        section(&mut out, 0, |s| {
+            name(s, "coremodules");
+            u32(s, modules.len() as u32);
+            for module in &modules {
+                s.push(0x00);
+                name(s, module);
+            }
+        });
+
+        section(&mut out, 0, |s| {
+            name(s, "coreinstances");
+            u32(s, instances.len() as u32);
+            for (i, (memories, globals)) in instances.iter().enumerate() {
+                s.push(0x00);
+                u32(s, i as u32);
+                u32(s, memories.len() as u32);
+                for idx in memories {
+                    u32(s, *idx);
+                }
+                u32(s, globals.len() as u32);
+                for idx in globals {
+                    u32(s, *idx);
+                }
+            }
+        });
+
+        if !memories.is_empty() {
+            section(&mut out, 5, |s| {
+                u32(s, memories.len() as u32);
+                for mem in &memories {
+                    let limits = mem.limits();
+                    let flags = if mem.is64() { 0x04 } else { 0x00 };
+                    match limits.max {
+                        None => { s.push(flags); u64(s, limits.min); }
+                        Some(max) => { s.push(flags | 0x01); u64(s, limits.min); u64(s, max); }
+                    }
+                }
+            });
+        }
+
+        if !globals.is_empty() {
+            section(&mut out, 6, |s| {
+                u32(s, globals.len() as u32);
+                for global in &globals {
+                    s.push(global.ty().to_u8());
+                    s.push(global.mutable() as u8);
+                    match global.get() {
+                        Value::I32(v) => { s.push(0x41); i64(s, v as i64); }
+                        Value::I64(v) => { s.push(0x42); i64(s, v); }
+                        Value::F32(v) => { s.push(0x43); s.extend_from_slice(&v.to_le_bytes()); }
+                        Value::F64(v) => { s.push(0x44); s.extend_from_slice(&v.to_le_bytes()); }
+                        // references can't be expressed in the dump.
+                        Value::FuncRef(_) | Value::ExternRef(_) => { s.push(0xd0); s.push(global.ty().to_u8()); }
+                    }
+                    s.push(0x0b);
+                }
+            });
+        }
+
+        if memories.iter().any(|mem| mem.size_bytes() > 0) {
+            section(&mut out, 11, |s| {
+                u32(s, memories.iter().filter(|mem| mem.size_bytes() > 0).count() as u32);
+                for (i, mem) in memories.iter().enumerate() {
+                    if mem.size_bytes() == 0 {
+                        continue;
+                    }
+
+                    if i == 0 {
+                        s.push(0x00);
+                    }
+                    else {
+                        s.push(0x02);
+                        u32(s, i as u32);
+                    }
+                    let offset_op = if mem.is64() { 0x42 } else { 0x41 };
+                    s.extend_from_slice(&[offset_op, 0x00, 0x0b]);
+
+                    let (ptr, len) = { let mut mem = *mem; mem.as_mut_ptr() };
+                    u64(s, len as u64);
+                    s.extend_from_slice(unsafe { core::slice::from_raw_parts(ptr, len) });
+                }
+            });
+        }
+
+        section(&mut out, 0, |s| {
+            name(s, "corestack");
+            s.push(0x00);
+            name(s, "main");
+
+            let frames = self.debug_frames();
+            u32(s, frames.len() as u32);
+            for (i, frame) in frames.iter().enumerate() {
+                let inst = unsafe { &*self.instances[frame.instance].get() };
+                let idx = frame.func_idx as usize - inst.module.imports.funcs.len();
+                let code = inst.module.codes[idx];
+
+                // callers are just after their call,
+                // but the dump names the instruction a frame executes.
+                let mut offset = frame.offset;
+                if i > 0 {
+                    let mut validator = wasm::Validator::new(&inst.module);
+                    let mut parser = wasm::Parser::from_sub_section(&inst.compiled.wasm, code.expr);
+                    // the function was validated when the module was compiled.
+                    let offsets = validator.validate_func_offsets(&mut parser, inst.module.funcs[idx], code.locals).unwrap();
+                    offset = *offsets.iter().rev().find(|o| **o < frame.offset).unwrap();
+                }
+
+                s.push(0x00);
+                u32(s, frame.instance.inner());
+                u32(s, frame.func_idx);
+                u32(s, offset - code.body.offset as u32);
+                for values in [self.debug_locals(i).unwrap(), self.debug_operands(i).unwrap()] {
+                    u32(s, values.len() as u32);
+                    for (value, ty) in values {
+                        stack_value(s, value, ty);
+                    }
+                }
+            }
+        });
+
+        return out;
+    }
+}
+
+
+fn section(out: &mut Vec<u8>, id: u8, f: impl FnOnce(&mut Vec<u8>)) {
+    let mut contents = Vec::new();
+    f(&mut contents);
+    out.push(id);
+    u64(out, contents.len() as u64);
+    out.extend_from_slice(&contents);
+}
+
+fn name(out: &mut Vec<u8>, name: &str) {
+    u32(out, name.len() as u32);
+    out.extend_from_slice(name.as_bytes());
+}
+
+fn u32(out: &mut Vec<u8>, v: u32) {
+    u64(out, v as u64);
+}
+
+fn u64(out: &mut Vec<u8>, mut v: u64) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn i64(out: &mut Vec<u8>, mut v: i64) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        let done = (v == 0 && byte & 0x40 == 0) || (v == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// - values that the format can't express are written as missing.
+fn stack_value(out: &mut Vec<u8>, value: StackValue, ty: wasm::ValueType) {
+    match ty {
+        wasm::ValueType::I32 => { out.push(0x7f); i64(out, value.as_i32() as i64); }
+        wasm::ValueType::I64 => { out.push(0x7e); i64(out, value.as_i64()); }
+        wasm::ValueType::F32 => { out.push(0x7d); out.extend_from_slice(&value.as_f32().to_le_bytes()); }
+        wasm::ValueType::F64 => { out.push(0x7c); out.extend_from_slice(&value.as_f64().to_le_bytes()); }
+        wasm::ValueType::V128 | wasm::ValueType::FuncRef | wasm::ValueType::ExternRef => out.push(0x01),
+    }
+}
@@ -0,0 +1,194 @@
+use crate::store::{Store, FuncKind, InstanceId, FuncId};
+
+
+/// the result of `Store::stop_coverage`.
+pub struct Coverage {
+    funcs: Vec<FuncCoverage>,
+}
+
+/// the coverage of a wasm function.
+/// - offsets are byte offsets into the module's binary,
+///   like in `BacktraceFrame::Wasm`.
+#[derive(Clone, Debug)]
+pub struct FuncCoverage {
+    pub instance: InstanceId,
+    pub func: FuncId,
+    /// the function's index in the module.
+    pub func_idx: u32,
+    /// `(offset, hits)` of each instruction, by offset.
+    pub instructions: Vec<(u32, u64)>,
+    /// `(offset, hits)` of the first instruction of each basic block, by offset.
+    /// - blocks start at the function's entry, after branches
+    ///   & at branch targets.
+    pub blocks: Vec<(u32, u64)>,
+}
+
+impl FuncCoverage {
+    /// the number of times the function was entered.
+    pub fn calls(&self) -> u64 {
+        self.blocks.first().map_or(0, |(_, hits)| *hits)
+    }
+}
+
+impl Coverage {
+    /// the wasm functions of all instances in the store,
+    /// including the ones that didn't run.
+    pub fn funcs(&self) -> &[FuncCoverage] {
+        &self.funcs
+    }
+
+    /// the coverage in the lcov tracefile format,
+    /// mapped to source lines with the modules' dwarf line info.
+    /// - a line's hits are the most hits of any of its instructions.
+    /// - returns `None` if no function has line info.
+    #[cfg(feature = "dwarf")]
+    pub fn lcov(&self, store: &Store) -> Option<String> {
+        use std::collections::BTreeMap;
+        use std::fmt::Write;
+
+        #[derive(Default)]
+        struct File {
+            // (first line, name, calls).
+            funcs: Vec<(u32, String, u64)>,
+            lines: BTreeMap<u32, u64>,
+        }
+
+        let mut files: BTreeMap<&str, File> = BTreeMap::new();
+        for func in &self.funcs {
+            let mut first = true;
+            for (offset, hits) in &func.instructions {
+                let Some(location) = store.source_location(func.instance, *offset) else { continue };
+                let (Some(file_name), true) = (location.file, location.line > 0) else { continue };
+
+                let file = files.entry(file_name).or_default();
+                let line = file.lines.entry(location.line).or_default();
+                *line = (*line).max(*hits);
+
+                // the function's first line.
+                if first {
+                    first = false;
+                    let name = location.func.or_else(|| store.func_name(func.instance, func.func_idx))
+                        .map_or_else(|| format!("func[{}]", func.func_idx), |name| name.into());
+                    file.funcs.push((location.line, name, func.calls()));
+                }
+            }
+        }
+
+        if files.is_empty() {
+            return None;
+        }
+
+        let mut result = String::new();
+        for (name, file) in &files {
+            _ = writeln!(result, "TN:");
+            _ = writeln!(result, "SF:{name}");
+            for (line, name, _) in &file.funcs {
+                _ = writeln!(result, "FN:{line},{name}");
+            }
+            for (_, name, calls) in &file.funcs {
+                _ = writeln!(result, "FNDA:{calls},{name}");
+            }
+            _ = writeln!(result, "FNF:{}", file.funcs.len());
+            _ = writeln!(result, "FNH:{}", file.funcs.iter().filter(|(_, _, calls)| *calls > 0).count());
+            for (line, hits) in &file.lines {
+                _ = writeln!(result, "DA:{line},{hits}");
+            }
+            _ = writeln!(result, "LF:{}", file.lines.len());
+            _ = writeln!(result, "LH:{}", file.lines.values().filter(|hits| **hits > 0).count());
+            _ = writeln!(result, "end_of_record");
+        }
+        return Some(result);
+    }
+}
+
+
+/// the instruction hits of the functions that ran.
+pub(crate) struct CoverageRecorder {
+    // by `FuncId`, the hits by offset into the function's code.
+    funcs: Vec<Vec<u64>>,
+}
+
+impl CoverageRecorder {
+    #[inline]
+    pub fn hit(&mut self, func: FuncId, offset: usize, code_len: usize) {
+        let idx = func.inner() as usize;
+        if idx >= self.funcs.len() {
+            self.funcs.resize_with(idx + 1, Vec::new);
+        }
+        let hits = &mut self.funcs[idx];
+        if hits.is_empty() {
+            hits.resize(code_len, 0);
+        }
+        hits[offset] += 1;
+    }
+}
+
+
+impl Store {
+    /// starts recording which wasm instructions run,
+    /// in the calls that start after this.
+    /// - covered calls run on the tracing interpreter,
+    ///   which makes them slower.
+    /// - restarts the recording, if already recording.
+    pub fn start_coverage(&mut self) {
+        self.coverage = Some(CoverageRecorder { funcs: Vec::new() });
+    }
+
+    /// - returns `None` if the store wasn't recording.
+    pub fn stop_coverage(&mut self) -> Option<Coverage> {
+        let recorder = self.coverage.take()?;
+
+        let mut funcs = Vec::new();
+        for inst in self.instances.inner() {
+            let inst = unsafe { &*inst.get() };
+            let wasm = &inst.compiled.wasm;
+
+            for func in inst.funcs.inner() {
+                let func = unsafe { &*func.get() };
+                let FuncKind::Interp(f) = &func.kind else { continue };
+                // imported from another instance.
+                if f.instance != inst.id {
+                    continue;
+                }
+
+                let code_begin = f.code_begin() as usize - wasm.as_ptr() as usize;
+                let hits = recorder.funcs.get(func.id.inner() as usize).filter(|hits| !hits.is_empty());
+                let hits_at = |offset: u32| hits.map_or(0, |hits| hits[offset as usize - code_begin]);
+
+                let idx = f.func_idx as usize - inst.module.imports.funcs.len();
+                let code = inst.module.codes[idx];
+                let mut validator = wasm::Validator::new(&inst.module);
+                let mut parser = wasm::Parser::from_sub_section(wasm, code.expr);
+                // the function was validated when the module was compiled.
+                let offsets = validator.validate_func_offsets(&mut parser, inst.module.funcs[idx], code.locals).unwrap();
+
+                // the validator's jumps are keyed by the end of the branch instruction,
+                // relative to the start of the code.
+                let jumps = unsafe { &*f.jumps };
+                let mut leaders = vec![false; f.code_len];
+                leaders[0] = true;
+                for from in 0..f.code_len as u32 {
+                    if let Some(jump) = jumps.get(&from) {
+                        leaders[from as usize] = true;
+                        leaders[jump.target as usize] = true;
+                    }
+                }
+
+                let instructions: Vec<(u32, u64)> = offsets.iter().map(|offset| (*offset, hits_at(*offset))).collect();
+                let blocks = instructions.iter().copied()
+                    .filter(|(offset, _)| leaders[*offset as usize - code_begin])
+                    .collect();
+
+                funcs.push(FuncCoverage {
+                    instance: inst.id,
+                    func: func.id,
+                    func_idx: f.func_idx,
+                    instructions,
+                    blocks,
+                });
+            }
+        }
+
+        return Some(Coverage { funcs });
+    }
+}
@@ -0,0 +1,381 @@
+use sti::hash::HashMap;
+
+use crate::{Error, Value, Global};
+use crate::store::{Store, FuncKind, InterpFunc, StackValue, InstanceId, FuncId, GlobalId};
+
+
+/// why a call stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugEvent {
+    Breakpoint,
+    Step,
+}
+
+/// how to continue a stopped call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugAction {
+    /// run until the next breakpoint.
+    Continue,
+    /// stop at the next instruction, including in callees.
+    Step,
+    /// stop at the next instruction of this function or its callers.
+    StepOver,
+    /// stop at the next instruction of a caller.
+    StepOut,
+}
+
+/// a wasm frame of a stopped call.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DebugFrame {
+    pub instance: InstanceId,
+    pub func: FuncId,
+    /// the function's index in the module.
+    pub func_idx: u32,
+    /// the byte offset in the module's binary.
+    /// - the next instruction for the innermost frame,
+    ///   just after the call instruction for the callers.
+    pub offset: u32,
+}
+
+
+type DebugHandler = dyn FnMut(&mut Store, DebugEvent) -> Result<DebugAction, Error>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    None,
+    Into,
+    // the call depth of the stop.
+    Over(usize),
+    Out(usize),
+}
+
+pub(crate) struct Debugger {
+    /// whether the interpreter needs to check for stops.
+    pub active: bool,
+    // (func, pc).
+    breakpoints: HashMap<(u32, usize), ()>,
+    step: Step,
+    handler: Option<std::boxed::Box<DebugHandler>>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            breakpoints: HashMap::new(),
+            step: Step::None,
+            handler: None,
+        }
+    }
+
+    fn update(&mut self) {
+        self.active = self.handler.is_some()
+            && (self.breakpoints.len() != 0 || self.step != Step::None);
+    }
+
+    #[inline]
+    pub fn has_handler(&self) -> bool {
+        self.handler.is_some()
+    }
+
+    /// - `depth` is the number of frames below the current function.
+    #[inline]
+    pub fn check(&self, func: FuncId, pc: *const u8, depth: usize) -> Option<DebugEvent> {
+        if self.breakpoints.get(&(func.inner(), pc as usize)).is_some() {
+            return Some(DebugEvent::Breakpoint);
+        }
+
+        let step = match self.step {
+            Step::None     => false,
+            Step::Into     => true,
+            Step::Over(d)  => depth <= d,
+            Step::Out(d)   => depth < d,
+        };
+        return step.then_some(DebugEvent::Step);
+    }
+
+    /// - the handler is taken while it runs,
+    ///   so calls made by the handler don't stop.
+    pub fn take_handler(&mut self) -> Option<std::boxed::Box<DebugHandler>> {
+        let handler = self.handler.take();
+        self.update();
+        return handler;
+    }
+
+    pub fn restore_handler(&mut self, handler: std::boxed::Box<DebugHandler>, action: DebugAction, depth: usize) {
+        // the handler may have installed a new handler.
+        if self.handler.is_none() {
+            self.handler = Some(handler);
+        }
+        self.step = match action {
+            DebugAction::Continue => Step::None,
+            DebugAction::Step     => Step::Into,
+            DebugAction::StepOver => Step::Over(depth),
+            DebugAction::StepOut  => Step::Out(depth),
+        };
+        self.update();
+    }
+
+    /// steps don't carry over into the next root call.
+    pub fn end_root_call(&mut self) {
+        self.step = Step::None;
+        self.update();
+    }
+}
+
+
+/// the stack layout of a frame of a stopped call.
+struct FrameLayout<'a> {
+    frame: DebugFrame,
+    func: &'a InterpFunc,
+    bp: usize,
+    // the end of the frame's operands.
+    end: usize,
+}
+
+impl Store {
+    /// sets the function that is called when wasm stops
+    /// at a breakpoint or after a step.
+    /// - the handler can inspect & modify the stopped call,
+    ///   using `debug_frames`, `debug_locals`, `debug_operands` & co.
+    /// - the returned action determines the next stop.
+    ///   errors abort the call.
+    /// - wasm called by the handler doesn't stop.
+    /// - only the wasm calls that start after this can stop,
+    ///   as they run on the tracing interpreter.
+    pub fn set_debug_handler<F>(&mut self, handler: F)
+    where F: FnMut(&mut Store, DebugEvent) -> Result<DebugAction, Error> + 'static {
+        self.debug.handler = Some(std::boxed::Box::new(handler));
+        self.debug.update();
+    }
+
+    pub fn clear_debug_handler(&mut self) {
+        self.debug.handler = None;
+        self.debug.update();
+    }
+
+    /// stops before the instruction at `offset` of the wasm function `func`.
+    /// - `offset` is a byte offset into the module's binary,
+    ///   like in `BacktraceFrame::Wasm`.
+    /// - fails with `Error::InvalidBreakpoint` if `func` is a host function,
+    ///   or `offset` isn't the start of one of its instructions.
+    /// - takes effect for the calls that start afterwards,
+    ///   & for the stopped call, when set by the debug handler.
+    pub fn set_breakpoint(&mut self, func: FuncId, offset: u32) -> Result<(), Error> {
+        let Some(pc) = self.instruction_pc(func, offset) else {
+            return Err(Error::InvalidBreakpoint);
+        };
+        self.debug.breakpoints.insert((func.inner(), pc as usize), ());
+        self.debug.update();
+        return Ok(());
+    }
+
+    /// returns whether there was a breakpoint.
+    pub fn clear_breakpoint(&mut self, func: FuncId, offset: u32) -> bool {
+        let Some(pc) = self.instruction_pc(func, offset) else { return false };
+        let result = self.debug.breakpoints.remove(&(func.inner(), pc as usize)).is_some();
+        self.debug.update();
+        return result;
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.debug.breakpoints = HashMap::new();
+        self.debug.update();
+    }
+
+    /// stops before the next instruction that is executed.
+    /// - takes effect for the calls that start afterwards,
+    ///   & for the stopped call, when set by the debug handler.
+    pub fn break_next(&mut self) {
+        self.debug.step = Step::Into;
+        self.debug.update();
+    }
+
+    /// the wasm frames of the stopped call, innermost first.
+    /// - empty if no call is stopped.
+    /// - ends at the nearest host function.
+    pub fn debug_frames(&self) -> std::vec::Vec<DebugFrame> {
+        self.frame_layouts().into_iter().map(|f| f.frame).collect()
+    }
+
+    /// the locals of a frame of the stopped call, including the params.
+    /// - `frame` is an index into `debug_frames`.
+    pub fn debug_locals(&self, frame: usize) -> Result<std::vec::Vec<(StackValue, wasm::ValueType)>, Error> {
+        let layout = self.frame_layout(frame)?;
+        let types = self.frame_local_types(&layout);
+        return Ok(types.iter().enumerate()
+            .map(|(i, ty)| (self.thread.stack[layout.bp + i], *ty))
+            .collect());
+    }
+
+    pub fn debug_set_local(&mut self, frame: usize, idx: u32, value: Value) -> Result<(), Error> {
+        let layout = self.frame_layout(frame)?;
+        let types = self.frame_local_types(&layout);
+        let Some(ty) = types.get(idx as usize) else { return Err(Error::InvalidIndex) };
+        if value.ty() != *ty {
+            return Err(Error::TypeMismatch);
+        }
+        let at = layout.bp + idx as usize;
+        self.thread.stack[at] = StackValue::from_value(value);
+        return Ok(());
+    }
+
+    /// the operand stack of a frame of the stopped call, bottom first,
+    /// with the types from the validator.
+    /// - `frame` is an index into `debug_frames`.
+    /// - the operands of the callers don't include the call's arguments.
+    pub fn debug_operands(&self, frame: usize) -> Result<std::vec::Vec<(StackValue, wasm::ValueType)>, Error> {
+        let layout = self.frame_layout(frame)?;
+        let types = self.frame_operand_types(frame, &layout);
+        let begin = layout.bp + layout.func.num_locals as usize;
+        return Ok(types.iter().enumerate()
+            .map(|(i, ty)| (self.thread.stack[begin + i], *ty))
+            .collect());
+    }
+
+    /// - `idx` counts from the bottom of the frame's operand stack.
+    pub fn debug_set_operand(&mut self, frame: usize, idx: u32, value: Value) -> Result<(), Error> {
+        let layout = self.frame_layout(frame)?;
+        let types = self.frame_operand_types(frame, &layout);
+        let Some(ty) = types.get(idx as usize) else { return Err(Error::InvalidIndex) };
+        if value.ty() != *ty {
+            return Err(Error::TypeMismatch);
+        }
+        let at = layout.bp + layout.func.num_locals as usize + idx as usize;
+        self.thread.stack[at] = StackValue::from_value(value);
+        return Ok(());
+    }
+
+    /// a function of an instance, by its index in the module.
+    /// - includes functions that aren't exported.
+    pub fn instance_func(&self, instance_id: InstanceId, idx: u32) -> Option<FuncId> {
+        let inst = unsafe { &*self.instances[instance_id].get() };
+        let func = inst.funcs.inner().get(idx as usize)?;
+        return Some(unsafe { &*func.get() }.id);
+    }
+
+    /// a global of an instance, by its index in the module.
+    /// - includes globals that aren't exported.
+    pub fn instance_global(&self, instance_id: InstanceId, idx: u32) -> Option<GlobalId> {
+        let inst = unsafe { &*self.instances[instance_id].get() };
+        let global = inst.globals.inner().get(idx as usize)?;
+        return Some(Global::new(global).id());
+    }
+
+    pub fn global<'a>(&'a self, id: GlobalId) -> Global<'a> {
+        Global::new(&self.globals[id])
+    }
+
+    pub fn set_global(&mut self, id: GlobalId, value: Value) -> Result<(), Error> {
+        let mut global = Global::new(&self.globals[id]);
+        if !global.mutable() {
+            return Err(Error::ImmutableGlobal);
+        }
+        if value.ty() != global.ty() {
+            return Err(Error::TypeMismatch);
+        }
+        global.set(value);
+        return Ok(());
+    }
+
+
+    /// the start of the instruction at `offset` of a wasm function.
+    fn instruction_pc(&self, func: FuncId, offset: u32) -> Option<*const u8> {
+        let func = unsafe { &*self.funcs[func].get() };
+        let FuncKind::Interp(f) = &func.kind else { return None };
+        let inst = unsafe { &*self.instances[f.instance].get() };
+
+        let pc = inst.compiled.wasm.as_ptr().wrapping_add(offset as usize);
+        if pc < f.code_begin() || pc >= f.code_end() {
+            return None;
+        }
+
+        let mut validator = wasm::Validator::new(&inst.module);
+        let (ty, code) = func_code(&inst.module, f.func_idx);
+        let mut parser = wasm::Parser::from_sub_section(&inst.compiled.wasm, code.expr);
+        let valid = validator.validate_func_until(&mut parser, ty, code.locals, offset as usize);
+        return valid.unwrap_or(false).then_some(pc);
+    }
+
+    fn frame_layouts(&self) -> std::vec::Vec<FrameLayout<'_>> {
+        let mut result = std::vec::Vec::new();
+        let Some((stop, sp)) = self.thread.debug_stop else { return result };
+
+        let mut callee_bp = sp;
+        for frame in self.thread.frames[..=stop].iter().rev() {
+            let Some(frame) = frame else { break };
+
+            let func = unsafe { &*self.funcs[frame.func].get() };
+            let FuncKind::Interp(f) = &func.kind else { unreachable!() };
+            let inst = unsafe { &*self.instances[frame.instance].get() };
+
+            let bp = callee_bp - frame.bp_offset as usize;
+            let offset = frame.pc.as_ptr() as usize - inst.compiled.wasm.as_ptr() as usize;
+            result.push(FrameLayout {
+                frame: DebugFrame {
+                    instance: frame.instance,
+                    func: frame.func,
+                    func_idx: f.func_idx,
+                    offset: offset as u32,
+                },
+                func: f,
+                bp,
+                end: callee_bp,
+            });
+            callee_bp = bp;
+        }
+        return result;
+    }
+
+    fn frame_layout(&self, frame: usize) -> Result<FrameLayout<'_>, Error> {
+        if self.thread.debug_stop.is_none() {
+            return Err(Error::NotStopped);
+        }
+        let mut layouts = self.frame_layouts();
+        if frame >= layouts.len() {
+            return Err(Error::InvalidIndex);
+        }
+        return Ok(layouts.swap_remove(frame));
+    }
+
+    fn frame_local_types(&self, layout: &FrameLayout) -> std::vec::Vec<wasm::ValueType> {
+        let inst = unsafe { &*self.instances[layout.frame.instance].get() };
+        let (ty, code) = func_code(&inst.module, layout.frame.func_idx);
+        let params = inst.module.types[ty as usize].params;
+        return params.iter().chain(code.locals).copied().collect();
+    }
+
+    /// - re-validates the function up to the frame's offset.
+    fn frame_operand_types(&self, frame: usize, layout: &FrameLayout) -> std::vec::Vec<wasm::ValueType> {
+        let inst = unsafe { &*self.instances[layout.frame.instance].get() };
+
+        let mut validator = wasm::Validator::new(&inst.module);
+        let (ty, code) = func_code(&inst.module, layout.frame.func_idx);
+        let mut parser = wasm::Parser::from_sub_section(&inst.compiled.wasm, code.expr);
+        // the function was validated when the module was compiled,
+        // and frames stop at instruction boundaries.
+        let found = validator.validate_func_until(&mut parser, ty, code.locals, layout.frame.offset as usize);
+        debug_assert!(matches!(found, Ok(true)));
+        let mut types = validator.stack().to_vec();
+
+        // a caller's offset is just after the call,
+        // where the stack holds the callee's results instead of its arguments.
+        if frame > 0 {
+            let callee = self.frame_layout(frame - 1).unwrap().frame.func;
+            let num_rets = unsafe { &*self.funcs[callee].get() }.ty.rets.len();
+            types.truncate(types.len() - num_rets);
+        }
+        // the trapping instruction of a core dump may have popped its operands.
+        else if self.thread.trapped {
+            types.truncate(layout.end - layout.bp - layout.func.num_locals as usize);
+        }
+
+        debug_assert_eq!(types.len(), layout.end - layout.bp - layout.func.num_locals as usize);
+        return types;
+    }
+}
+
+fn func_code<'a>(module: &wasm::Module<'a>, func_idx: u32) -> (wasm::TypeIdx, wasm::Code<'a>) {
+    let idx = func_idx as usize - module.imports.funcs.len();
+    return (module.funcs[idx], module.codes[idx]);
+}
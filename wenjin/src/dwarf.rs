@@ -0,0 +1,978 @@
+use sti::hash::HashMap;
+
+
+/// source level debug info, from a module's dwarf custom sections.
+/// - built from `.debug_line`, `.debug_info` & the sections they reference.
+/// - supports dwarf versions 2 to 5, 32 and 64 bit.
+/// - malformed units & line programs are skipped.
+/// - functions with `DW_AT_ranges` and inlined functions aren't tracked.
+pub struct DebugInfo {
+    code_offset: usize,
+    files: Vec<String>,
+    // sorted by address.
+    // each sequence ends with an `end` row.
+    rows: Vec<Row>,
+    // sorted by `low`.
+    funcs: Vec<FuncRange>,
+}
+
+/// the source location of a code offset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLocation<'a> {
+    pub file: Option<&'a str>,
+    /// 1-based, 0 if unknown.
+    pub line: u32,
+    /// 1-based, 0 if unknown.
+    pub column: u32,
+    /// the name of the enclosing function.
+    pub func: Option<&'a str>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Row {
+    address: u64,
+    file: u32,
+    line: u32,
+    column: u32,
+    end: bool,
+}
+
+#[derive(Clone, Debug)]
+struct FuncRange {
+    low: u64,
+    high: u64,
+    name: String,
+}
+
+impl DebugInfo {
+    /// - returns `None` if the module has no line info.
+    pub fn new(module: &wasm::Module) -> Option<DebugInfo> {
+        let code = module.code_section?;
+
+        let mut sections = Sections::default();
+        for custom in module.customs {
+            let data = custom.data;
+            match custom.name {
+                ".debug_line"        => sections.line        = data,
+                ".debug_info"        => sections.info        = data,
+                ".debug_abbrev"      => sections.abbrev      = data,
+                ".debug_str"         => sections.str         = data,
+                ".debug_line_str"    => sections.line_str    = data,
+                ".debug_str_offsets" => sections.str_offsets = data,
+                ".debug_addr"        => sections.addr        = data,
+                _ => (),
+            }
+        }
+        if sections.line.is_empty() {
+            return None;
+        }
+
+        let mut builder = Builder {
+            sections,
+            files: Vec::new(),
+            file_ids: HashMap::new(),
+            sequences: Vec::new(),
+            funcs: Vec::new(),
+            subprograms: HashMap::new(),
+        };
+
+        let units = builder.parse_units();
+        if units.is_empty() {
+            // no `.debug_info`, parse all line programs.
+            let mut offset = 0;
+            while offset < sections.line.len() {
+                let Some(next) = builder.parse_line_program(offset, "") else { break };
+                offset = next;
+            }
+        }
+        else {
+            let mut done = Vec::new();
+            for unit in &units {
+                if !done.contains(&unit.stmt_list) {
+                    done.push(unit.stmt_list);
+                    builder.parse_line_program(unit.stmt_list, &unit.comp_dir);
+                }
+            }
+        }
+
+        return Some(builder.finish(code.offset));
+    }
+
+    /// the source location of an offset in the code section.
+    /// - `offset` is a byte offset into the module,
+    ///   like in `BacktraceFrame::Wasm`.
+    pub fn location(&self, offset: usize) -> Option<SourceLocation<'_>> {
+        let address = offset.checked_sub(self.code_offset)? as u64;
+
+        let row = self.row(address);
+        let func = self.func(address);
+        if row.is_none() && func.is_none() {
+            return None;
+        }
+
+        let (file, line, column) = match row {
+            Some(row) => (self.files.get(row.file as usize).map(|f| f.as_str()), row.line, row.column),
+            None => (None, 0, 0),
+        };
+        return Some(SourceLocation { file, line, column, func });
+    }
+
+    /// the name of the function containing a code offset.
+    /// - `offset` is a byte offset into the module.
+    pub fn func_name(&self, offset: usize) -> Option<&str> {
+        let address = offset.checked_sub(self.code_offset)? as u64;
+        self.func(address)
+    }
+
+    /// the source files referenced by the line info.
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|f| f.as_str())
+    }
+
+    fn row(&self, address: u64) -> Option<&Row> {
+        let i = self.rows.partition_point(|r| r.address <= address);
+        let row = &self.rows[i.checked_sub(1)?];
+        if row.end {
+            return None;
+        }
+        return Some(row);
+    }
+
+    fn func(&self, address: u64) -> Option<&str> {
+        let i = self.funcs.partition_point(|f| f.low <= address);
+        let func = &self.funcs[i.checked_sub(1)?];
+        if address >= func.high {
+            return None;
+        }
+        return Some(&func.name);
+    }
+}
+
+
+#[derive(Clone, Copy, Default)]
+struct Sections<'a> {
+    line:        &'a [u8],
+    info:        &'a [u8],
+    abbrev:      &'a [u8],
+    str:         &'a [u8],
+    line_str:    &'a [u8],
+    str_offsets: &'a [u8],
+    addr:        &'a [u8],
+}
+
+struct Builder<'a> {
+    sections: Sections<'a>,
+    files: Vec<String>,
+    file_ids: HashMap<String, u32>,
+    sequences: Vec<Vec<Row>>,
+    funcs: Vec<PendingFunc>,
+    // `.debug_info` offset -> (name, origin).
+    subprograms: HashMap<u64, (Option<String>, Option<u64>)>,
+}
+
+struct Unit {
+    stmt_list: usize,
+    comp_dir: String,
+}
+
+struct PendingFunc {
+    low: u64,
+    high: u64,
+    name: Option<String>,
+    // `DW_AT_specification` or `DW_AT_abstract_origin`.
+    origin: Option<u64>,
+}
+
+/// the encoding of a unit or line program.
+#[derive(Clone, Copy)]
+struct Format {
+    version: u16,
+    offset_size: u8,
+    address_size: u8,
+}
+
+/// the bases for indexed forms.
+#[derive(Clone, Copy, Default)]
+struct Bases {
+    str_offsets: u64,
+    addr: u64,
+}
+
+struct Abbrev {
+    tag: u64,
+    has_children: bool,
+    // (name, form, implicit const).
+    attrs: Vec<(u64, u64, i64)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Attr<'a> {
+    Uint(u64),
+    Addr(u64),
+    AddrIndex(u64),
+    Str(&'a [u8]),
+    StrOffset(u64),
+    LineStrOffset(u64),
+    StrIndex(u64),
+    /// a `.debug_info` offset.
+    Ref(u64),
+    /// an offset from the start of the unit.
+    UnitRef(u64),
+    Other,
+}
+
+impl<'a> Builder<'a> {
+    fn finish(mut self, code_offset: usize) -> DebugInfo {
+        self.sequences.sort_by_key(|s| s[0].address);
+        let mut rows = Vec::with_capacity(self.sequences.iter().map(|s| s.len()).sum());
+        for sequence in &self.sequences {
+            rows.extend_from_slice(sequence);
+        }
+
+        let mut funcs = Vec::with_capacity(self.funcs.len());
+        for func in &self.funcs {
+            // `DW_AT_abstract_origin` may refer to a `DW_AT_specification`.
+            let mut name = func.name.clone();
+            let mut origin = func.origin;
+            for _ in 0..4 {
+                if name.is_some() { break }
+                let Some((n, o)) = origin.and_then(|o| self.subprograms.get(&o)) else { break };
+                name   = n.clone();
+                origin = *o;
+            }
+            let Some(name) = name else { continue };
+            funcs.push(FuncRange { low: func.low, high: func.high, name });
+        }
+        funcs.sort_by_key(|f| f.low);
+
+        return DebugInfo { code_offset, files: self.files, rows, funcs };
+    }
+
+    fn file_id(&mut self, path: String) -> u32 {
+        if let Some(id) = self.file_ids.get(&path) {
+            return *id;
+        }
+        let id = self.files.len() as u32;
+        self.files.push(path.clone());
+        self.file_ids.insert(path, id);
+        return id;
+    }
+
+
+    // .debug_info
+
+    fn parse_units(&mut self) -> Vec<Unit> {
+        let mut units = Vec::new();
+
+        let info = self.sections.info;
+        let mut offset = 0;
+        while offset < info.len() {
+            let mut r = Reader::new(info, offset);
+            let Some((len, offset_size)) = r.initial_length() else { break };
+            let end = r.pos.saturating_add(len);
+            if end > info.len() {
+                break;
+            }
+
+            let mut unit_r = Reader { data: &info[..end], pos: r.pos };
+            if let Some(unit) = self.parse_unit(&mut unit_r, offset as u64, offset_size) {
+                units.push(unit);
+            }
+            offset = end;
+        }
+
+        return units;
+    }
+
+    fn parse_unit(&mut self, r: &mut Reader<'a>, unit_offset: u64, offset_size: u8) -> Option<Unit> {
+        const DW_UT_COMPILE: u8 = 0x01;
+        const DW_UT_PARTIAL: u8 = 0x03;
+
+        let version = r.u16()?;
+        let (abbrev_offset, address_size) = match version {
+            2..=4 => {
+                let abbrev_offset = r.offset(offset_size)?;
+                (abbrev_offset, r.u8()?)
+            }
+            5 => {
+                let unit_type = r.u8()?;
+                if unit_type != DW_UT_COMPILE && unit_type != DW_UT_PARTIAL {
+                    return None;
+                }
+                let address_size = r.u8()?;
+                (r.offset(offset_size)?, address_size)
+            }
+            _ => return None,
+        };
+        let format = Format { version, offset_size, address_size };
+
+        let abbrevs = parse_abbrevs(self.sections.abbrev, abbrev_offset as usize)?;
+
+        let mut unit = None;
+        let mut bases = Bases::default();
+        let mut attrs = Vec::new();
+        let mut depth = 0usize;
+        while !r.is_done() {
+            let die_offset = r.pos as u64;
+            let code = r.uleb()?;
+            if code == 0 {
+                depth = depth.checked_sub(1)?;
+                continue;
+            }
+
+            let abbrev = abbrevs.get(&code)?;
+            attrs.clear();
+            for &(name, form, implicit_const) in &abbrev.attrs {
+                let value = read_attr(r, format, form, implicit_const)?;
+                attrs.push((name, value));
+            }
+
+            if unit.is_none() {
+                if abbrev.tag != DW_TAG_COMPILE_UNIT && abbrev.tag != DW_TAG_PARTIAL_UNIT {
+                    return None;
+                }
+
+                // the bases can come after the attributes that use them.
+                for &(name, value) in &attrs {
+                    match (name, value) {
+                        (DW_AT_STR_OFFSETS_BASE, Attr::Uint(base)) => bases.str_offsets = base,
+                        (DW_AT_ADDR_BASE | DW_AT_GNU_ADDR_BASE, Attr::Uint(base)) => bases.addr = base,
+                        _ => (),
+                    }
+                }
+                // dwarf 5 string offsets start after the section header.
+                if bases.str_offsets == 0 && version >= 5 {
+                    bases.str_offsets = if offset_size == 4 { 8 } else { 16 };
+                }
+
+                let mut stmt_list = None;
+                let mut comp_dir = String::new();
+                for &(name, value) in &attrs {
+                    match name {
+                        DW_AT_STMT_LIST => {
+                            if let Attr::Uint(offset) = value {
+                                stmt_list = Some(offset as usize);
+                            }
+                        }
+                        DW_AT_COMP_DIR => {
+                            if let Some(dir) = self.string(format, bases, value) {
+                                comp_dir = String::from_utf8_lossy(dir).into_owned();
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                unit = Some(Unit { stmt_list: stmt_list?, comp_dir });
+            }
+            else if abbrev.tag == DW_TAG_SUBPROGRAM {
+                self.subprogram(format, bases, unit_offset, die_offset, &attrs);
+            }
+
+            if abbrev.has_children {
+                depth += 1;
+            }
+        }
+
+        return unit;
+    }
+
+    fn subprogram(&mut self, format: Format, bases: Bases, unit_offset: u64, die_offset: u64, attrs: &[(u64, Attr<'a>)]) {
+        let mut name = None;
+        let mut linkage_name = None;
+        let mut origin = None;
+        let mut low = None;
+        let mut high = None;
+        for &(attr, value) in attrs {
+            match attr {
+                DW_AT_NAME         => name = self.string(format, bases, value),
+                DW_AT_LINKAGE_NAME |
+                DW_AT_MIPS_LINKAGE_NAME => linkage_name = self.string(format, bases, value),
+                DW_AT_SPECIFICATION |
+                DW_AT_ABSTRACT_ORIGIN => {
+                    match value {
+                        Attr::Ref(offset)     => origin = Some(offset),
+                        Attr::UnitRef(offset) => origin = unit_offset.checked_add(offset),
+                        _ => (),
+                    }
+                }
+                DW_AT_LOW_PC  => low  = self.address(format, bases, value),
+                DW_AT_HIGH_PC => high = Some(value),
+                _ => (),
+            }
+        }
+        let name = name.or(linkage_name).map(|n| String::from_utf8_lossy(n).into_owned());
+
+        if let (Some(low), Some(high)) = (low, high) {
+            let high = match high {
+                Attr::Uint(size) => low.checked_add(size),
+                _ => self.address(format, bases, high),
+            };
+            if let Some(high) = high {
+                // dead code is relocated to 0 or -1 by linkers.
+                if low != 0 && !is_tombstone(low, format.address_size) && low < high {
+                    self.funcs.push(PendingFunc { low, high, name: name.clone(), origin });
+                }
+            }
+        }
+
+        self.subprograms.insert(die_offset, (name, origin));
+    }
+
+    fn string(&self, format: Format, bases: Bases, value: Attr<'a>) -> Option<&'a [u8]> {
+        let s = self.sections;
+        match value {
+            Attr::Str(string)          => Some(string),
+            Attr::StrOffset(offset)    => Reader::new(s.str,      offset as usize).cstr(),
+            Attr::LineStrOffset(offset) => Reader::new(s.line_str, offset as usize).cstr(),
+            Attr::StrIndex(index) => {
+                let size = format.offset_size as u64;
+                let at = index.checked_mul(size)?.checked_add(bases.str_offsets)?;
+                let offset = Reader::new(s.str_offsets, at as usize).offset(format.offset_size)?;
+                Reader::new(s.str, offset as usize).cstr()
+            }
+            _ => None,
+        }
+    }
+
+    fn address(&self, format: Format, bases: Bases, value: Attr<'a>) -> Option<u64> {
+        match value {
+            Attr::Addr(address) => Some(address),
+            Attr::AddrIndex(index) => {
+                let size = format.address_size as u64;
+                let at = index.checked_mul(size)?.checked_add(bases.addr)?;
+                Reader::new(self.sections.addr, at as usize).uint(format.address_size)
+            }
+            _ => None,
+        }
+    }
+
+
+    // .debug_line
+
+    /// - returns the offset of the next line program.
+    fn parse_line_program(&mut self, offset: usize, comp_dir: &str) -> Option<usize> {
+        let line = self.sections.line;
+
+        let mut r = Reader::new(line, offset);
+        let (len, offset_size) = r.initial_length()?;
+        let end = r.pos.checked_add(len)?;
+        if end > line.len() {
+            return None;
+        }
+        let mut r = Reader { data: &line[..end], pos: r.pos };
+
+        let version = r.u16()?;
+        if !(2..=5).contains(&version) {
+            return Some(end);
+        }
+
+        let mut address_size = 4;
+        if version >= 5 {
+            address_size = r.u8()?;
+            let _segment_selector_size = r.u8()?;
+        }
+        let format = Format { version, offset_size, address_size };
+
+        let header_len = r.offset(offset_size)?;
+        let program = r.pos.checked_add(header_len as usize)?;
+
+        let min_inst_len = r.u8()? as u64;
+        if version >= 4 {
+            let _max_ops_per_inst = r.u8()?;
+        }
+        let _default_is_stmt = r.u8()?;
+        let line_base = r.u8()? as i8 as i64;
+        let line_range = r.u8()?;
+        let opcode_base = r.u8()?;
+        if line_range == 0 {
+            return Some(end);
+        }
+        let opcode_lengths = r.bytes(opcode_base.saturating_sub(1) as usize)?;
+
+        // file index -> file id.
+        let mut files: Vec<Option<u32>> = Vec::new();
+        let mut dirs: Vec<String> = Vec::new();
+        if version < 5 {
+            // dir 0 is the unit's directory, files are 1-based.
+            dirs.push(comp_dir.into());
+            loop {
+                let dir = r.cstr()?;
+                if dir.is_empty() { break }
+                dirs.push(join(comp_dir, &String::from_utf8_lossy(dir)));
+            }
+
+            files.push(None);
+            loop {
+                let name = r.cstr()?;
+                if name.is_empty() { break }
+                let dir = r.uleb()?;
+                let _mtime = r.uleb()?;
+                let _len = r.uleb()?;
+
+                let file = self.file_path(&dirs, dir, name);
+                files.push(file);
+            }
+        }
+        else {
+            let dir_format = parse_entry_format(&mut r)?;
+            let num_dirs = r.uleb()?;
+            for _ in 0..num_dirs {
+                let (path, _) = self.parse_entry(&mut r, format, &dir_format)?;
+                let path = path.map(|p| String::from_utf8_lossy(p).into_owned()).unwrap_or_default();
+                dirs.push(join(comp_dir, &path));
+            }
+
+            let file_format = parse_entry_format(&mut r)?;
+            let num_files = r.uleb()?;
+            for _ in 0..num_files {
+                let (path, dir) = self.parse_entry(&mut r, format, &file_format)?;
+                let file = path.and_then(|path| self.file_path(&dirs, dir, path));
+                files.push(file);
+            }
+        }
+
+        let mut r = Reader { data: r.data, pos: program };
+
+        let mut sequence = Vec::new();
+        let mut address = 0u64;
+        let mut file = 1u64;
+        let mut line = 1i64;
+        let mut column = 0u64;
+        while !r.is_done() {
+            let mut emit = false;
+            let mut end_sequence = false;
+
+            let opcode = r.u8()?;
+            if opcode >= opcode_base {
+                let adjusted = opcode - opcode_base;
+                address = address.wrapping_add(((adjusted / line_range) as u64).wrapping_mul(min_inst_len));
+                line = line.wrapping_add(line_base + (adjusted % line_range) as i64);
+                emit = true;
+            }
+            else {
+                match opcode {
+                    0 => {
+                        let len = r.uleb()? as usize;
+                        let next = r.pos.checked_add(len)?;
+                        if len == 0 || next > r.data.len() {
+                            return Some(end);
+                        }
+                        match r.u8()? {
+                            DW_LNE_END_SEQUENCE => {
+                                emit = true;
+                                end_sequence = true;
+                            }
+                            DW_LNE_SET_ADDRESS => {
+                                address = r.uint((len - 1) as u8)?;
+                            }
+                            _ => (),
+                        }
+                        r.pos = next;
+                    }
+                    DW_LNS_COPY => emit = true,
+                    DW_LNS_ADVANCE_PC => {
+                        address = address.wrapping_add(r.uleb()?.wrapping_mul(min_inst_len));
+                    }
+                    DW_LNS_ADVANCE_LINE => line = line.wrapping_add(r.sleb()?),
+                    DW_LNS_SET_FILE => file = r.uleb()?,
+                    DW_LNS_SET_COLUMN => column = r.uleb()?,
+                    DW_LNS_CONST_ADD_PC => {
+                        let adjusted = 255 - opcode_base;
+                        address = address.wrapping_add(((adjusted / line_range) as u64).wrapping_mul(min_inst_len));
+                    }
+                    DW_LNS_FIXED_ADVANCE_PC => {
+                        address = address.wrapping_add(r.u16()? as u64);
+                    }
+                    _ => {
+                        // skip unknown opcodes by their arg count.
+                        for _ in 0..opcode_lengths[opcode as usize - 1] {
+                            r.uleb()?;
+                        }
+                    }
+                }
+            }
+
+            if emit {
+                let file = files.get(file as usize).copied().flatten().unwrap_or(u32::MAX);
+                sequence.push(Row {
+                    address,
+                    file,
+                    line:   line.clamp(0, u32::MAX as i64) as u32,
+                    column: column.min(u32::MAX as u64) as u32,
+                    end: end_sequence,
+                });
+            }
+
+            if end_sequence {
+                // dead code is relocated to 0 or -1 by linkers.
+                let start = sequence[0].address;
+                if start != 0 && !is_tombstone(start, address_size) {
+                    self.sequences.push(core::mem::take(&mut sequence));
+                }
+                sequence.clear();
+
+                address = 0;
+                file = 1;
+                line = 1;
+                column = 0;
+            }
+        }
+
+        return Some(end);
+    }
+
+    fn file_path(&mut self, dirs: &[String], dir: u64, name: &[u8]) -> Option<u32> {
+        let dir = dirs.get(dir as usize)?;
+        let path = join(dir, &String::from_utf8_lossy(name));
+        return Some(self.file_id(path));
+    }
+
+    /// - returns the entry's path & directory index.
+    fn parse_entry(&self, r: &mut Reader<'a>, format: Format, entry_format: &[(u64, u64)]) -> Option<(Option<&'a [u8]>, u64)> {
+        let mut path = None;
+        let mut dir = 0;
+        for &(content, form) in entry_format {
+            let value = read_attr(r, format, form, 0)?;
+            match content {
+                DW_LNCT_PATH => path = self.string(format, Bases::default(), value),
+                DW_LNCT_DIRECTORY_INDEX => {
+                    if let Attr::Uint(index) = value {
+                        dir = index;
+                    }
+                }
+                _ => (),
+            }
+        }
+        return Some((path, dir));
+    }
+}
+
+
+fn parse_abbrevs(data: &[u8], offset: usize) -> Option<HashMap<u64, Abbrev>> {
+    let mut abbrevs = HashMap::new();
+    let mut r = Reader::new(data, offset);
+    loop {
+        let code = r.uleb()?;
+        if code == 0 {
+            break;
+        }
+
+        let tag = r.uleb()?;
+        let has_children = r.u8()? != 0;
+        let mut attrs = Vec::new();
+        loop {
+            let name = r.uleb()?;
+            let form = r.uleb()?;
+            if name == 0 && form == 0 {
+                break;
+            }
+            let implicit_const = if form == DW_FORM_IMPLICIT_CONST { r.sleb()? } else { 0 };
+            attrs.push((name, form, implicit_const));
+        }
+
+        abbrevs.insert(code, Abbrev { tag, has_children, attrs });
+    }
+    return Some(abbrevs);
+}
+
+fn parse_entry_format(r: &mut Reader) -> Option<Vec<(u64, u64)>> {
+    let count = r.u8()?;
+    let mut result = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        result.push((r.uleb()?, r.uleb()?));
+    }
+    return Some(result);
+}
+
+fn read_attr<'a>(r: &mut Reader<'a>, format: Format, form: u64, implicit_const: i64) -> Option<Attr<'a>> {
+    let offset_size = format.offset_size;
+    Some(match form {
+        DW_FORM_ADDR => Attr::Addr(r.uint(format.address_size)?),
+
+        DW_FORM_DATA1 | DW_FORM_REF1 | DW_FORM_FLAG | DW_FORM_STRX1 | DW_FORM_ADDRX1 => {
+            resolve_form(form, r.u8()? as u64)
+        }
+        DW_FORM_DATA2 | DW_FORM_REF2 | DW_FORM_STRX2 | DW_FORM_ADDRX2 => {
+            resolve_form(form, r.u16()? as u64)
+        }
+        DW_FORM_STRX3 | DW_FORM_ADDRX3 => resolve_form(form, r.uint(3)?),
+        DW_FORM_DATA4 | DW_FORM_REF4 | DW_FORM_REF_SUP4 | DW_FORM_STRX4 | DW_FORM_ADDRX4 => {
+            resolve_form(form, r.u32()? as u64)
+        }
+        DW_FORM_DATA8 | DW_FORM_REF8 | DW_FORM_REF_SIG8 | DW_FORM_REF_SUP8 => {
+            resolve_form(form, r.u64()?)
+        }
+        DW_FORM_DATA16 => { r.bytes(16)?; Attr::Other }
+
+        DW_FORM_UDATA | DW_FORM_REF_UDATA | DW_FORM_STRX | DW_FORM_ADDRX |
+        DW_FORM_LOCLISTX | DW_FORM_RNGLISTX | DW_FORM_GNU_ADDR_INDEX | DW_FORM_GNU_STR_INDEX => {
+            resolve_form(form, r.uleb()?)
+        }
+        DW_FORM_SDATA => { r.sleb()?; Attr::Other }
+        DW_FORM_IMPLICIT_CONST => Attr::Uint(implicit_const as u64),
+
+        DW_FORM_STRING => Attr::Str(r.cstr()?),
+        DW_FORM_STRP => Attr::StrOffset(r.offset(offset_size)?),
+        DW_FORM_LINE_STRP => Attr::LineStrOffset(r.offset(offset_size)?),
+        DW_FORM_STRP_SUP | DW_FORM_GNU_STRP_ALT | DW_FORM_GNU_REF_ALT => {
+            r.offset(offset_size)?;
+            Attr::Other
+        }
+
+        DW_FORM_REF_ADDR => {
+            let size = if format.version <= 2 { format.address_size } else { offset_size };
+            Attr::Ref(r.uint(size)?)
+        }
+        DW_FORM_SEC_OFFSET => Attr::Uint(r.offset(offset_size)?),
+
+        DW_FORM_FLAG_PRESENT => Attr::Uint(1),
+
+        DW_FORM_BLOCK1 => { let len = r.u8()?  as usize; r.bytes(len)?; Attr::Other }
+        DW_FORM_BLOCK2 => { let len = r.u16()? as usize; r.bytes(len)?; Attr::Other }
+        DW_FORM_BLOCK4 => { let len = r.u32()? as usize; r.bytes(len)?; Attr::Other }
+        DW_FORM_BLOCK | DW_FORM_EXPRLOC => {
+            let len = r.uleb()? as usize;
+            r.bytes(len)?;
+            Attr::Other
+        }
+
+        DW_FORM_INDIRECT => {
+            let form = r.uleb()?;
+            let implicit_const = if form == DW_FORM_IMPLICIT_CONST { r.sleb()? } else { 0 };
+            return read_attr(r, format, form, implicit_const);
+        }
+
+        _ => return None,
+    })
+}
+
+fn resolve_form<'a>(form: u64, value: u64) -> Attr<'a> {
+    match form {
+        DW_FORM_REF1 | DW_FORM_REF2 | DW_FORM_REF4 | DW_FORM_REF8 | DW_FORM_REF_UDATA => Attr::UnitRef(value),
+        DW_FORM_STRX | DW_FORM_STRX1 | DW_FORM_STRX2 | DW_FORM_STRX3 | DW_FORM_STRX4 |
+        DW_FORM_GNU_STR_INDEX => Attr::StrIndex(value),
+        DW_FORM_ADDRX | DW_FORM_ADDRX1 | DW_FORM_ADDRX2 | DW_FORM_ADDRX3 | DW_FORM_ADDRX4 |
+        DW_FORM_GNU_ADDR_INDEX => Attr::AddrIndex(value),
+        DW_FORM_REF_SIG8 | DW_FORM_REF_SUP4 | DW_FORM_REF_SUP8 => Attr::Other,
+        _ => Attr::Uint(value),
+    }
+}
+
+fn is_tombstone(address: u64, address_size: u8) -> bool {
+    let max = match address_size {
+        1..=7 => (1u64 << (8*address_size as u32)) - 1,
+        _ => u64::MAX,
+    };
+    return address == max;
+}
+
+fn join(dir: &str, path: &str) -> String {
+    let bytes = path.as_bytes();
+    let absolute = bytes.first().map_or(false, |c| *c == b'/' || *c == b'\\')
+        || bytes.get(1) == Some(&b':');
+    if absolute || dir.is_empty() {
+        return path.into();
+    }
+    if path.is_empty() {
+        return dir.into();
+    }
+    let dir = dir.trim_end_matches(|c| c == '/' || c == '\\');
+    return format!("{dir}/{path}");
+}
+
+
+#[derive(Clone, Copy)]
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    #[inline]
+    fn is_done(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    #[inline]
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let result = self.data.get(self.pos..end)?;
+        self.pos = end;
+        return Some(result);
+    }
+
+    #[inline]
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    #[inline]
+    fn u16(&mut self) -> Option<u16> {
+        Some(self.uint(2)? as u16)
+    }
+
+    #[inline]
+    fn u32(&mut self) -> Option<u32> {
+        Some(self.uint(4)? as u32)
+    }
+
+    #[inline]
+    fn u64(&mut self) -> Option<u64> {
+        self.uint(8)
+    }
+
+    /// little endian, `size <= 8`.
+    fn uint(&mut self, size: u8) -> Option<u64> {
+        if size > 8 {
+            return None;
+        }
+        let bytes = self.bytes(size as usize)?;
+        let mut result = 0;
+        for (i, b) in bytes.iter().enumerate() {
+            result |= (*b as u64) << (8*i);
+        }
+        return Some(result);
+    }
+
+    fn offset(&mut self, offset_size: u8) -> Option<u64> {
+        self.uint(offset_size)
+    }
+
+    /// - returns the length & the offset size.
+    fn initial_length(&mut self) -> Option<(usize, u8)> {
+        let len = self.u32()?;
+        if len == 0xffff_ffff {
+            return Some((usize::try_from(self.u64()?).ok()?, 8));
+        }
+        if len >= 0xffff_fff0 {
+            return None;
+        }
+        return Some((len as usize, 4));
+    }
+
+    fn uleb(&mut self) -> Option<u64> {
+        let mut result = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+        }
+    }
+
+    fn sleb(&mut self) -> Option<i64> {
+        let mut result = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                result |= ((byte & 0x7f) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1 << shift;
+                }
+                return Some(result);
+            }
+        }
+    }
+
+    /// a nul terminated string, without the nul.
+    fn cstr(&mut self) -> Option<&'a [u8]> {
+        let rest = self.data.get(self.pos..)?;
+        let len = rest.iter().position(|b| *b == 0)?;
+        self.pos += len + 1;
+        return Some(&rest[..len]);
+    }
+}
+
+
+const DW_TAG_COMPILE_UNIT: u64 = 0x11;
+const DW_TAG_SUBPROGRAM:   u64 = 0x2e;
+const DW_TAG_PARTIAL_UNIT: u64 = 0x3c;
+
+const DW_AT_NAME:              u64 = 0x03;
+const DW_AT_STMT_LIST:         u64 = 0x10;
+const DW_AT_LOW_PC:            u64 = 0x11;
+const DW_AT_HIGH_PC:           u64 = 0x12;
+const DW_AT_COMP_DIR:          u64 = 0x1b;
+const DW_AT_ABSTRACT_ORIGIN:   u64 = 0x31;
+const DW_AT_SPECIFICATION:     u64 = 0x47;
+const DW_AT_LINKAGE_NAME:      u64 = 0x6e;
+const DW_AT_STR_OFFSETS_BASE:  u64 = 0x72;
+const DW_AT_ADDR_BASE:         u64 = 0x73;
+const DW_AT_MIPS_LINKAGE_NAME: u64 = 0x2007;
+const DW_AT_GNU_ADDR_BASE:     u64 = 0x2133;
+
+const DW_FORM_ADDR:           u64 = 0x01;
+const DW_FORM_BLOCK2:         u64 = 0x03;
+const DW_FORM_BLOCK4:         u64 = 0x04;
+const DW_FORM_DATA2:          u64 = 0x05;
+const DW_FORM_DATA4:          u64 = 0x06;
+const DW_FORM_DATA8:          u64 = 0x07;
+const DW_FORM_STRING:         u64 = 0x08;
+const DW_FORM_BLOCK:          u64 = 0x09;
+const DW_FORM_BLOCK1:         u64 = 0x0a;
+const DW_FORM_DATA1:          u64 = 0x0b;
+const DW_FORM_FLAG:           u64 = 0x0c;
+const DW_FORM_SDATA:          u64 = 0x0d;
+const DW_FORM_STRP:           u64 = 0x0e;
+const DW_FORM_UDATA:          u64 = 0x0f;
+const DW_FORM_REF_ADDR:       u64 = 0x10;
+const DW_FORM_REF1:           u64 = 0x11;
+const DW_FORM_REF2:           u64 = 0x12;
+const DW_FORM_REF4:           u64 = 0x13;
+const DW_FORM_REF8:           u64 = 0x14;
+const DW_FORM_REF_UDATA:      u64 = 0x15;
+const DW_FORM_INDIRECT:       u64 = 0x16;
+const DW_FORM_SEC_OFFSET:     u64 = 0x17;
+const DW_FORM_EXPRLOC:        u64 = 0x18;
+const DW_FORM_FLAG_PRESENT:   u64 = 0x19;
+const DW_FORM_STRX:           u64 = 0x1a;
+const DW_FORM_ADDRX:          u64 = 0x1b;
+const DW_FORM_REF_SUP4:       u64 = 0x1c;
+const DW_FORM_STRP_SUP:       u64 = 0x1d;
+const DW_FORM_DATA16:         u64 = 0x1e;
+const DW_FORM_LINE_STRP:      u64 = 0x1f;
+const DW_FORM_REF_SIG8:       u64 = 0x20;
+const DW_FORM_IMPLICIT_CONST: u64 = 0x21;
+const DW_FORM_LOCLISTX:       u64 = 0x22;
+const DW_FORM_RNGLISTX:       u64 = 0x23;
+const DW_FORM_REF_SUP8:       u64 = 0x24;
+const DW_FORM_STRX1:          u64 = 0x25;
+const DW_FORM_STRX2:          u64 = 0x26;
+const DW_FORM_STRX3:          u64 = 0x27;
+const DW_FORM_STRX4:          u64 = 0x28;
+const DW_FORM_ADDRX1:         u64 = 0x29;
+const DW_FORM_ADDRX2:         u64 = 0x2a;
+const DW_FORM_ADDRX3:         u64 = 0x2b;
+const DW_FORM_ADDRX4:         u64 = 0x2c;
+const DW_FORM_GNU_ADDR_INDEX: u64 = 0x1f01;
+const DW_FORM_GNU_STR_INDEX:  u64 = 0x1f02;
+const DW_FORM_GNU_REF_ALT:    u64 = 0x1f20;
+const DW_FORM_GNU_STRP_ALT:   u64 = 0x1f21;
+
+const DW_LNS_COPY:             u8 = 0x01;
+const DW_LNS_ADVANCE_PC:       u8 = 0x02;
+const DW_LNS_ADVANCE_LINE:     u8 = 0x03;
+const DW_LNS_SET_FILE:         u8 = 0x04;
+const DW_LNS_SET_COLUMN:       u8 = 0x05;
+const DW_LNS_CONST_ADD_PC:     u8 = 0x08;
+const DW_LNS_FIXED_ADVANCE_PC: u8 = 0x09;
+
+const DW_LNE_END_SEQUENCE: u8 = 0x01;
+const DW_LNE_SET_ADDRESS:  u8 = 0x02;
+
+const DW_LNCT_PATH:            u64 = 0x1;
+const DW_LNCT_DIRECTORY_INDEX: u64 = 0x2;
@@ -0,0 +1,117 @@
+use core::any::Any;
+
+use crate::{Error, ExternRef, RefValue};
+use crate::store::Store;
+
+
+/// the host objects of a store's `externref`s.
+/// - a `RefValue` id is a slot index (low 24 bits)
+///   & the slot's generation (high 8 bits).
+/// - freed slots are reused with the next generation,
+///   so references to dropped objects can't refer to later objects.
+///   slots whose generations ran out are retired.
+#[derive(Default)]
+pub(crate) struct ExternRefs {
+    slots: Vec<ExternRefSlot>,
+    free: Vec<u32>,
+    live: usize,
+}
+
+struct ExternRefSlot {
+    generation: u32,
+    object: Option<Box<dyn Any>>,
+}
+
+impl ExternRefs {
+    const INDEX_BITS: u32 = 24;
+    const INDEX_MASK: u32 = (1 << Self::INDEX_BITS) - 1;
+    const MAX_GENERATION: u32 = u32::MAX >> Self::INDEX_BITS;
+    // the last index isn't used, as `u32::MAX` is null.
+    const MAX_SLOTS: usize = Self::INDEX_MASK as usize;
+
+    #[inline]
+    fn slot(&self, r: ExternRef) -> Option<&ExternRefSlot> {
+        let slot = self.slots.get((r.0.id & Self::INDEX_MASK) as usize)?;
+        (slot.generation == r.0.id >> Self::INDEX_BITS).then_some(slot)
+    }
+
+    #[inline]
+    fn slot_mut(&mut self, r: ExternRef) -> Option<&mut ExternRefSlot> {
+        let slot = self.slots.get_mut((r.0.id & Self::INDEX_MASK) as usize)?;
+        (slot.generation == r.0.id >> Self::INDEX_BITS).then_some(slot)
+    }
+
+    /// removes the object of `r`'s slot & frees the slot.
+    fn free(&mut self, r: ExternRef) -> Option<Box<dyn Any>> {
+        let slot = self.slot_mut(r)?;
+        let object = slot.object.take()?;
+        if slot.generation < Self::MAX_GENERATION {
+            slot.generation += 1;
+            self.free.push(r.0.id & Self::INDEX_MASK);
+        }
+        self.live -= 1;
+        return Some(object);
+    }
+}
+
+impl Store {
+    /// creates an `externref` for a host object.
+    /// - the store owns the object, until it is taken or dropped,
+    ///   or until the store is dropped.
+    /// - wasm can't access the object, only pass the reference around.
+    /// - fails with `Error::OOM` if the store has no free `externref` slots.
+    pub fn new_extern_ref<T: Any>(&mut self, object: T) -> Result<ExternRef, Error> {
+        let refs = &mut self.extern_refs;
+        let index = match refs.free.pop() {
+            Some(index) => index,
+            None => {
+                if refs.slots.len() >= ExternRefs::MAX_SLOTS {
+                    return Err(Error::OOM);
+                }
+                refs.slots.push(ExternRefSlot { generation: 0, object: None });
+                (refs.slots.len() - 1) as u32
+            }
+        };
+
+        let slot = &mut refs.slots[index as usize];
+        slot.object = Some(Box::new(object));
+        refs.live += 1;
+        return Ok(ExternRef(RefValue { id: (slot.generation << ExternRefs::INDEX_BITS) | index }));
+    }
+
+    /// the object of an `externref`, if it is a `T`.
+    /// - returns `None` for null & dropped references.
+    #[inline]
+    pub fn extern_ref<T: Any>(&self, r: ExternRef) -> Option<&T> {
+        self.extern_refs.slot(r)?.object.as_ref()?.downcast_ref()
+    }
+
+    #[inline]
+    pub fn extern_ref_mut<T: Any>(&mut self, r: ExternRef) -> Option<&mut T> {
+        self.extern_refs.slot_mut(r)?.object.as_mut()?.downcast_mut()
+    }
+
+    /// removes the object of an `externref`, if it is a `T`.
+    /// - the reference is dropped.
+    pub fn take_extern_ref<T: Any>(&mut self, r: ExternRef) -> Option<T> {
+        if !self.extern_refs.slot(r)?.object.as_ref()?.is::<T>() {
+            return None;
+        }
+        let object = self.extern_refs.free(r)?.downcast().ok()?;
+        return Some(*object);
+    }
+
+    /// drops the object of an `externref`.
+    /// - wasm can keep using the reference,
+    ///   but the store no longer resolves it.
+    /// - returns whether the reference was live.
+    pub fn drop_extern_ref(&mut self, r: ExternRef) -> bool {
+        self.extern_refs.free(r).is_some()
+    }
+
+    /// the number of live `externref` objects.
+    #[inline]
+    pub fn num_extern_refs(&self) -> usize {
+        self.extern_refs.live
+    }
+}
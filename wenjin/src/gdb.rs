@@ -0,0 +1,433 @@
+use std::io::{self, Read, Write, BufRead, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::fmt::Write as _;
+
+use crate::{Error, DebugEvent, DebugAction};
+use crate::store::{Store, FuncKind, StackValue, InstanceId, FuncId};
+use crate::memory::Memory;
+
+
+/// a gdb remote serial protocol stub,
+/// with the wasm extensions supported by lldb.
+/// - connect with `lldb`, then `process connect connect://<addr>`.
+/// - addresses use lldb's wasm encoding:
+///   the top 2 bits are the address space (0: memory, 1: code),
+///   the next 30 bits the instance, the low 32 bits the offset.
+///   code offsets are byte offsets into the module's binary.
+/// - the stub only talks to the debugger while wasm is stopped.
+///   interrupting running wasm isn't supported.
+/// - a `kill` aborts the stopped call with `Error::Interrupted`.
+pub struct GdbStub {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    ack: bool,
+    // whether the debugger is waiting for a stop reply.
+    running: bool,
+    detached: bool,
+}
+
+enum Command {
+    Reply(String),
+    Resume(DebugAction),
+    Detach,
+    Kill,
+}
+
+const SPACE_MEMORY: u64 = 0;
+const SPACE_CODE: u64 = 1;
+
+const TRIPLE: &str = "wasm32-unknown-unknown-wasm";
+
+impl GdbStub {
+    /// waits for a debugger to connect.
+    pub fn accept(listener: &TcpListener) -> io::Result<GdbStub> {
+        let (stream, _) = listener.accept()?;
+        return GdbStub::new(stream);
+    }
+
+    pub fn new(stream: TcpStream) -> io::Result<GdbStub> {
+        stream.set_nodelay(true)?;
+        return Ok(GdbStub {
+            writer: stream.try_clone()?,
+            reader: BufReader::new(stream),
+            ack: true,
+            running: false,
+            detached: false,
+        });
+    }
+
+    /// makes the stub the store's debug handler.
+    /// - the next wasm call stops at its first instruction,
+    ///   so the debugger can set breakpoints.
+    pub fn attach(mut self, store: &mut Store) {
+        store.set_debug_handler(move |store: &mut Store, event| self.stopped(store, event));
+        store.break_next();
+    }
+
+
+    fn stopped(&mut self, store: &mut Store, event: DebugEvent) -> Result<DebugAction, Error> {
+        if self.detached {
+            return Ok(DebugAction::Continue);
+        }
+
+        match self.serve(store, event) {
+            Ok(Some(action)) => Ok(action),
+
+            Ok(None) => {
+                self.detach(store);
+                Err(Error::Interrupted)
+            }
+
+            // detach, if the debugger went away.
+            Err(_) => {
+                self.detach(store);
+                Ok(DebugAction::Continue)
+            }
+        }
+    }
+
+    /// returns `None` if the debugger killed the call.
+    fn serve(&mut self, store: &mut Store, event: DebugEvent) -> io::Result<Option<DebugAction>> {
+        if self.running {
+            self.running = false;
+            self.send(&stop_reply(store, event))?;
+        }
+
+        loop {
+            let packet = self.recv()?;
+            match command(store, event, &packet) {
+                Command::Reply(reply) => {
+                    self.send(&reply)?;
+                    if packet == "QStartNoAckMode" {
+                        self.ack = false;
+                    }
+                }
+
+                Command::Resume(action) => {
+                    self.running = true;
+                    return Ok(Some(action));
+                }
+
+                Command::Detach => {
+                    self.send("OK")?;
+                    self.detach(store);
+                    return Ok(Some(DebugAction::Continue));
+                }
+
+                Command::Kill => {
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    fn detach(&mut self, store: &mut Store) {
+        self.detached = true;
+        store.clear_breakpoints();
+    }
+
+
+    fn recv(&mut self) -> io::Result<String> {
+        // skip acks & interrupts.
+        let mut skipped = Vec::new();
+        self.reader.read_until(b'$', &mut skipped)?;
+        if skipped.last() != Some(&b'$') {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let mut packet = Vec::new();
+        self.reader.read_until(b'#', &mut packet)?;
+        if packet.pop() != Some(b'#') {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let mut checksum = [0; 2];
+        self.reader.read_exact(&mut checksum)?;
+        let valid = std::str::from_utf8(&checksum).ok()
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            == Some(checksum_of(&packet));
+
+        if self.ack {
+            self.writer.write_all(if valid { b"+" } else { b"-" })?;
+            if !valid {
+                return self.recv();
+            }
+        }
+
+        return String::from_utf8(packet).map_err(|_| io::ErrorKind::InvalidData.into());
+    }
+
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        let packet = format!("${data}#{:02x}", checksum_of(data.as_bytes()));
+        loop {
+            self.writer.write_all(packet.as_bytes())?;
+            if !self.ack {
+                return Ok(());
+            }
+
+            let mut ack = [0];
+            self.reader.read_exact(&mut ack)?;
+            if ack[0] == b'+' {
+                return Ok(());
+            }
+        }
+    }
+}
+
+
+fn command(store: &mut Store, event: DebugEvent, packet: &str) -> Command {
+    let reply = |r: &str| Command::Reply(r.into());
+    let error = || reply("E03");
+
+    if let Some(args) = packet.strip_prefix("qWasm") {
+        let Some(reply) = wasm_query(store, args) else { return error() };
+        return Command::Reply(reply);
+    }
+
+    if let Some(args) = packet.strip_prefix("qXfer:libraries:read::") {
+        let Some((offset, len)) = args.split_once(',') else { return error() };
+        let (Some(offset), Some(len)) = (parse_hex(offset), parse_hex(len)) else { return error() };
+        let xml = library_list(store);
+        let begin = (offset as usize).min(xml.len());
+        let end = begin.saturating_add(len as usize).min(xml.len());
+        let more = if end < xml.len() { "m" } else { "l" };
+        return Command::Reply(format!("{more}{}", &xml[begin..end]));
+    }
+
+    if let Some(args) = packet.strip_prefix("qRegisterInfo") {
+        if parse_hex(args) != Some(0) {
+            return reply("E45");
+        }
+        return reply("name:pc;alt-name:pc;bitsize:64;offset:0;encoding:uint;format:hex;set:General Purpose Registers;gcc:16;dwarf:16;generic:pc;");
+    }
+
+    let (kind, args) = packet.split_at(packet.chars().next().map_or(0, |c| c.len_utf8()));
+    match kind {
+        "?" => Command::Reply(stop_reply(store, event)),
+
+        "q" if args.starts_with("Supported") => reply("PacketSize=4000;QStartNoAckMode+;qXfer:libraries:read+"),
+        "q" if args == "HostInfo" => Command::Reply(format!(
+            "vendor:unknown;ostype:wasi;arch:wasm32;triple:{};endian:little;ptrsize:4;",
+            hex(TRIPLE.as_bytes()))),
+        "q" if args == "ProcessInfo" => Command::Reply(format!(
+            "pid:1;parent-pid:1;vendor:unknown;ostype:wasi;arch:wasm32;triple:{};endian:little;ptrsize:4;",
+            hex(TRIPLE.as_bytes()))),
+        "q" if args == "C" => reply("QC1"),
+        "q" if args == "fThreadInfo" => reply("m1"),
+        "q" if args == "sThreadInfo" => reply("l"),
+        "q" if args.starts_with("ThreadStopInfo") => Command::Reply(stop_reply(store, event)),
+        "Q" if args == "StartNoAckMode" => reply("OK"),
+        "H" => reply("OK"),
+
+        "g" => Command::Reply(hex(&pc(store).to_le_bytes())),
+        "p" => {
+            if parse_hex(args) != Some(0) {
+                return reply("E45");
+            }
+            Command::Reply(hex(&pc(store).to_le_bytes()))
+        }
+
+        "m" => {
+            let Some((addr, len)) = args.split_once(',') else { return error() };
+            let (Some(addr), Some(len)) = (parse_hex(addr), parse_hex(len)) else { return error() };
+            let Some(bytes) = read_memory(store, addr, len as usize) else { return error() };
+            Command::Reply(hex(&bytes))
+        }
+
+        "Z" | "z" => {
+            let mut parts = args.split(',');
+            let (Some("0"), Some(Some(addr))) = (parts.next(), parts.next().map(parse_hex)) else {
+                return reply("");
+            };
+            let Some((func, offset)) = code_func(store, addr) else { return error() };
+            if kind == "Z" {
+                if store.set_breakpoint(func, offset).is_err() {
+                    return error();
+                }
+            }
+            else {
+                store.clear_breakpoint(func, offset);
+            }
+            reply("OK")
+        }
+
+        "c" => Command::Resume(DebugAction::Continue),
+        "s" => Command::Resume(DebugAction::Step),
+        "D" => Command::Detach,
+        "k" => Command::Kill,
+
+        _ => reply(""),
+    }
+}
+
+/// the `qWasm*` queries, with the `qWasm` prefix removed.
+/// - values are little endian, like wasm's memory.
+fn wasm_query(store: &Store, args: &str) -> Option<String> {
+    if args.starts_with("CallStack") {
+        let mut result = String::new();
+        for frame in store.debug_frames() {
+            result.push_str(&hex(&address(SPACE_CODE, frame.instance, frame.offset).to_le_bytes()));
+        }
+        return Some(result);
+    }
+
+    let (query, args) = args.split_once(':')?;
+    let mut args = args.split(';').map(parse_hex);
+    let frame = args.next()?? as usize;
+    let idx = args.next()??;
+
+    let value = match query {
+        "Local" => {
+            let locals = store.debug_locals(frame).ok()?;
+            let (value, ty) = locals.get(idx as usize)?;
+            value_bytes(*value, *ty)
+        }
+
+        "StackValue" => {
+            let operands = store.debug_operands(frame).ok()?;
+            let (value, ty) = operands.get(idx as usize)?;
+            value_bytes(*value, *ty)
+        }
+
+        "Global" => {
+            let instance = store.debug_frames().get(frame)?.instance;
+            let global = store.global(store.instance_global(instance, idx as u32)?);
+            value_bytes(StackValue::from_value(global.get()), global.ty())
+        }
+
+        "Mem" => {
+            let instance = store.debug_frames().get(frame)?.instance;
+            let len = args.next()??;
+            read_linear_memory(store, instance, idx, len as usize)?
+        }
+
+        _ => return None,
+    };
+    return Some(hex(&value));
+}
+
+fn stop_reply(store: &Store, event: DebugEvent) -> String {
+    let pc = pc(store);
+    let reason = match event {
+        DebugEvent::Breakpoint => "breakpoint",
+        DebugEvent::Step => "trace",
+    };
+    return format!("T05thread:1;name:wasm;thread-pcs:{pc:x};00:{};reason:{reason};", hex(&pc.to_le_bytes()));
+}
+
+fn pc(store: &Store) -> u64 {
+    let Some(frame) = store.debug_frames().first().copied() else { return 0 };
+    return address(SPACE_CODE, frame.instance, frame.offset);
+}
+
+fn library_list(store: &Store) -> String {
+    let mut result = String::from("<library-list>");
+    for inst in store.instances.inner() {
+        let inst = unsafe { &*inst.get() };
+        let id = inst.id.inner();
+        let name = inst.module.names.module.map_or_else(|| format!("instance{id}.wasm"), |name| name.into());
+        let addr = address(SPACE_CODE, inst.id, 0);
+        _ = write!(result, "<library name=\"{}\"><section address=\"0x{addr:x}\"/></library>", xml_escape(&name));
+    }
+    result.push_str("</library-list>");
+    return result;
+}
+
+
+fn address(space: u64, instance: InstanceId, offset: u32) -> u64 {
+    space << 62 | (instance.inner() as u64) << 32 | offset as u64
+}
+
+fn instance_at(store: &Store, addr: u64) -> Option<InstanceId> {
+    let id = (addr >> 32 & 0x3fff_ffff) as u32;
+    let inst = store.instances.inner().iter().find(|inst| unsafe { &*inst.get() }.id.inner() == id)?;
+    return Some(unsafe { &*inst.get() }.id);
+}
+
+/// the wasm function whose code contains a code address.
+fn code_func(store: &Store, addr: u64) -> Option<(FuncId, u32)> {
+    if addr >> 62 != SPACE_CODE {
+        return None;
+    }
+    let instance = instance_at(store, addr)?;
+    let inst = unsafe { &*store.instances[instance].get() };
+    let pc = inst.compiled.wasm.as_ptr().wrapping_add(addr as u32 as usize);
+
+    for func in inst.funcs.inner() {
+        let func = unsafe { &*func.get() };
+        let FuncKind::Interp(f) = &func.kind else { continue };
+        if f.instance == instance && pc >= f.code_begin() && pc < f.code_end() {
+            return Some((func.id, addr as u32));
+        }
+    }
+    return None;
+}
+
+/// reads up to `len` bytes.
+fn read_memory(store: &Store, addr: u64, len: usize) -> Option<Vec<u8>> {
+    let instance = instance_at(store, addr)?;
+    let offset = addr as u32;
+    match addr >> 62 {
+        SPACE_MEMORY => read_linear_memory(store, instance, offset as u64, len),
+
+        SPACE_CODE => {
+            let inst = unsafe { &*store.instances[instance].get() };
+            let wasm = &inst.compiled.wasm;
+            let begin = offset as usize;
+            if begin >= wasm.len() {
+                return None;
+            }
+            let end = begin.saturating_add(len).min(wasm.len());
+            Some(wasm[begin..end].to_vec())
+        }
+
+        _ => None,
+    }
+}
+
+/// reads up to `len` bytes of the instance's first memory.
+fn read_linear_memory(store: &Store, instance: InstanceId, addr: u64, len: usize) -> Option<Vec<u8>> {
+    let inst = unsafe { &*store.instances[instance].get() };
+    let memory = Memory::new(inst.memories.inner().first()?);
+    let size = memory.size_bytes();
+
+    let begin = usize::try_from(addr).ok()?;
+    if begin >= size {
+        return None;
+    }
+    let len = len.min(size - begin);
+
+    let mut result = vec![0; len];
+    let slice = crate::WasmSlice64 { ptr: crate::WasmPtr64::new(addr), len: crate::WasmSize64(len as u64) };
+    unsafe { memory.read_slice64(slice, result.as_mut_ptr()).ok()? };
+    return Some(result);
+}
+
+fn value_bytes(value: StackValue, ty: wasm::ValueType) -> Vec<u8> {
+    match ty {
+        wasm::ValueType::I64 => value.as_i64().to_le_bytes().to_vec(),
+        wasm::ValueType::F64 => value.as_f64().to_le_bytes().to_vec(),
+        _ => value.as_i32().to_le_bytes().to_vec(),
+    }
+}
+
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |acc, b| acc.wrapping_add(*b))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(2*bytes.len());
+    for b in bytes {
+        _ = write!(result, "{b:02x}");
+    }
+    return result;
+}
+
+fn parse_hex(s: &str) -> Option<u64> {
+    u64::from_str_radix(s, 16).ok()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -1,12 +1,16 @@
+use core::cell::UnsafeCell;
 use core::hint::unreachable_unchecked;
+use core::sync::atomic::Ordering;
 
 use sti::traits::UnwrapDebug;
 use sti::hash::HashMap;
+use sti::rc::Rc;
 
 use wasm::Opcode;
 
-use crate::{Error, Table, Memory, Global, InstanceId};
-use crate::store::{Store, FuncKind, StackValue, StackFrame, FuncId};
+use crate::{Error, Table, Memory, Global, RefValue, InstanceId, DebugEvent, DebugAction};
+use crate::memory::MemoryData;
+use crate::store::{Store, FuncKind, StackValue, StackFrame, Suspended, FuncId};
 
 
 #[derive(Debug)]
@@ -25,9 +29,13 @@ struct State {
     stack_frame_end: *mut StackValue,
     stack_alloc_end: *mut StackValue,
 
+    // the instance's memories.
+    // memory 0 is cached below.
+    memories: *const [Rc<UnsafeCell<MemoryData>>],
     memory_data: Option<Memory<'static>>,
     memory: *mut u8,
     memory_size: usize,
+    memory64: bool,
 }
 
 impl State {
@@ -181,12 +189,60 @@ impl State {
         self.pc = unsafe { self.code_begin.add(target as usize) };
     }
 
+    /// reload the cached memory pointer & size.
+    /// - required after anything that may have grown the memory.
+    ///   that includes calls, as the memory may be shared with other instances.
     #[inline]
-    fn mem_bounds_check(&self, addr: u32, offset: u32, size: u32) -> Result<(), Error> {
+    fn sync_memory(&mut self) {
+        if let Some(mem) = self.memory_data.as_mut() {
+            (self.memory, self.memory_size) = mem.as_mut_ptr();
+            self.memory64 = mem.is64();
+        }
+    }
+
+    #[inline]
+    fn memory_data(&self, mem: u32) -> Memory<'static> {
+        Memory::new(unsafe { &(*self.memories)[mem as usize] })
+    }
+
+    /// the base & size of one of the instance's memories,
+    /// and whether it has `i64` addresses.
+    /// - memory 0 is cached, the others are looked up.
+    #[inline]
+    fn memory(&self, mem: u32) -> (*mut u8, usize, bool) {
+        if mem == 0 {
+            return (self.memory, self.memory_size, self.memory64);
+        }
+        let mut mem = self.memory_data(mem);
+        let (memory, memory_size) = mem.as_mut_ptr();
+        return (memory, memory_size, mem.is64());
+    }
+
+    /// the memory index & offset of a `memarg`.
+    /// - the memory index follows the alignment, if its bit 6 is set.
+    #[inline]
+    fn next_memarg(&mut self) -> (u32, u64) {
+        let align = self.next_u32();
+        let mem = if align & 0x40 != 0 { self.next_u32() } else { 0 };
+        let offset = self.next_u64();
+        return (mem, offset);
+    }
+
+    /// pops an address or size of a memory.
+    #[inline]
+    fn pop_addr(&mut self, memory64: bool) -> u64 {
+        if memory64 {
+            return self.pop().as_i64() as u64;
+        }
+        return self.pop().as_i32() as u32 as u64;
+    }
+
+    #[inline]
+    fn mem_bounds_check(memory_size: usize, addr: u64, offset: u64, size: u64) -> Result<(), Error> {
         // check addr+offset+N <= memory_size
         if let Some(x) = addr.checked_add(offset) {
             if let Some(end) = x.checked_add(size) {
-                if end as usize <= self.memory_size {
+                if end <= memory_size as u64 {
                     return Ok(())
                 }
             }
@@ -195,28 +251,28 @@ impl State {
     }
 
     #[inline]
-    fn load<const N: usize>(&mut self, addr: u32, offset: u32) -> Result<[u8; N], Error> {
-        self.mem_bounds_check(addr, offset, N as u32)?;
+    fn load<const N: usize>(memory: *mut u8, memory_size: usize, addr: u64, offset: u64) -> Result<[u8; N], Error> {
+        Self::mem_bounds_check(memory_size, addr, offset, N as u64)?;
         unsafe {
-            let ptr = self.memory.add((addr + offset) as usize);
+            let ptr = memory.add((addr + offset) as usize);
             Ok(ptr.cast::<[u8; N]>().read())
         }
     }
 
     #[inline]
     fn load_op<const N: usize>(&mut self) -> Result<[u8; N], Error> {
-        let _align = self.next_u32();
-        let offset = self.next_u32();
-        let addr = self.pop().as_i32() as u32;
-        self.load(addr, offset)
+        let (mem, offset) = self.next_memarg();
+        let (memory, memory_size, memory64) = self.memory(mem);
+        let addr = self.pop_addr(memory64);
+        Self::load(memory, memory_size, addr, offset)
     }
 
     #[must_use]
     #[inline]
-    fn store<const N: usize>(&mut self, addr: u32, offset: u32, value: [u8; N]) -> Result<(), Error> {
-        self.mem_bounds_check(addr, offset, N as u32)?;
+    fn store<const N: usize>(memory: *mut u8, memory_size: usize, addr: u64, offset: u64, value: [u8; N]) -> Result<(), Error> {
+        Self::mem_bounds_check(memory_size, addr, offset, N as u64)?;
         unsafe {
-            let ptr = self.memory.add((addr + offset) as usize);
+            let ptr = memory.add((addr + offset) as usize);
             ptr.cast::<[u8; N]>().write(value);
             Ok(())
         }
@@ -225,10 +281,10 @@ impl State {
     #[must_use]
     #[inline]
     fn store_op<const N: usize>(&mut self, value: [u8; N]) -> Result<(), Error> {
-        let _align = self.next_u32();
-        let offset = self.next_u32();
-        let addr = self.pop().as_i32() as u32;
-        self.store(addr, offset, value)
+        let (mem, offset) = self.next_memarg();
+        let (memory, memory_size, memory64) = self.memory(mem);
+        let addr = self.pop_addr(memory64);
+        Self::store(memory, memory_size, addr, offset, value)
     }
 }
 
@@ -236,11 +292,23 @@ impl Store {
     pub(crate) fn run_interp(&mut self, init_func: FuncId) -> (Result<(), Error>,) {
         assert!(!self.thread.trapped);
 
-        let mut state = unsafe {
+        if self.interrupted() {
+            return (Err(Error::Interrupted),);
+        }
+
+        // only calls that aren't nested in host functions can be suspended.
+        let is_root = self.thread.frames.len() == 0;
+
+        let state = unsafe {
             let func = &*self.funcs[init_func].get();
             let FuncKind::Interp(f) = &func.kind else { unreachable!() };
 
             let stack = &mut self.thread.stack;
+
+            if self.thread.frames.len() >= self.max_call_depth
+            || stack.len() + (f.stack_size - f.num_params) as usize > self.max_stack_size {
+                return (Err(self.trap(Error::TrapStackOverflow, None)),);
+            }
             stack.reserve_extra((f.stack_size - f.num_params) as usize);
 
             let stack_ptr = stack.as_mut_ptr();
@@ -259,13 +327,16 @@ impl Store {
 
 
             let inst = &*self.instances[f.instance].get();
+            let memories = &inst.memories.inner()[..] as *const _;
 
             let mut memory_data = None;
             let mut memory = core::ptr::null_mut();
             let mut memory_size = 0;
+            let mut memory64 = false;
             if let Some(mem) = inst.memories.inner().get(0) {
                 let mut mem = Memory::new(mem);
                 (memory, memory_size) = mem.as_mut_ptr();
+                memory64 = mem.is64();
                 memory_data = Some(mem);
             }
 
@@ -278,18 +349,149 @@ impl Store {
                 pc: f.code_begin(),
                 code_begin: f.code_begin(),
                 code_end: f.code_end(),
-                jumps: &f.jumps as *const _,
+                jumps: f.jumps,
                 bp,
                 sp,
                 locals_end,
                 stack_frame_end,
                 stack_alloc_end,
+                memories,
                 memory_data,
                 memory,
                 memory_size,
+                memory64,
             }
         };
 
+        let root = is_root.then(|| {
+            let bp = unsafe { state.bp.offset_from(self.thread.stack.as_ptr()) as usize };
+            (init_func, bp)
+        });
+
+        if self.tracing() {
+            let depth = self.thread.frames.len();
+            self.trace_call(init_func, state.bp);
+            let result = self.interp::<true>(state, root);
+            self.trace_end(depth);
+            return result;
+        }
+        self.interp::<false>(state, root)
+    }
+
+    #[inline]
+    fn interrupted(&self) -> bool {
+        // relaxed, as interrupts don't publish any data.
+        self.interrupt_epoch.load(Ordering::Relaxed) != self.call_epoch
+    }
+
+    /// continues the suspended call.
+    pub(crate) fn resume_interp(&mut self) -> (Result<(), Error>,) {
+        assert!(!self.thread.trapped);
+
+        let s = self.thread.suspended.take().unwrap();
+        self.begin_epoch();
+
+        let state = unsafe {
+            let func = &*self.funcs[s.func].get();
+            let FuncKind::Interp(f) = &func.kind else { unreachable!() };
+
+            let stack = &mut self.thread.stack;
+            let stack_ptr = stack.as_mut_ptr();
+            let bp = stack_ptr.add(s.bp);
+            debug_assert!(s.bp + f.stack_size as usize <= stack.cap());
+
+            let inst = &*self.instances[s.instance].get();
+            let memories = &inst.memories.inner()[..] as *const _;
+            let memory_data = inst.memories.inner().get(0).map(|mem| Memory::new(mem));
+
+            let mut state = State {
+                instance: s.instance,
+                func: s.func,
+                pc: s.pc.as_ptr(),
+                code_begin: f.code_begin(),
+                code_end: f.code_end(),
+                jumps: f.jumps,
+                bp,
+                sp: stack_ptr.add(stack.len()),
+                locals_end: bp.add(f.num_locals as usize),
+                stack_frame_end: bp.add(f.stack_size as usize),
+                stack_alloc_end: stack_ptr.add(stack.cap()),
+                memories,
+                memory_data,
+                memory: core::ptr::null_mut(),
+                memory_size: 0,
+                memory64: false,
+            };
+            state.sync_memory();
+            state
+        };
+
+        let root = Some((s.root_func, s.root_bp));
+        if self.tracing() {
+            let result = self.interp::<true>(state, root);
+            // suspended calls are root calls.
+            self.trace_end(0);
+            return result;
+        }
+        self.interp::<false>(state, root)
+    }
+
+    /// calls the debug handler.
+    /// - the stopped function's state is saved like for a host call,
+    ///   with an extra frame for the debugger's stack walk.
+    #[inline(never)]
+    fn debug_stop(&mut self, state: &mut State, event: DebugEvent) -> Result<(), Error> {
+        let Some(mut handler) = self.debug.take_handler() else { return Ok(()) };
+        let depth = self.thread.frames.len();
+
+        unsafe {
+            let stack = &mut self.thread.stack;
+            let stack_ptr = stack.as_ptr();
+            let bp = state.bp.offset_from(stack_ptr) as usize;
+            let sp = state.sp.offset_from(stack_ptr) as usize;
+            let locals_end = state.locals_end.offset_from(stack_ptr) as usize;
+            let stack_frame_end = state.stack_frame_end.offset_from(stack_ptr) as usize;
+            stack.set_len(sp);
+
+            self.thread.frames.push(Some(StackFrame {
+                instance: state.instance,
+                func: state.func,
+                pc: core::ptr::NonNull::new_unchecked(state.pc as *mut u8),
+                bp_offset: (sp - bp) as u32,
+            }));
+            let prev_stop = self.thread.debug_stop.replace((depth, sp));
+
+            let result = handler(self, event);
+
+            self.thread.debug_stop = prev_stop;
+            self.thread.frames.pop().unwrap_debug();
+
+            let action = *result.as_ref().unwrap_or(&DebugAction::Continue);
+            self.debug.restore_handler(handler, action, depth);
+            result?;
+
+            // the handler may have called wasm, which may have grown the stack.
+            let stack = &mut self.thread.stack;
+            debug_assert_eq!(stack.len(), sp);
+            let stack_ptr = stack.as_mut_ptr();
+            state.bp = stack_ptr.add(bp);
+            state.sp = stack_ptr.add(sp);
+            state.locals_end = stack_ptr.add(locals_end);
+            state.stack_frame_end = stack_ptr.add(stack_frame_end);
+            state.stack_alloc_end = stack_ptr.add(stack.cap());
+            state.sync_memory();
+        }
+
+        return Ok(());
+    }
+
+    /// - `root` is the root function & its base pointer,
+    ///   if the call can be suspended.
+    /// - `TRACE` calls the hooks of the tracer, the profiler & the coverage recorder.
+    fn interp<const TRACE: bool>(&mut self, mut state: State, root: Option<(FuncId, usize)>) -> (Result<(), Error>,) {
+        // start of the current instruction, for backtraces.
+        let mut op_pc = state.pc;
+
         let e = 'err: loop {
             macro_rules! vm_err {
                 ($e:expr) => {
@@ -306,6 +508,39 @@ impl Store {
                 };
             }
 
+            if self.fuel == 0 {
+                let Some((root_func, root_bp)) = root else {
+                    vm_err!(Error::OutOfFuel);
+                };
+
+                // suspend at the current instruction.
+                unsafe {
+                    let stack = &mut self.thread.stack;
+                    let stack_ptr = stack.as_ptr();
+                    stack.set_len(state.sp.offset_from(stack_ptr) as usize);
+
+                    self.thread.suspended = Some(Suspended {
+                        root_func,
+                        root_bp,
+                        instance: state.instance,
+                        func: state.func,
+                        pc: core::ptr::NonNull::new_unchecked(state.pc as *mut u8),
+                        bp: state.bp.offset_from(stack_ptr) as usize,
+                    });
+                }
+                return (Err(Error::OutOfFuel),);
+            }
+            self.fuel -= 1;
+            self.fuel_consumed += 1;
+
+            op_pc = state.pc;
+
+            if TRACE && self.debug.active {
+                if let Some(event) = self.debug.check(state.func, state.pc, self.thread.frames.len()) {
+                    vm_try!(self.debug_stop(&mut state, event));
+                }
+            }
+
             let op = unsafe { match Opcode::parse(state.next_u8()) {
                 wasm::opcode::ParseResult::Opcode(op) => op,
 
@@ -316,6 +551,11 @@ impl Store {
                 wasm::opcode::ParseResult::Error =>
                     unreachable_unchecked(),
             }};
+
+            if TRACE {
+                self.trace_instruction(state.instance, state.func, op_pc, op, (state.locals_end, state.sp));
+            }
+
             match op {
                 Opcode::Unreachable => {
                     vm_err!(Error::TrapUnreachable);
@@ -329,6 +569,9 @@ impl Store {
 
                 Opcode::Loop => {
                     let _ty = state.next_u64();
+                    if self.interrupted() {
+                        vm_err!(Error::Interrupted);
+                    }
                 }
 
                 Opcode::If => {
@@ -381,6 +624,10 @@ impl Store {
                     let num_rets = f.ty.rets.len();
 
                     let rets = state.sp.sub(num_rets);
+                    if TRACE {
+                        self.trace_ret(state.func, rets);
+                    }
+
                     if num_rets == 1 {
                         *state.bp = *rets;
                     }
@@ -396,21 +643,12 @@ impl Store {
                         let func = &*self.funcs[frame.func].get();
                         let FuncKind::Interp(f) = &func.kind else { unreachable_unchecked() };
 
+                        let mut memories = state.memories;
                         let mut memory_data = state.memory_data;
-                        let mut memory = state.memory;
-                        let mut memory_size = state.memory_size;
                         if frame.instance != state.instance {
                             let inst = &*self.instances[frame.instance].get();
-
-                            memory_data = None;
-                            memory = core::ptr::null_mut();
-                            memory_size = 0;
-
-                            if let Some(mem) = inst.memories.inner().get(0) {
-                                let mut mem = Memory::new(mem);
-                                (memory, memory_size) = mem.as_mut_ptr();
-                                memory_data = Some(mem);
-                            }
+                            memories = &inst.memories.inner()[..] as *const _;
+                            memory_data = inst.memories.inner().get(0).map(|mem| Memory::new(mem));
                         }
 
                         state = State {
@@ -419,22 +657,29 @@ impl Store {
                             pc: frame.pc.as_ptr(),
                             code_begin: f.code_begin(),
                             code_end: f.code_end(),
-                            jumps: &f.jumps as *const _,
+                            jumps: f.jumps,
                             bp,
                             sp,
                             locals_end: bp.add(f.num_locals as usize),
                             stack_frame_end: bp.add(f.stack_size as usize),
                             stack_alloc_end: state.stack_alloc_end,
+                            memories,
                             memory_data,
-                            memory,
-                            memory_size,
+                            memory: core::ptr::null_mut(),
+                            memory_size: 0,
+                            memory64: false,
                         };
+                        state.sync_memory();
                     }
                     else {
                         let sp = state.bp.add(num_rets);
                         let stack = &mut self.thread.stack;
                         stack.set_len(sp.offset_from(stack.as_ptr()) as usize);
 
+                        if self.thread.frames.len() == 0 {
+                            self.debug.end_root_call();
+                        }
+
                         return (Ok(()),);
                     }
                 }}
@@ -461,7 +706,11 @@ impl Store {
                             vm_err!(Error::TrapCallIndirectRefNull);
                         };
 
-                        let func = unsafe { &*self.funcs.inner()[func_id as usize].get() };
+                        // a ref from another store may not name a function of this one.
+                        let Some(func) = self.funcs.inner().get(func_id as usize) else {
+                            vm_err!(Error::TrapCallIndirectTypeMismatch);
+                        };
+                        let func = unsafe { &*func.get() };
 
                         let expected_ty = inst.module.types[type_idx as usize];
                         if func.ty != expected_ty {
@@ -474,22 +723,34 @@ impl Store {
                     while let FuncKind::Var(val) = &func.kind {
                         func = unsafe { &*val.as_ref().unwrap().get() };
                     }
+
+                    if self.thread.frames.len() >= self.max_call_depth {
+                        vm_err!(Error::TrapStackOverflow);
+                    }
+
                     match &func.kind {
                         FuncKind::Interp(f) => unsafe {
+                            if self.interrupted() {
+                                vm_err!(Error::Interrupted);
+                            }
+
                             let bp_offset = state.sp.offset_from(state.bp) as u32 - f.num_params;
 
+                            let stack_len = state.sp.offset_from(self.thread.stack.as_ptr()) as usize;
+                            let stack_required = (f.stack_size - f.num_params) as usize;
+                            if stack_len + stack_required > self.max_stack_size {
+                                vm_err!(Error::TrapStackOverflow);
+                            }
+
                             // grow stack.
                             let (sp, stack_alloc_end);
                             let stack_remaining = state.stack_alloc_end.offset_from(state.sp) as usize;
-                            let stack_required = (f.stack_size - f.num_params) as usize;
                             if stack_remaining >= stack_required {
                                 sp = state.sp;
                                 stack_alloc_end = state.stack_alloc_end;
                             }
                             else {
                                 let stack = &mut self.thread.stack;
-
-                                let stack_len = state.sp.offset_from(stack.as_ptr()) as usize;
                                 stack.set_len(stack_len);
                                 stack.reserve_extra(stack_required);
 
@@ -516,19 +777,24 @@ impl Store {
                             };
                             self.thread.frames.push(Some(frame));
 
+                            let mut memories = state.memories;
                             let mut memory_data = state.memory_data;
                             let mut memory = state.memory;
                             let mut memory_size = state.memory_size;
+                            let mut memory64 = state.memory64;
                             if f.instance != state.instance {
                                 let inst = &*self.instances[f.instance].get();
 
+                                memories = &inst.memories.inner()[..] as *const _;
                                 memory_data = None;
                                 memory = core::ptr::null_mut();
                                 memory_size = 0;
+                                memory64 = false;
 
                                 if let Some(mem) = inst.memories.inner().get(0) {
                                     let mut mem = Memory::new(mem);
                                     (memory, memory_size) = mem.as_mut_ptr();
+                                    memory64 = mem.is64();
                                     memory_data = Some(mem);
                                 }
                             }
@@ -539,20 +805,26 @@ impl Store {
                                 pc: f.code_begin(),
                                 code_begin: f.code_begin(),
                                 code_end: f.code_end(),
-                                jumps: &f.jumps as *const _,
+                                jumps: f.jumps,
                                 bp,
                                 sp,
                                 locals_end,
                                 stack_frame_end,
                                 stack_alloc_end,
+                                memories,
                                 memory_data,
                                 memory,
                                 memory_size,
+                                memory64,
                             };
+
+                            if TRACE {
+                                self.trace_call(func.id, state.bp);
+                            }
                         }
 
                         FuncKind::Host(f) => unsafe {
-                            let bp_offset = state.sp.offset_from(state.bp) as u32 - f.num_params as u32;
+                            let bp_offset = state.sp.offset_from(state.bp) as u32 - f.num_params;
 
                             let stack = &mut self.thread.stack;
 
@@ -572,21 +844,29 @@ impl Store {
                                 bp_offset,
                             };
                             self.thread.frames.push(Some(frame));
+                            self.thread.host_frames.push((self.thread.frames.len(), func.id));
+
+                            if TRACE {
+                                let args = self.thread.stack.as_ptr().add(sp - f.num_params as usize);
+                                self.trace_call(func.id, args);
+                            }
 
-                            if (f.call)(&*f.data as *const _ as *const u8, self).is_err() {
-                                todo!()
+                            if let Err(e) = (f.call)(&*f.data as *const _ as *const u8, self) {
+                                // the caller's frame is already on the stack.
+                                vm_err!(self.trap(e, None));
+                            }
+
+                            if TRACE {
+                                let stack = &self.thread.stack;
+                                let rets = stack.as_ptr().add(stack.len() - f.num_rets as usize);
+                                self.trace_ret(func.id, rets);
                             }
 
                             self.thread.frames.pop().unwrap_debug();
+                            self.thread.host_frames.pop().unwrap_debug();
 
                             // @cleanup: reuse `RETURN` logic?
 
-                            let mut memory = state.memory;
-                            let mut memory_size = state.memory_size;
-                            if let Some(mem) = state.memory_data.as_mut() {
-                                (memory, memory_size) = mem.as_mut_ptr();
-                            }
-
                             let stack = &mut self.thread.stack;
                             let stack_ptr = stack.as_mut_ptr();
                             state = State {
@@ -601,10 +881,13 @@ impl Store {
                                 locals_end: stack_ptr.add(locals_end),
                                 stack_frame_end: stack_ptr.add(stack_frame_end),
                                 stack_alloc_end: stack_ptr.add(stack.cap()),
+                                memories: state.memories,
                                 memory_data: state.memory_data,
-                                memory,
-                                memory_size,
+                                memory: state.memory,
+                                memory_size: state.memory_size,
+                                memory64: state.memory64,
                             };
+                            state.sync_memory();
                         }
 
                         FuncKind::Var(_) => unreachable!(),
@@ -662,11 +945,30 @@ impl Store {
                 }
 
                 Opcode::TableGet => {
-                    vm_err!(Error::Unimplemented);
+                    let tab_idx = state.next_u32();
+                    let i = state.pop().as_i32() as u32 as usize;
+
+                    let inst = unsafe { &*self.instances[state.instance].get() };
+                    let tab = Table::new(&inst.tables.inner()[tab_idx as usize]);
+
+                    let Some(v) = unsafe { tab.as_slice() }.get(i) else {
+                        vm_err!(Error::TrapTableBounds);
+                    };
+                    state.push(StackValue::from_i32(v.id as i32));
                 }
 
                 Opcode::TableSet => {
-                    vm_err!(Error::Unimplemented);
+                    let tab_idx = state.next_u32();
+                    let v = RefValue { id: state.pop().as_i32() as u32 };
+                    let i = state.pop().as_i32() as u32 as usize;
+
+                    let inst = unsafe { &*self.instances[state.instance].get() };
+                    let mut tab = Table::new(&inst.tables.inner()[tab_idx as usize]);
+
+                    let Some(slot) = unsafe { tab.as_mut_slice() }.get_mut(i) else {
+                        vm_err!(Error::TrapTableBounds);
+                    };
+                    *slot = v;
                 }
 
                 Opcode::I32Load => {
@@ -786,23 +1088,34 @@ impl Store {
 
                 Opcode::MemorySize => {
                     let mem = state.next_u32();
-                    if mem != 0 { todo!() }
-
-                    state.push(StackValue::from_i32((state.memory_size / wasm::PAGE_SIZE) as i32));
+                    let (_, memory_size, memory64) = state.memory(mem);
+                    let pages = memory_size / wasm::PAGE_SIZE;
+                    if memory64 {
+                        state.push(StackValue::from_i64(pages as i64));
+                    }
+                    else {
+                        state.push(StackValue::from_i32(pages as i32));
+                    }
                 }
 
                 Opcode::MemoryGrow => {
                     let mem = state.next_u32();
-                    if mem != 0 { todo!() }
-
-                    let delta = state.pop().as_i32() as u32;
+                    let (_, _, memory64) = state.memory(mem);
+                    let delta = state.pop_addr(memory64);
 
-                    let mem = state.memory_data.as_mut().unwrap();
-                    let result = match mem.grow(delta) {
-                        Ok(n) => n as i32,
+                    let result = match state.memory_data(mem).grow64(delta) {
+                        Ok(n) => n as i64,
                         Err(_) => -1,
                     };
-                    state.push(StackValue::from_i32(result));
+                    // memories can be imported more than once,
+                    // so any grow can move memory 0.
+                    state.sync_memory();
+                    if memory64 {
+                        state.push(StackValue::from_i64(result));
+                    }
+                    else {
+                        state.push(StackValue::from_i32(result as i32));
+                    }
                 }
 
                 Opcode::I32Const => {
@@ -1480,6 +1793,7 @@ impl Store {
                 }
 
                 Opcode::RefNull => {
+                    let _ty = state.next_u8();
                     state.push(StackValue::from_i32(u32::MAX as i32));
                 }
 
@@ -1489,18 +1803,20 @@ impl Store {
                 }
 
                 Opcode::RefFunc => {
-                    vm_err!(Error::Unimplemented);
+                    let func_idx = state.next_u32();
+                    let inst = unsafe { &*self.instances[state.instance].get() };
+                    let id = unsafe { &*inst.funcs.inner()[func_idx as usize].get() }.id;
+                    state.push(StackValue::from_i32(id.inner() as i32));
                 }
 
                 Opcode::MemoryCopy => {
                     let (dst_mem, src_mem) = (state.next_u32(), state.next_u32());
-                    if dst_mem != 0 || src_mem != 0 {
-                        vm_err!(Error::Unimplemented);
-                    }
+                    let (dst_memory, dst_size, dst64) = state.memory(dst_mem);
+                    let (src_memory, src_size, src64) = state.memory(src_mem);
 
-                    let n = state.pop().as_i32() as usize;
-                    let src = state.pop().as_i32() as usize;
-                    let dst = state.pop().as_i32() as usize;
+                    let n = state.pop_addr(dst64 && src64);
+                    let src = state.pop_addr(src64);
+                    let dst = state.pop_addr(dst64);
 
                     let Some(src_end) = src.checked_add(n) else {
                         vm_err!(Error::TrapMemoryBounds);
@@ -1508,40 +1824,188 @@ impl Store {
                     let Some(dst_end) = dst.checked_add(n) else {
                         vm_err!(Error::TrapMemoryBounds);
                     };
-                    if src_end > state.memory_size || dst_end > state.memory_size {
+                    if src_end > src_size as u64 || dst_end > dst_size as u64 {
                         vm_err!(Error::TrapMemoryBounds);
                     }
 
                     unsafe {
-                        core::ptr::copy(state.memory.add(src), state.memory.add(dst), n);
+                        core::ptr::copy(src_memory.add(src as usize), dst_memory.add(dst as usize), n as usize);
                     }
                 }
 
                 Opcode::MemoryFill => {
                     let mem = state.next_u32();
-                    if mem != 0 {
-                        vm_err!(Error::Unimplemented);
-                    }
+                    let (memory, memory_size, memory64) = state.memory(mem);
 
-                    let n = state.pop().as_i32() as usize;
+                    let n = state.pop_addr(memory64);
                     let v = state.pop().as_i32() as u8;
-                    let dst = state.pop().as_i32() as usize;
+                    let dst = state.pop_addr(memory64);
 
                     let Some(dst_end) = dst.checked_add(n) else {
                         vm_err!(Error::TrapMemoryBounds);
                     };
-                    if dst_end > state.memory_size {
+                    if dst_end > memory_size as u64 {
+                        vm_err!(Error::TrapMemoryBounds);
+                    }
+
+                    unsafe {
+                        core::ptr::write_bytes(memory.add(dst as usize), v, n as usize);
+                    }
+                }
+
+                Opcode::MemoryInit => {
+                    let (data_idx, mem) = (state.next_u32(), state.next_u32());
+                    let (memory, memory_size, memory64) = state.memory(mem);
+
+                    let n = state.pop().as_i32() as u32 as usize;
+                    let src = state.pop().as_i32() as u32 as usize;
+                    let dst = state.pop_addr(memory64);
+
+                    let inst = unsafe { &*self.instances[state.instance].get() };
+                    let data: &[u8] =
+                        if inst.dropped_datas[data_idx as usize] { &[] }
+                        else { inst.module.datas[data_idx as usize].values };
+
+                    let Some(src_end) = src.checked_add(n) else {
+                        vm_err!(Error::TrapMemoryBounds);
+                    };
+                    let Some(dst_end) = dst.checked_add(n as u64) else {
+                        vm_err!(Error::TrapMemoryBounds);
+                    };
+                    if src_end > data.len() || dst_end > memory_size as u64 {
                         vm_err!(Error::TrapMemoryBounds);
                     }
 
                     unsafe {
-                        core::ptr::write_bytes(state.memory.add(dst), v, n);
+                        core::ptr::copy_nonoverlapping(data.as_ptr().add(src), memory.add(dst as usize), n);
+                    }
+                }
+
+                Opcode::DataDrop => {
+                    let data_idx = state.next_u32();
+                    let inst = unsafe { &mut *self.instances[state.instance].get() };
+                    inst.dropped_datas[data_idx as usize] = true;
+                }
+
+                Opcode::TableInit => {
+                    let (elem_idx, tab_idx) = (state.next_u32(), state.next_u32());
+
+                    let n = state.pop().as_i32() as u32 as usize;
+                    let src = state.pop().as_i32() as u32 as usize;
+                    let dst = state.pop().as_i32() as u32 as usize;
+
+                    let inst = unsafe { &*self.instances[state.instance].get() };
+                    let elem = &inst.module.elements[elem_idx as usize];
+                    let elem_len = if inst.dropped_elems[elem_idx as usize] { 0 } else { elem.values.len() };
+
+                    let mut tab = Table::new(&inst.tables.inner()[tab_idx as usize]);
+                    let values = unsafe { tab.as_mut_slice() };
+
+                    let Some(src_end) = src.checked_add(n) else {
+                        vm_err!(Error::TrapTableBounds);
+                    };
+                    let Some(dst_end) = dst.checked_add(n) else {
+                        vm_err!(Error::TrapTableBounds);
+                    };
+                    if src_end > elem_len || dst_end > values.len() {
+                        vm_err!(Error::TrapTableBounds);
+                    }
+
+                    for i in 0..n {
+                        values[dst + i] = inst.elem_value(elem, src + i);
+                    }
+                }
+
+                Opcode::ElemDrop => {
+                    let elem_idx = state.next_u32();
+                    let inst = unsafe { &mut *self.instances[state.instance].get() };
+                    inst.dropped_elems[elem_idx as usize] = true;
+                }
+
+                Opcode::TableCopy => {
+                    let (dst_idx, src_idx) = (state.next_u32(), state.next_u32());
+
+                    let n = state.pop().as_i32() as u32 as usize;
+                    let src = state.pop().as_i32() as u32 as usize;
+                    let dst = state.pop().as_i32() as u32 as usize;
+
+                    let inst = unsafe { &*self.instances[state.instance].get() };
+                    let src_tab = Table::new(&inst.tables.inner()[src_idx as usize]);
+                    let mut dst_tab = Table::new(&inst.tables.inner()[dst_idx as usize]);
+
+                    let Some(src_end) = src.checked_add(n) else {
+                        vm_err!(Error::TrapTableBounds);
+                    };
+                    let Some(dst_end) = dst.checked_add(n) else {
+                        vm_err!(Error::TrapTableBounds);
+                    };
+                    if src_end > src_tab.len() as usize || dst_end > dst_tab.len() as usize {
+                        vm_err!(Error::TrapTableBounds);
+                    }
+
+                    // the tables may be the same.
+                    unsafe {
+                        let src_ptr = src_tab.as_slice().as_ptr().add(src);
+                        let dst_ptr = dst_tab.as_mut_slice().as_mut_ptr().add(dst);
+                        core::ptr::copy(src_ptr, dst_ptr, n);
+                    }
+                }
+
+                Opcode::TableGrow => {
+                    let tab_idx = state.next_u32();
+                    let delta = state.pop().as_i32() as u32;
+                    let init = RefValue { id: state.pop().as_i32() as u32 };
+
+                    let inst = unsafe { &*self.instances[state.instance].get() };
+                    let mut tab = Table::new(&inst.tables.inner()[tab_idx as usize]);
+                    let result = 'grow: {
+                        if tab.len() as usize + delta as usize > self.max_table_elements {
+                            break 'grow -1;
+                        }
+                        match tab.grow(delta, init) {
+                            Ok(n) => n as i32,
+                            Err(_) => -1,
+                        }
+                    };
+                    state.push(StackValue::from_i32(result));
+                }
+
+                Opcode::TableSize => {
+                    let tab_idx = state.next_u32();
+                    let inst = unsafe { &*self.instances[state.instance].get() };
+                    let tab = Table::new(&inst.tables.inner()[tab_idx as usize]);
+                    state.push(StackValue::from_i32(tab.len() as i32));
+                }
+
+                Opcode::TableFill => {
+                    let tab_idx = state.next_u32();
+
+                    let n = state.pop().as_i32() as u32 as usize;
+                    let v = RefValue { id: state.pop().as_i32() as u32 };
+                    let dst = state.pop().as_i32() as u32 as usize;
+
+                    let inst = unsafe { &*self.instances[state.instance].get() };
+                    let mut tab = Table::new(&inst.tables.inner()[tab_idx as usize]);
+                    let values = unsafe { tab.as_mut_slice() };
+
+                    let Some(dst_end) = dst.checked_add(n) else {
+                        vm_err!(Error::TrapTableBounds);
+                    };
+                    if dst_end > values.len() {
+                        vm_err!(Error::TrapTableBounds);
                     }
+
+                    values[dst..dst_end].fill(v);
                 }
             }
         };
 
+        let is_trap = e.is_trap();
+        let e = self.trap(e, Some((state.func, op_pc)));
         self.thread.trapped = true;
+        if is_trap && self.coredumps.is_some() {
+            self.write_coredump(state.instance, state.func, op_pc, state.bp, state.sp);
+        }
         return (Err(e),);
     }
 }
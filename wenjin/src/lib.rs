@@ -3,36 +3,144 @@ mod table;
 mod memory;
 mod global;
 mod typed;
+mod module;
 mod store;
+mod linker;
 mod interp;
+mod debug;
+mod trace;
+mod profile;
+mod coverage;
+mod coredump;
+mod externref;
 
+#[cfg(feature = "dwarf")]
+pub mod dwarf;
 
-#[derive(Clone, Copy, Debug)]
+#[cfg(feature = "gdb")]
+pub mod gdb;
+
+
+#[derive(Clone, Debug)]
 pub enum Error {
     Wasm(wasm::Error),
-    MissingImport,
+    Link(Box<LinkError>),
+    Trap(Box<Trap>),
     CallerNotWasm,
     CallerNoMemory,
+    NoData,
+    TypeMismatch,
+    ImmutableGlobal,
+    InvalidBreakpoint,
+    NotStopped,
+    InvalidIndex,
     TrapUnreachable,
     TrapTableBounds,
     TrapMemoryBounds,
     TrapCallIndirectRefNull,
     TrapCallIndirectTypeMismatch,
     TrapDivZero,
+    TrapStackOverflow,
+    OutOfFuel,
+    Interrupted,
+    NotSuspended,
     OOM,
     Unimplemented,
     Todo,
 }
 
+impl Error {
+    /// whether the error is a trap, without a backtrace.
+    #[inline]
+    pub fn is_trap(&self) -> bool {
+        matches!(self,
+            Error::TrapUnreachable |
+            Error::TrapTableBounds |
+            Error::TrapMemoryBounds |
+            Error::TrapCallIndirectRefNull |
+            Error::TrapCallIndirectTypeMismatch |
+            Error::TrapDivZero |
+            Error::TrapStackOverflow)
+    }
+
+    /// the error without its backtrace.
+    #[inline]
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::Trap(trap) => &trap.error,
+            _ => self,
+        }
+    }
+}
+
+impl From<LinkError> for Error {
+    #[inline]
+    fn from(value: LinkError) -> Self {
+        Self::Link(Box::new(value))
+    }
+}
+
+
+/// an import that could not be satisfied.
+#[derive(Clone, Debug)]
+pub struct LinkError {
+    pub module: String,
+    pub name: String,
+    pub expected: ExternType,
+    /// the type of the provided extern.
+    /// - `None` if the import was missing.
+    pub found: Option<ExternType>,
+}
+
+/// a trap & the call stack at the time of the trap.
+#[derive(Clone, Debug)]
+pub struct Trap {
+    pub error: Error,
+    /// innermost frame first.
+    pub backtrace: Vec<BacktraceFrame>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BacktraceFrame {
+    /// - `func` is the function's index in the module.
+    /// - `offset` is the byte offset in the module's binary.
+    ///   it points at the trapping instruction for the innermost frame,
+    ///   and after the call instruction for the callers.
+    Wasm { instance: InstanceId, func: u32, offset: u32 },
+    Host { func: FuncId },
+}
+
+
+#[derive(Clone, Debug)]
+pub enum ExternType {
+    Func { params: Vec<wasm::ValueType>, rets: Vec<wasm::ValueType> },
+    Table(wasm::TableType),
+    Memory(wasm::MemoryType),
+    Global(wasm::GlobalType),
+}
+
+impl ExternType {
+    #[inline]
+    pub fn func(ty: wasm::FuncType) -> Self {
+        Self::Func { params: ty.params.to_vec(), rets: ty.rets.to_vec() }
+    }
+}
+
 
 pub use wasm;
-pub use value::Value;
-pub use store::RefValue;
+pub use value::{Value, FuncRef, ExternRef};
+pub use store::{RefValue, StackValue};
 pub use table::Table;
-pub use memory::{Memory, CType, WasmSize, WasmPtr, WasmSlice};
+pub use memory::{Memory, CType, WasmSize, WasmPtr, WasmSize64, WasmPtr64, WasmSlice, WasmSlice64};
 pub use global::Global;
 pub use typed::{WasmType, WasmTypes, WasmResult};
-pub use store::{Store, InstanceId, FuncId, TypedFuncId, MemoryId};
+pub use store::{Store, InterruptHandle, Extern, InstanceId, FuncId, TypedFuncId, TableId, MemoryId, GlobalId};
+pub use module::Module;
+pub use linker::Linker;
+pub use debug::{DebugEvent, DebugAction, DebugFrame};
+pub use trace::{Tracer, TextTracer};
+pub use profile::{Profile, FuncProfile, ProfileWeight};
+pub use coverage::{Coverage, FuncCoverage};
 
 pub use sti::num::ceil_to_multiple_pow2;
 
@@ -0,0 +1,54 @@
+use sti::hash::HashMap;
+
+use crate::{Error, Module};
+use crate::store::{Store, Extern, InstanceId};
+
+
+/// a set of named externs used to satisfy imports.
+/// - externs are registered under a module name & a field name,
+///   either one at a time, or all exports of an instance at once.
+/// - defining a name again replaces the previous definition.
+pub struct Linker {
+    modules: HashMap<String, HashMap<String, Extern>>,
+}
+
+impl Linker {
+    pub fn new() -> Self {
+        Self { modules: HashMap::new() }
+    }
+
+    pub fn define(&mut self, module: &str, name: &str, ext: impl Into<Extern>) {
+        if self.modules.get(module).is_none() {
+            self.modules.insert(module.into(), HashMap::new());
+        }
+        let fields = self.modules.get_mut(module).unwrap();
+        fields.insert(name.into(), ext.into());
+    }
+
+    /// registers all exports of `instance` under `module`.
+    pub fn define_instance(&mut self, store: &Store, module: &str, instance: InstanceId) {
+        for (name, ext) in store.exports(instance) {
+            self.define(module, name, ext);
+        }
+    }
+
+    pub fn get(&self, module: &str, name: &str) -> Option<Extern> {
+        self.modules.get(module)?.get(name).copied()
+    }
+
+    /// compiles `wasm` & instantiates it.
+    pub fn instantiate(&self, store: &mut Store, wasm: &[u8]) -> Result<InstanceId, Error> {
+        self.instantiate_module(store, &Module::new(wasm)?)
+    }
+
+    pub fn instantiate_module(&self, store: &mut Store, module: &Module) -> Result<InstanceId, Error> {
+        store.instantiate_ex(module, |module, name| self.get(module, name))
+    }
+}
+
+impl Default for Linker {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -16,17 +16,19 @@ use crate::store::StackValue;
 pub(crate) struct MemoryData {
     id: MemoryId,
     limits: Limits,
+    is64: bool,
     buffer: NonNull<u8>,
-    size_pages: u32,
+    size_pages: u64,
 }
 
 const ALIGN: usize = 16;
 
 impl MemoryData {
-    pub fn new(id: MemoryId, limits: Limits) -> Result<Self, Error> {
+    pub fn new(id: MemoryId, limits: Limits, is64: bool) -> Result<Self, Error> {
         let mut this = Self {
             id,
             limits,
+            is64,
             buffer: NonNull::dangling(),
             size_pages: 0,
         };
@@ -41,7 +43,7 @@ impl MemoryData {
         self.size_pages as usize * wasm::PAGE_SIZE
     }
 
-    fn grow(&mut self, by_pages: u32) -> Result<u32, ()> {
+    fn grow(&mut self, by_pages: u64) -> Result<u64, ()> {
         let old_pages  = self.size_pages;
         let old_size   = old_pages as usize * wasm::PAGE_SIZE;
         let old_layout = unsafe { Layout::from_size_align_unchecked(old_size, ALIGN) };
@@ -53,7 +55,14 @@ impl MemoryData {
             }
         }
 
-        let Some(new_size) = (new_pages as usize).checked_mul(wasm::PAGE_SIZE) else { return Err(()) };
+        // the pages the addresses can reach.
+        let addr_pages = if self.is64 { 1 << 48 } else { 1 << 16 };
+        if new_pages > addr_pages {
+            return Err(());
+        }
+
+        let Ok(new_pages) = usize::try_from(new_pages) else { return Err(()) };
+        let Some(new_size) = new_pages.checked_mul(wasm::PAGE_SIZE) else { return Err(()) };
         let Ok(new_layout) = Layout::from_size_align(new_size, ALIGN)          else { return Err(()) };
 
         // allocate.
@@ -68,7 +77,7 @@ impl MemoryData {
         }
 
         self.buffer = new_buffer;
-        self.size_pages = new_pages;
+        self.size_pages = new_pages as u64;
 
         return Ok(old_pages);
     }
@@ -100,6 +109,12 @@ impl<'a> Memory<'a> {
 
     #[inline]
     pub fn size_pages(&self) -> u32 {
+        // memories with 2^32 pages (256 TiB) can't be allocated.
+        self.size_pages64() as u32
+    }
+
+    #[inline]
+    pub fn size_pages64(&self) -> u64 {
         unsafe { self.inner.as_ref().size_pages }
     }
 
@@ -108,16 +123,50 @@ impl<'a> Memory<'a> {
         unsafe { self.inner.as_ref().size_bytes() }
     }
 
+    /// the memory's current limits.
+    /// - `min` is the current size in pages.
+    #[inline]
+    pub fn limits(&self) -> Limits {
+        unsafe { Limits { min: self.size_pages64(), max: self.inner.as_ref().limits.max } }
+    }
+
+    /// whether the memory has `i64` addresses.
+    #[inline]
+    pub fn is64(&self) -> bool {
+        unsafe { self.inner.as_ref().is64 }
+    }
+
+    /// - returns the previous size in pages.
     #[inline]
     pub fn grow(&mut self, by_pages: u32) -> Result<u32, Error> {
+        // memories with 2^32 pages (256 TiB) can't be allocated.
+        self.grow64(by_pages as u64).map(|old_pages| old_pages as u32)
+    }
+
+    #[inline]
+    pub fn grow64(&mut self, by_pages: u64) -> Result<u64, Error> {
         unsafe { self.inner.as_mut().grow(by_pages) }
         .map_err(|_| Error::OOM)
     }
 
     #[inline]
     pub fn read<T: CType>(&self, ptr: WasmPtr<T>) -> Result<T, Error> {
-        let Some(end) = (ptr.addr as usize).checked_add(size_of::<T>()) else { todo!() };
-        if end > self.size_bytes() { todo!() }
+        self.read64(ptr.into())
+    }
+
+    #[inline]
+    pub fn write<T: CType>(&mut self, ptr: WasmPtr<T>, value: T) -> Result<(), Error> {
+        self.write64(ptr.into(), value)
+    }
+
+    #[inline]
+    pub fn read64<T: CType>(&self, ptr: WasmPtr64<T>) -> Result<T, Error> {
+        let Some(end) = ptr.addr.checked_add(size_of::<T>() as u64) else {
+            return Err(Error::TrapMemoryBounds);
+        };
+        if end > self.size_bytes() as u64 {
+            return Err(Error::TrapMemoryBounds);
+        }
         unsafe {
             let base = self.inner.as_ref().buffer.as_ptr();
             return Ok(base.add(ptr.addr as usize).cast::<T>().read_unaligned());
@@ -125,9 +174,13 @@ impl<'a> Memory<'a> {
     }
 
     #[inline]
-    pub fn write<T: CType>(&mut self, ptr: WasmPtr<T>, value: T) -> Result<(), Error> {
-        let Some(end) = (ptr.addr as usize).checked_add(size_of::<T>()) else { todo!() };
-        if end > self.size_bytes() { todo!() }
+    pub fn write64<T: CType>(&mut self, ptr: WasmPtr64<T>, value: T) -> Result<(), Error> {
+        let Some(end) = ptr.addr.checked_add(size_of::<T>() as u64) else {
+            return Err(Error::TrapMemoryBounds);
+        };
+        if end > self.size_bytes() as u64 {
+            return Err(Error::TrapMemoryBounds);
+        }
         unsafe {
             let base = self.inner.as_mut().buffer.as_ptr();
             let ptr = base.add(ptr.addr as usize);
@@ -155,15 +208,21 @@ impl<'a> Memory<'a> {
 
     #[inline]
     pub unsafe fn read_slice(&self, slice: WasmSlice<u8>, dst: *mut u8) -> Result<(), Error> {
-        let addr = slice.ptr.addr as usize;
-        let len = slice.len.usize();
+        unsafe { self.read_slice64(slice.into(), dst) }
+    }
 
-        let Some(end) = addr.checked_add(len) else { todo!() };
-        if end > self.size_bytes() { todo!() }
+    #[inline]
+    pub unsafe fn read_slice64(&self, slice: WasmSlice64<u8>, dst: *mut u8) -> Result<(), Error> {
+        let Some(end) = slice.ptr.addr.checked_add(slice.len.0) else {
+            return Err(Error::TrapMemoryBounds);
+        };
+        if end > self.size_bytes() as u64 {
+            return Err(Error::TrapMemoryBounds);
+        }
 
         unsafe {
             let base = self.inner.as_ref().buffer.as_ptr();
-            core::ptr::copy_nonoverlapping(base.add(addr), dst, len);
+            core::ptr::copy_nonoverlapping(base.add(slice.ptr.addr as usize), dst, slice.len.usize());
             return Ok(());
         }
     }
@@ -383,6 +442,163 @@ unsafe impl<T: CType> CType for WasmPtr<T> { #[inline(always)] unsafe fn clear_p
 
 
 
+/// a size in a memory with `i64` addresses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct WasmSize64(pub u64);
+
+impl WasmSize64 {
+    #[inline(always)]
+    pub fn usize(self) -> usize { self.0 as usize }
+}
+
+impl From<WasmSize> for WasmSize64 {
+    #[inline(always)]
+    fn from(size: WasmSize) -> Self {
+        WasmSize64(size.0 as u64)
+    }
+}
+
+impl WasmType for WasmSize64 {
+    const WASM_TYPE: wasm::ValueType = wasm::ValueType::I64;
+
+    #[inline(always)]
+    fn to_stack_value(self) -> StackValue {
+        StackValue::from_i64(self.0 as i64)
+    }
+
+    #[inline(always)]
+    fn from_stack_value(value: StackValue) -> WasmSize64 {
+        WasmSize64(value.as_i64() as u64)
+    }
+}
+
+unsafe impl CType for WasmSize64 { #[inline(always)] unsafe fn clear_padding(bytes: &mut [u8]) { debug_assert_eq!(bytes.len(), size_of::<Self>()) } }
+
+
+
+/// a pointer into a memory with `i64` addresses.
+#[repr(transparent)]
+pub struct WasmPtr64<T: CType> {
+    pub addr: u64,
+    pub phantom: PhantomData<*mut T>,
+}
+
+impl<T: CType> WasmPtr64<T> {
+    #[inline(always)]
+    pub fn new(addr: u64) -> WasmPtr64<T> {
+        WasmPtr64 { addr, phantom: PhantomData }
+    }
+
+    #[inline(always)]
+    pub fn is_null(self) -> bool {
+        self.addr == 0
+    }
+
+    #[inline(always)]
+    pub fn add(self, delta: u64) -> WasmPtr64<T> {
+        WasmPtr64::new(self.addr + delta*size_of::<T>() as u64)
+    }
+
+    #[inline(always)]
+    pub fn checked_add(self, delta: u64) -> Option<WasmPtr64<T>> {
+        let delta = delta.checked_mul(size_of::<T>() as u64)?;
+        Some(WasmPtr64::new(self.addr.checked_add(delta)?))
+    }
+
+    #[inline(always)]
+    pub fn wrapping_add(self, delta: u64) -> WasmPtr64<T> {
+        let delta = delta.wrapping_mul(size_of::<T>() as u64);
+        WasmPtr64::new(self.addr.wrapping_add(delta))
+    }
+
+
+    #[inline(always)]
+    pub fn byte_add(self, delta: u64) -> WasmPtr64<T> {
+        WasmPtr64::new(self.addr + delta)
+    }
+
+    #[inline(always)]
+    pub fn checked_byte_add(self, delta: u64) -> Option<WasmPtr64<T>> {
+        Some(WasmPtr64::new(self.addr.checked_add(delta)?))
+    }
+
+    #[inline(always)]
+    pub fn wrapping_byte_add(self, delta: u64) -> WasmPtr64<T> {
+        WasmPtr64::new(self.addr.wrapping_add(delta))
+    }
+}
+
+impl<T: CType> From<WasmPtr<T>> for WasmPtr64<T> {
+    #[inline(always)]
+    fn from(ptr: WasmPtr<T>) -> Self {
+        WasmPtr64::new(ptr.addr as u64)
+    }
+}
+
+impl<T: CType> Clone for WasmPtr64<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        WasmPtr64 { addr: self.addr, phantom: PhantomData }
+    }
+}
+
+impl<T: CType> Copy for WasmPtr64<T> {}
+
+impl<T: CType> PartialEq for WasmPtr64<T> {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.addr.eq(&other.addr)
+    }
+}
+
+impl<T: CType> Eq for WasmPtr64<T> {}
+
+impl<T: CType> PartialOrd for WasmPtr64<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.addr.partial_cmp(&other.addr)
+    }
+}
+
+impl<T: CType> Ord for WasmPtr64<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.addr.cmp(&other.addr)
+    }
+}
+
+impl<T: CType> core::hash::Hash for WasmPtr64<T> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.addr.hash(state)
+    }
+}
+
+impl<T: CType> core::fmt::Debug for WasmPtr64<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "0x{:x}", self.addr)
+    }
+}
+
+impl<T: CType> WasmType for WasmPtr64<T> {
+    const WASM_TYPE: wasm::ValueType = wasm::ValueType::I64;
+
+    #[inline(always)]
+    fn to_stack_value(self) -> StackValue {
+        StackValue::from_i64(self.addr as i64)
+    }
+
+    #[inline(always)]
+    fn from_stack_value(value: StackValue) -> WasmPtr64<T> {
+        WasmPtr64 { addr: value.as_i64() as u64, phantom: PhantomData }
+    }
+}
+
+unsafe impl<T: CType> CType for WasmPtr64<T> { #[inline(always)] unsafe fn clear_padding(bytes: &mut [u8]) { debug_assert_eq!(bytes.len(), size_of::<Self>()) } }
+
+
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(C)]
 pub struct WasmSlice<T: CType> {
@@ -390,4 +606,18 @@ pub struct WasmSlice<T: CType> {
     pub len: WasmSize,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct WasmSlice64<T: CType> {
+    pub ptr: WasmPtr64<T>,
+    pub len: WasmSize64,
+}
+
+impl<T: CType> From<WasmSlice<T>> for WasmSlice64<T> {
+    #[inline(always)]
+    fn from(slice: WasmSlice<T>) -> Self {
+        WasmSlice64 { ptr: slice.ptr.into(), len: slice.len.into() }
+    }
+}
+
 
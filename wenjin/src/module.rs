@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use sti::arena::Arena;
+use sti::vec::Vec;
+use sti::hash::HashMap;
+
+use crate::{Error, ExternType};
+
+
+/// a parsed & validated wasm module.
+/// - can be instantiated any number of times, in any store.
+/// - cloning is cheap, clones share the compiled code.
+/// - modules are `Send + Sync`, so stores on different threads
+///   can instantiate the same module.
+#[derive(Clone)]
+pub struct Module {
+    pub(crate) data: Arc<ModuleData>,
+}
+
+pub(crate) struct ModuleData {
+    pub module: wasm::Module<'static>,
+    pub funcs: Vec<CompiledFunc>,
+    // `module` borrows from these.
+    // they are never modified after compilation.
+    pub wasm: Vec<u8>,
+    #[allow(dead_code)]
+    pub alloc: Arena,
+    // parsed on first use.
+    #[cfg(feature = "dwarf")]
+    pub debug_info: std::sync::OnceLock<Option<crate::dwarf::DebugInfo>>,
+}
+
+impl ModuleData {
+    #[cfg(feature = "dwarf")]
+    pub fn debug_info(&self) -> Option<&crate::dwarf::DebugInfo> {
+        self.debug_info.get_or_init(|| crate::dwarf::DebugInfo::new(&self.module)).as_ref()
+    }
+}
+
+// safety: a `ModuleData` is immutable after compilation.
+//  the raw pointers point into `wasm`, and `alloc` is never allocated from
+//  after `Module::new` returns, so shared access needs no synchronization.
+unsafe impl Send for ModuleData {}
+unsafe impl Sync for ModuleData {}
+
+/// validator outputs for a function body.
+pub(crate) struct CompiledFunc {
+    pub code: *const u8,
+    pub code_len: usize,
+    pub jumps: HashMap<u32, wasm::Jump>,
+    pub num_params: u32,
+    pub num_locals: u32, // including params.
+    pub stack_size: u32, // including locals.
+}
+
+impl Module {
+    pub fn new(wasm: &[u8]) -> Result<Module, Error> {
+        let wasm = Vec::from_slice(wasm);
+        let alloc = Arena::new();
+
+        let wasm_static = unsafe { core::mem::transmute::<&[u8], &[u8]>(wasm.as_slice()) };
+        let alloc_static = unsafe { core::mem::transmute::<&Arena, &Arena>(&alloc) };
+
+        let module = wasm::Parser::parse_module(wasm_static, Default::default(), alloc_static)
+            .map_err(|e| Error::Wasm(e))?;
+
+        let mut funcs = Vec::with_cap(module.codes.len());
+        let mut validator = wasm::Validator::new(&module);
+        for (i, code) in module.codes.iter().enumerate() {
+            let mut p = wasm::Parser::from_sub_section(&*wasm, code.expr);
+
+            let ty_idx = module.funcs[i];
+            let ty = module.types[ty_idx as usize];
+
+            let mut jumps = HashMap::new();
+            validator.validate_func(&mut p, ty_idx, code.locals, Some(&mut jumps))
+                .map_err(|e| Error::Wasm(e))?;
+
+            funcs.push(CompiledFunc {
+                code: unsafe { wasm.as_ptr().add(code.expr.offset) },
+                code_len: code.expr.len,
+                jumps,
+                num_params: ty.params.len() as u32,
+                num_locals: validator.num_locals(),
+                stack_size: validator.stack_size(),
+            });
+        }
+
+        return Ok(Module { data: Arc::new(ModuleData {
+            module, funcs, wasm, alloc,
+            #[cfg(feature = "dwarf")]
+            debug_info: Default::default(),
+        })});
+    }
+
+    /// the module's imports, in order, as `(module, name, type)`.
+    pub fn imports(&self) -> impl Iterator<Item = (&str, &str, ExternType)> {
+        let module = &self.data.module;
+        module.imports.imports.iter().map(|import|
+            (import.module, import.name, import_type(module, import.kind)))
+    }
+
+    /// the decoded `name` section.
+    /// - empty if the module has none.
+    #[inline]
+    pub fn names(&self) -> wasm::NameSection<'_> {
+        self.data.module.names
+    }
+
+    /// the first decoding error in the `name` section, if any.
+    #[inline]
+    pub fn names_error(&self) -> Option<wasm::Error> {
+        self.data.module.names_error
+    }
+
+    /// the module's dwarf debug info.
+    /// - `None` if the module has no `.debug_line` section.
+    /// - parsed on first use.
+    #[cfg(feature = "dwarf")]
+    #[inline]
+    pub fn debug_info(&self) -> Option<&crate::dwarf::DebugInfo> {
+        self.data.debug_info()
+    }
+
+    /// the module's exports, in order, as `(name, type)`.
+    pub fn exports(&self) -> impl Iterator<Item = (&str, ExternType)> {
+        let module = &self.data.module;
+        module.exports.iter().map(|export| {
+            // the parser checks export indices.
+            let ty = match export.kind {
+                wasm::ExportKind::Func(idx) => {
+                    let ty = module.get_func(idx).unwrap();
+                    ExternType::func(module.types[ty as usize])
+                }
+                wasm::ExportKind::Table(idx)  => ExternType::Table(module.get_table(idx).unwrap()),
+                wasm::ExportKind::Memory(idx) => ExternType::Memory(module.get_memory(idx).unwrap()),
+                wasm::ExportKind::Global(idx) => ExternType::Global(module.get_global(idx).unwrap()),
+            };
+            (export.name, ty)
+        })
+    }
+}
+
+
+pub(crate) fn import_type(module: &wasm::Module, kind: wasm::ImportKind) -> ExternType {
+    match kind {
+        wasm::ImportKind::Func(ty)   => ExternType::func(module.types[ty as usize]),
+        wasm::ImportKind::Table(ty)  => ExternType::Table(ty),
+        wasm::ImportKind::Memory(ty) => ExternType::Memory(ty),
+        wasm::ImportKind::Global(ty) => ExternType::Global(ty),
+    }
+}
@@ -0,0 +1,278 @@
+use sti::hash::HashMap;
+use std::time::{Duration, Instant};
+use std::fmt::Write;
+use std::cmp::Reverse;
+
+use crate::store::{Store, FuncKind, FuncId};
+
+
+/// the statistics of a function in a profile.
+#[derive(Clone, Debug)]
+pub struct FuncProfile {
+    pub func: FuncId,
+    /// from the `name` section.
+    /// - `func[idx]` for unnamed wasm functions, `host[id]` for host functions.
+    pub name: String,
+    pub calls: u64,
+    /// the instructions executed by the function itself.
+    pub instructions: u64,
+    pub self_time: Duration,
+    /// including the callees.
+    /// - recursive calls are only counted once.
+    pub total_time: Duration,
+}
+
+/// what folded stacks are weighted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfileWeight {
+    Instructions,
+    Nanoseconds,
+}
+
+/// the result of `Store::stop_profiling`.
+pub struct Profile {
+    // by self time, descending.
+    funcs: Vec<FuncProfile>,
+    // the call tree, without the root.
+    nodes: Vec<ProfileNode>,
+}
+
+struct ProfileNode {
+    // index into `funcs`.
+    func: usize,
+    // index into `nodes`.
+    parent: Option<usize>,
+    instructions: u64,
+    self_time: Duration,
+}
+
+impl Profile {
+    pub fn funcs(&self) -> &[FuncProfile] {
+        &self.funcs
+    }
+
+    /// the profile in the collapsed stack format of flamegraph tools.
+    /// - one line per call stack: the function names, outermost first,
+    ///   separated by `;`, then the stack's self weight.
+    /// - stacks with a weight of 0 are omitted.
+    pub fn folded_stacks(&self, weight: ProfileWeight) -> String {
+        let mut result = String::new();
+        let mut path = Vec::new();
+        for node in &self.nodes {
+            let weight = match weight {
+                ProfileWeight::Instructions => node.instructions,
+                ProfileWeight::Nanoseconds => node.self_time.as_nanos() as u64,
+            };
+            if weight == 0 {
+                continue;
+            }
+
+            path.clear();
+            let mut at = Some(node);
+            while let Some(node) = at {
+                path.push(self.funcs[node.func].name.as_str());
+                at = node.parent.map(|parent| &self.nodes[parent]);
+            }
+            path.reverse();
+            _ = writeln!(result, "{} {weight}", path.join(";"));
+        }
+        return result;
+    }
+
+    /// a table with one line per function, by self time.
+    pub fn summary(&self) -> String {
+        let mut result = String::new();
+        _ = writeln!(result, "{:>10} {:>14} {:>12} {:>12}  function", "calls", "instructions", "self (us)", "total (us)");
+        for func in &self.funcs {
+            _ = writeln!(result, "{:>10} {:>14} {:>12.1} {:>12.1}  {}",
+                func.calls, func.instructions,
+                func.self_time.as_secs_f64() * 1e6,
+                func.total_time.as_secs_f64() * 1e6,
+                func.name);
+        }
+        return result;
+    }
+}
+
+
+/// the call tree of the profiled calls.
+/// - the stack's depths are `ThreadData::frames` depths,
+///   so frames that are unwound by traps can be closed on the next event.
+pub(crate) struct Profiler {
+    // node 0 is the root.
+    nodes: Vec<Node>,
+    // (node, entry time, call depth).
+    stack: Vec<(usize, Instant, usize)>,
+}
+
+struct Node {
+    // `None` for the root.
+    func: Option<FuncId>,
+    parent: usize,
+    children: HashMap<u32, usize>,
+    calls: u64,
+    instructions: u64,
+    total_time: Duration,
+}
+
+impl Profiler {
+    fn new() -> Self {
+        let root = Node {
+            func: None,
+            parent: 0,
+            children: HashMap::new(),
+            calls: 0,
+            instructions: 0,
+            total_time: Duration::ZERO,
+        };
+        Self { nodes: vec![root], stack: Vec::new() }
+    }
+
+    #[inline]
+    fn top(&self) -> usize {
+        self.stack.last().map_or(0, |(node, _, _)| *node)
+    }
+
+    #[inline]
+    pub fn instruction(&mut self) {
+        let top = self.top();
+        self.nodes[top].instructions += 1;
+    }
+
+    pub fn call(&mut self, func: FuncId, depth: usize) {
+        self.unwind(depth);
+
+        let parent = self.top();
+        let next = self.nodes.len();
+        let node = match self.nodes[parent].children.get(&func.inner()) {
+            Some(node) => *node,
+            None => {
+                self.nodes[parent].children.insert(func.inner(), next);
+                next
+            }
+        };
+        if node == next {
+            self.nodes.push(Node {
+                func: Some(func),
+                parent,
+                children: HashMap::new(),
+                calls: 0,
+                instructions: 0,
+                total_time: Duration::ZERO,
+            });
+        }
+
+        self.nodes[node].calls += 1;
+        self.stack.push((node, Instant::now(), depth));
+    }
+
+    /// closes the frames at `depth` & deeper.
+    pub fn unwind(&mut self, depth: usize) {
+        let now = Instant::now();
+        while let Some(&(node, start, d)) = self.stack.last() {
+            if d < depth {
+                break;
+            }
+            self.nodes[node].total_time += now - start;
+            self.stack.pop();
+        }
+    }
+}
+
+
+impl Store {
+    /// starts collecting a profile of the wasm calls that start after this.
+    /// - counts the calls, instructions & time of each function,
+    ///   per call stack.
+    /// - profiled calls run on the tracing interpreter,
+    ///   which makes them slower.
+    /// - restarts the profile, if already profiling.
+    pub fn start_profiling(&mut self) {
+        self.profiler = Some(Profiler::new());
+    }
+
+    /// - returns `None` if the store wasn't profiling.
+    pub fn stop_profiling(&mut self) -> Option<Profile> {
+        let mut profiler = self.profiler.take()?;
+        profiler.unwind(0);
+
+        let mut funcs: Vec<FuncProfile> = Vec::new();
+        let mut func_indices = HashMap::new();
+        let mut nodes = Vec::with_capacity(profiler.nodes.len() - 1);
+
+        let mut children_times = vec![Duration::ZERO; profiler.nodes.len()];
+        for node in profiler.nodes.iter().skip(1) {
+            children_times[node.parent] += node.total_time;
+        }
+
+        for (i, node) in profiler.nodes.iter().enumerate().skip(1) {
+            let id = node.func.unwrap();
+            let func = match func_indices.get(&id.inner()) {
+                Some(func) => *func,
+                None => {
+                    funcs.push(FuncProfile {
+                        func: id,
+                        name: self.profile_name(id),
+                        calls: 0,
+                        instructions: 0,
+                        self_time: Duration::ZERO,
+                        total_time: Duration::ZERO,
+                    });
+                    func_indices.insert(id.inner(), funcs.len() - 1);
+                    funcs.len() - 1
+                }
+            };
+
+            let self_time = node.total_time.saturating_sub(children_times[i]);
+
+            let mut recursive = false;
+            let mut at = node.parent;
+            while at != 0 {
+                recursive |= profiler.nodes[at].func == node.func;
+                at = profiler.nodes[at].parent;
+            }
+
+            let f = &mut funcs[func];
+            f.calls += node.calls;
+            f.instructions += node.instructions;
+            f.self_time += self_time;
+            if !recursive {
+                f.total_time += node.total_time;
+            }
+
+            // nodes are created after their parents.
+            nodes.push(ProfileNode {
+                func,
+                parent: (node.parent != 0).then(|| node.parent - 1),
+                instructions: node.instructions,
+                self_time,
+            });
+            debug_assert_eq!(nodes.len(), i);
+        }
+
+        // by self time, descending.
+        let mut order: Vec<usize> = (0..funcs.len()).collect();
+        order.sort_by_key(|i| Reverse(funcs[*i].self_time));
+        let mut rank = vec![0; funcs.len()];
+        for (r, i) in order.iter().enumerate() {
+            rank[*i] = r;
+        }
+        for node in &mut nodes {
+            node.func = rank[node.func];
+        }
+        funcs.sort_by_key(|f| Reverse(f.self_time));
+
+        return Some(Profile { funcs, nodes });
+    }
+
+    fn profile_name(&self, func: FuncId) -> String {
+        let data = unsafe { &*self.funcs[func].get() };
+        match &data.kind {
+            FuncKind::Interp(f) => match self.func_name(f.instance, f.func_idx) {
+                Some(name) => name.into(),
+                None => format!("func[{}]", f.func_idx),
+            },
+            _ => format!("host[{}]", func.inner()),
+        }
+    }
+}
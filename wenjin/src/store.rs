@@ -2,20 +2,28 @@ use core::cell::UnsafeCell;
 use core::ptr::NonNull;
 use core::marker::PhantomData;
 use core::any::Any;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use sti::arena::Arena;
 use sti::boks::Box;
 use sti::rc::Rc;
 use sti::vec::Vec;
 use sti::keyed::KVec;
 use sti::hash::HashMap;
 
-use crate::{Error, Value};
+use crate::{Error, LinkError, ExternType, Trap, BacktraceFrame, Value, FuncRef};
+use crate::module::{Module, ModuleData, import_type};
 use crate::table::{TableData, Table};
 use crate::memory::{MemoryData, Memory};
 use crate::global::{GlobalData, Global};
 use crate::typed::{WasmTypes, HostFunc};
 use crate::interp;
+use crate::debug::Debugger;
+use crate::trace::Tracer;
+use crate::profile::Profiler;
+use crate::coverage::CoverageRecorder;
+use crate::coredump::Coredumps;
+use crate::externref::ExternRefs;
 
 
 sti::define_key!(pub, u32, InstanceId);
@@ -30,7 +38,7 @@ pub struct TypedFuncId<P, R> { func_id: FuncId, phantom: PhantomData<fn(P) -> R>
 
 impl<P, R> TypedFuncId<P, R> {
     #[inline]
-    fn func_id(&self) -> FuncId { self.func_id }
+    pub fn func_id(&self) -> FuncId { self.func_id }
 }
 
 
@@ -75,21 +83,100 @@ pub struct Store {
     pub(crate) memories:    KVec<MemoryId,      Rc<UnsafeCell<MemoryData>>>,
     pub(crate) globals:     KVec<GlobalId,      Rc<UnsafeCell<GlobalData>>>,
     pub(crate) thread: ThreadData,
+    pub(crate) fuel: u64,
+    pub(crate) fuel_consumed: u64,
+    pub(crate) interrupt_epoch: Arc<AtomicU64>,
+    // the interrupt epoch when the current root call started.
+    pub(crate) call_epoch: u64,
+    pub(crate) max_call_depth: usize,
+    pub(crate) max_stack_size: usize,
+    pub(crate) max_table_elements: usize,
+    pub(crate) data: Option<std::boxed::Box<dyn Any>>,
+    pub(crate) debug: Debugger,
+    pub(crate) tracer: Option<std::boxed::Box<dyn Tracer>>,
+    pub(crate) profiler: Option<Profiler>,
+    pub(crate) coverage: Option<CoverageRecorder>,
+    pub(crate) coredumps: Option<Coredumps>,
+    pub(crate) extern_refs: ExternRefs,
+}
+
+
+/// stops wasm running in a store, from any thread.
+/// - the interpreter checks for interrupts on function entry & at loop headers.
+///   the running call then fails with `Error::Interrupted`.
+/// - an interrupt only affects the calls that are running when it is requested.
+///   calls that start afterwards run normally,
+///   so a late watchdog doesn't stop an unrelated call.
+#[derive(Clone, Debug)]
+pub struct InterruptHandle {
+    epoch: Arc<AtomicU64>,
+}
+
+impl InterruptHandle {
+    #[inline]
+    pub fn interrupt(&self) {
+        self.epoch.fetch_add(1, Ordering::Relaxed);
+    }
 }
 
 
 pub(crate) struct InstanceData {
     #[allow(dead_code)]
     pub id: InstanceId,
+    // `module` & the interp funcs borrow from `compiled`.
     #[allow(dead_code)]
-    pub wasm: Vec<u8>,
-    #[allow(dead_code)]
-    pub alloc: Arena,
+    pub compiled: Arc<ModuleData>,
     pub module: wasm::Module<'static>,
     pub funcs:    KVec<FuncId,   Rc<UnsafeCell<FuncData>>>,
     pub tables:   KVec<TableId,  Rc<UnsafeCell<TableData>>>,
     pub memories: KVec<MemoryId, Rc<UnsafeCell<MemoryData>>>,
     pub globals:  KVec<GlobalId, Rc<UnsafeCell<GlobalData>>>,
+    /// by element segment index.
+    /// - active & declarative segments are dropped by instantiation.
+    pub dropped_elems: std::vec::Vec<bool>,
+    /// by data segment index.
+    /// - active segments are dropped by instantiation.
+    pub dropped_datas: std::vec::Vec<bool>,
+}
+
+
+impl InstanceData {
+    /// the `i`-th value of an element segment.
+    pub fn elem_value(&self, elem: &wasm::Element, i: usize) -> RefValue {
+        match eval_const_expr(elem.values[i], &self.funcs, &self.globals) {
+            Value::FuncRef(v) | Value::ExternRef(v) => v,
+            // the parser validates the element types.
+            _ => unreachable!(),
+        }
+    }
+
+    fn export_extern(&self, kind: wasm::ExportKind) -> Extern {
+        match kind {
+            wasm::ExportKind::Func(idx)   => Extern::Func(unsafe { &*self.funcs.inner()[idx as usize].get() }.id),
+            wasm::ExportKind::Table(idx)  => Extern::Table(Table::new(&self.tables.inner()[idx as usize]).id()),
+            wasm::ExportKind::Memory(idx) => Extern::Memory(Memory::new(&self.memories.inner()[idx as usize]).id()),
+            wasm::ExportKind::Global(idx) => Extern::Global(Global::new(&self.globals.inner()[idx as usize]).id()),
+        }
+    }
+}
+
+/// - `funcs` & `globals` are the instance's, up to the ones the expression can refer to.
+fn eval_const_expr(expr: wasm::ConstExpr, funcs: &KVec<FuncId, Rc<UnsafeCell<FuncData>>>, globals: &KVec<GlobalId, Rc<UnsafeCell<GlobalData>>>) -> Value {
+    match expr {
+        wasm::ConstExpr::I32(v) => Value::I32(v),
+        wasm::ConstExpr::I64(v) => Value::I64(v),
+        wasm::ConstExpr::F32(v) => Value::F32(v),
+        wasm::ConstExpr::F64(v) => Value::F64(v),
+        wasm::ConstExpr::Global(idx) => Global::new(&globals.inner()[idx as usize]).get(),
+        wasm::ConstExpr::RefNull(ty) => match ty {
+            wasm::RefType::FuncRef => Value::FuncRef(RefValue::NULL),
+            wasm::RefType::ExternRef => Value::ExternRef(RefValue::NULL),
+        },
+        wasm::ConstExpr::RefFunc(idx) => {
+            let id = unsafe { &*funcs.inner()[idx as usize].get() }.id.inner();
+            Value::FuncRef(RefValue { id })
+        }
+    }
 }
 
 
@@ -107,9 +194,10 @@ pub(crate) enum FuncKind {
 
 pub(crate) struct InterpFunc {
     pub instance: InstanceId,
+    pub func_idx: u32,
     pub code: *const u8,
     pub code_len: usize,
-    pub jumps: HashMap<u32, wasm::Jump>,
+    pub jumps: *const HashMap<u32, wasm::Jump>,
     pub num_params: u32,
     pub num_locals: u32, // including params.
     pub stack_size: u32, // including locals.
@@ -129,8 +217,50 @@ impl InterpFunc {
 pub(crate) struct HostFuncData {
     pub data: Box<dyn Any>,
     pub call: fn(*const u8, &mut Store) -> Result<(), Error>,
-    pub num_params: u8,
-    pub num_rets: u8,
+    pub num_params: u32,
+    pub num_rets: u32,
+}
+
+/// the data of a host function created with `new_host_func_dyn`.
+struct DynHostFunc<F> {
+    // params, then rets.
+    // `FuncData::ty` borrows from this.
+    types: Vec<wasm::ValueType>,
+    num_params: usize,
+    f: F,
+}
+
+impl<F: Fn(&mut Store, &[Value], &mut [Value]) -> Result<(), Error>> DynHostFunc<F> {
+    fn call(&self, store: &mut Store) -> Result<(), Error> {
+        let (params, rets) = self.types.split_at(self.num_params);
+
+        let mut args = Vec::with_cap(params.len());
+        let stack = &mut store.thread.stack;
+        let bp = stack.len() - params.len();
+        for (i, ty) in params.iter().enumerate() {
+            let value = unsafe { stack.as_ptr().add(bp + i).read() };
+            args.push(value.to_value(*ty));
+        }
+        unsafe { stack.set_len(bp) };
+
+        let mut results = Vec::with_cap(rets.len());
+        for ty in rets {
+            results.push(Value::zero(*ty));
+        }
+
+        (self.f)(store, &args, &mut results)?;
+
+        let stack = &mut store.thread.stack;
+        stack.reserve_extra(rets.len());
+        for (result, ty) in results.iter().zip(rets) {
+            if result.ty() != *ty {
+                unsafe { stack.set_len(bp) };
+                return Err(Error::TypeMismatch);
+            }
+            stack.push(StackValue::from_value(*result));
+        }
+        return Ok(());
+    }
 }
 
 
@@ -203,8 +333,8 @@ impl StackValue {
             wasm::ValueType::F32 => Value::F32(self.as_f32()),
             wasm::ValueType::F64 => Value::F64(self.as_f64()),
             wasm::ValueType::V128 => todo!(),
-            wasm::ValueType::FuncRef => todo!(),
-            wasm::ValueType::ExternRef => todo!(),
+            wasm::ValueType::FuncRef => Value::FuncRef(RefValue { id: self.as_i32() as u32 }),
+            wasm::ValueType::ExternRef => Value::ExternRef(RefValue { id: self.as_i32() as u32 }),
         }
     }
 }
@@ -217,10 +347,27 @@ pub(crate) struct StackFrame {
     pub bp_offset: u32,
 }
 
+/// a root call that ran out of fuel.
+#[derive(Debug)]
+pub(crate) struct Suspended {
+    pub root_func: FuncId,
+    pub root_bp: usize,
+    pub instance: InstanceId,
+    pub func: FuncId,
+    pub pc: NonNull<u8>,
+    pub bp: usize,
+}
+
 pub(crate) struct ThreadData {
     pub stack: Vec<StackValue>,
     pub frames: Vec<Option<StackFrame>>,
+    /// active host functions & the number of `frames` below them.
+    pub host_frames: Vec<(usize, FuncId)>,
     pub trapped: bool,
+    pub suspended: Option<Suspended>,
+    /// the index of the stopped frame in `frames` & the stack size,
+    /// while the debug handler runs.
+    pub debug_stop: Option<(usize, usize)>,
 }
 
 impl Store {
@@ -234,22 +381,48 @@ impl Store {
             thread: ThreadData {
                 stack: Vec::new(),
                 frames: Vec::new(),
+                host_frames: Vec::new(),
                 trapped: false,
+                suspended: None,
+                debug_stop: None,
             },
+            fuel: u64::MAX,
+            fuel_consumed: 0,
+            interrupt_epoch: Arc::new(AtomicU64::new(0)),
+            call_epoch: 0,
+            max_call_depth: 10_000,
+            max_stack_size: 1 << 20,
+            max_table_elements: 1 << 20,
+            data: None,
+            debug: Debugger::new(),
+            tracer: None,
+            profiler: None,
+            coverage: None,
+            coredumps: None,
+            extern_refs: ExternRefs::default(),
         }
     }
 
+    /// compiles `wasm` & instantiates it.
+    /// - use `Module::new` & `Store::instantiate` to instantiate a module repeatedly.
     pub fn new_instance(&mut self, wasm: &[u8], imports: &[(&str, &str, Extern)]) -> Result<InstanceId, Error> {
-        let instance_id = self.instances.next_key();
+        let module = Module::new(wasm)?;
+        self.instantiate(&module, imports)
+    }
 
-        let wasm = Vec::from_slice(wasm);
-        let alloc = Arena::new();
+    pub fn instantiate(&mut self, module: &Module, imports: &[(&str, &str, Extern)]) -> Result<InstanceId, Error> {
+        self.instantiate_ex(module, |module, name| {
+            imports.iter()
+                .find(|(m, n, _)| *m == module && *n == name)
+                .map(|(_, _, ext)| *ext)
+        })
+    }
 
-        let wasm_static = unsafe { core::mem::transmute::<&[u8], &[u8]>(wasm.as_slice()) };
-        let alloc_static = unsafe { core::mem::transmute::<&Arena, &Arena>(&alloc) };
+    pub(crate) fn instantiate_ex<F: Fn(&str, &str) -> Option<Extern>>(&mut self, compiled: &Module, lookup_import: F) -> Result<InstanceId, Error> {
+        let instance_id = self.instances.next_key();
 
-        let module = wasm::Parser::parse_module(wasm_static, Default::default(), alloc_static)
-            .map_err(|e| Error::Wasm(e))?;
+        let compiled = compiled.data.clone();
+        let module = compiled.module.clone();
 
 
         let num_funcs = module.imports.funcs.len() + module.funcs.len();
@@ -269,55 +442,72 @@ impl Store {
         self.globals.inner_mut_unck().reserve_extra(num_globals);
 
         for import in module.imports.imports {
-            let lookup_import = |module, name| {
-                for (m, n, import) in imports.iter().copied() {
-                    if m == module && n == name {
-                        return Ok(import);
-                    }
-                }
-                println!("missing import {module:?} {name:?}");
-                return Err(Error::MissingImport);
+            let link_error = |found: Option<Extern>| -> Error {
+                LinkError {
+                    module: import.module.into(),
+                    name: import.name.into(),
+                    expected: import_type(&module, import.kind),
+                    found: found.map(|e| self.extern_type(e)),
+                }.into()
+            };
+
+            let Some(ext) = lookup_import(import.module, import.name) else {
+                return Err(link_error(None));
             };
 
             match import.kind {
                 wasm::ImportKind::Func(ty) => {
                     let ty = module.types[ty as usize];
 
-                    let Extern::Func(func_id) = lookup_import(import.module, import.name)? else {
-                        todo!()
+                    let Extern::Func(func_id) = ext else {
+                        return Err(link_error(Some(ext)));
                     };
 
                     let func = self.funcs[func_id].clone();
-                    let func_ty = unsafe { &*func.get() }.ty;
-                    if func_ty != ty {
-                        dbg!(func_ty);
-                        dbg!(ty);
-                        todo!()
+                    if unsafe { &*func.get() }.ty != ty {
+                        return Err(link_error(Some(ext)));
                     }
 
                     funcs.push(func);
                 }
 
-                wasm::ImportKind::Table(_) => {
-                    return Err(Error::Todo);
+                wasm::ImportKind::Table(ty) => {
+                    let Extern::Table(table_id) = ext else {
+                        return Err(link_error(Some(ext)));
+                    };
+
+                    let table = self.tables[table_id].clone();
+                    let t = Table::new(&table);
+                    if t.ty() != ty.ty || !t.limits().matches(ty.limits) {
+                        return Err(link_error(Some(ext)));
+                    }
+
+                    tables.push(table);
                 }
 
-                wasm::ImportKind::Memory(_) => {
-                    return Err(Error::Todo);
+                wasm::ImportKind::Memory(ty) => {
+                    let Extern::Memory(memory_id) = ext else {
+                        return Err(link_error(Some(ext)));
+                    };
+
+                    let memory = self.memories[memory_id].clone();
+                    let m = Memory::new(&memory);
+                    if m.is64() != ty.is64 || !m.limits().matches(ty.limits) {
+                        return Err(link_error(Some(ext)));
+                    }
+
+                    memories.push(memory);
                 }
 
                 wasm::ImportKind::Global(ty) => {
-                    let Extern::Global(global_id) = lookup_import(import.module, import.name)? else {
-                        todo!()
+                    let Extern::Global(global_id) = ext else {
+                        return Err(link_error(Some(ext)));
                     };
 
                     let global = self.globals[global_id].clone();
                     let g = Global::new(&global);
-                    if g.ty() != ty.ty {
-                        todo!()
-                    }
-                    if !g.mutable() && ty.mutable {
-                        todo!()
+                    if g.ty() != ty.ty || g.mutable() != ty.mutable {
+                        return Err(link_error(Some(ext)));
                     }
 
                     globals.push(global);
@@ -326,29 +516,20 @@ impl Store {
         }
 
 
-        let mut validator = wasm::Validator::new(&module);
-        for (i, code) in module.codes.iter().enumerate() {
-            let mut p = wasm::Parser::from_sub_section(&*wasm, code.expr);
-
-            let ty_idx = module.funcs[i];
-            let ty = module.types[module.funcs[i] as usize];
-
-
-            if 0==1 { println!("{i}") };
-            let mut jumps = HashMap::new();
-            validator.validate_func(&mut p, ty_idx, code.locals, Some(&mut jumps))
-                .map_err(|e| Error::Wasm(e))?;
-
+        for (i, f) in compiled.funcs.iter().enumerate() {
             let interp_func = InterpFunc {
                 instance: instance_id,
-                code: unsafe { wasm.as_ptr().add(code.expr.offset) },
-                code_len: code.expr.len,
-                num_params: ty.params.len() as u32,
-                num_locals: validator.num_locals(),
-                stack_size: validator.stack_size(),
-                jumps,
+                func_idx: funcs.len() as u32,
+                code: f.code,
+                code_len: f.code_len,
+                jumps: &f.jumps,
+                num_params: f.num_params,
+                num_locals: f.num_locals,
+                stack_size: f.stack_size,
             };
 
+            let ty = module.types[module.funcs[i] as usize];
+
             let id = self.funcs.next_key();
             let func = Rc::new(UnsafeCell::new(
                 FuncData { id, ty, kind: FuncKind::Interp(interp_func) }));
@@ -364,55 +545,60 @@ impl Store {
         debug_assert_eq!(tables.len(), num_tables);
 
         for mem in module.memories {
-            let id = self.new_memory(mem.limits)?;
+            let id = self.new_memory_of_type(*mem)?;
             memories.push(self.memories[id].clone());
         }
         debug_assert_eq!(memories.len(), num_memories);
 
         for global in module.globals {
-            let init = match global.init {
-                wasm::ConstExpr::I32(v) => Value::I32(v),
-                wasm::ConstExpr::I64(v) => Value::I64(v),
-                wasm::ConstExpr::F32(v) => Value::F32(v),
-                wasm::ConstExpr::F64(v) => Value::F64(v),
-                wasm::ConstExpr::Global(idx) => Global::new(&globals.inner()[idx as usize]).get(),
-                wasm::ConstExpr::RefNull(ty) => match ty {
-                    wasm::RefType::FuncRef => Value::FuncRef(RefValue::NULL),
-                    wasm::RefType::ExternRef => Value::ExternRef(RefValue::NULL),
-                },
-            };
+            let init = eval_const_expr(global.init, &funcs, &globals);
 
             let id = self.new_global(global.ty.mutable, init);
             globals.push(self.globals[id].clone());
         }
         debug_assert_eq!(globals.len(), num_globals);
 
+        let dropped_elems = module.elements.iter().map(|elem| !matches!(elem.kind, wasm::ElementKind::Passive)).collect();
+        let dropped_datas = module.datas.iter().map(|data| !matches!(data.kind, wasm::DataKind::Passive)).collect();
 
-        for elem in module.elements {
+        self.instances.push(Rc::new(UnsafeCell::new(InstanceData {
+            id: instance_id,
+            compiled,
+            module,
+            funcs,
+            tables,
+            memories,
+            globals,
+            dropped_elems,
+            dropped_datas,
+        })));
+
+        // the instance is pushed before initialization, as its functions
+        // may already be reachable through imported tables when a segment
+        // is out of bounds or the start function traps.
+        // the writes of the preceding segments remain in that case.
+        let inst = unsafe { &*self.instances[instance_id].get() };
+
+        for elem in inst.module.elements {
             match elem.kind {
                 wasm::ElementKind::Passive => (),
 
                 wasm::ElementKind::Active { table, offset } => {
-                    let mut tab = Table::new(&tables.inner()[table as usize]);
+                    let mut tab = Table::new(&inst.tables.inner()[table as usize]);
                     let values = unsafe { tab.as_mut_slice() };
 
+                    // the parser validates the offset types.
+                    let Value::I32(offset) = eval_const_expr(offset, &inst.funcs, &inst.globals) else { unreachable!() };
+                    let offset = offset as u32;
                     let Some(end) = (offset as usize).checked_add(elem.values.len()) else {
-                        todo!()
+                        return Err(Error::TrapTableBounds);
                     };
                     if end > values.len() {
-                        todo!()
+                        return Err(Error::TrapTableBounds);
                     }
 
                     for i in 0..elem.values.len() {
-                        match elem.ty {
-                            wasm::RefType::FuncRef => {
-                                let idx = elem.values[i];
-                                let id = unsafe { &*funcs.inner()[idx as usize].get() }.id.inner();
-                                values[offset as usize + i] = RefValue { id };
-                            }
-
-                            wasm::RefType::ExternRef => todo!(),
-                        }
+                        values[offset as usize + i] = inst.elem_value(elem, i);
                     }
                 }
 
@@ -420,20 +606,26 @@ impl Store {
             }
         }
 
-        for data in module.datas {
+        for data in inst.module.datas {
             let bytes = data.values;
             match data.kind {
                 wasm::DataKind::Passive => (),
 
                 wasm::DataKind::Active { mem, offset } => {
-                    let mut mem = Memory::new(&memories.inner()[mem as usize]);
+                    let mut mem = Memory::new(&inst.memories.inner()[mem as usize]);
                     let (ptr, mem_len) = mem.as_mut_ptr();
 
-                    let Some(end) = (offset as usize).checked_add(bytes.len()) else {
-                        todo!()
+                    // the parser validates the offset types.
+                    let offset = match eval_const_expr(offset, &inst.funcs, &inst.globals) {
+                        Value::I32(offset) => offset as u32 as u64,
+                        Value::I64(offset) => offset as u64,
+                        _ => unreachable!(),
                     };
-                    if end > mem_len {
-                        todo!()
+                    let Some(end) = offset.checked_add(bytes.len() as u64) else {
+                        return Err(Error::TrapMemoryBounds);
+                    };
+                    if end > mem_len as u64 {
+                        return Err(Error::TrapMemoryBounds);
                     }
 
                     unsafe {
@@ -446,37 +638,152 @@ impl Store {
             }
         }
 
-        self.instances.push(Rc::new(UnsafeCell::new(InstanceData {
-            id: instance_id,
-            wasm,
-            alloc,
-            module,
-            funcs,
-            tables,
-            memories,
-            globals,
-        })));
+        let start = inst.module.start.map(|idx|
+            unsafe { &*inst.funcs.inner()[idx as usize].get() }.id);
+
+        // the start function's host imports see this instance as the caller.
+        if let Some(start) = start {
+            self.run_start(start)?;
+        }
 
         return Ok(instance_id);
     }
 
+    /// runs a start function as a nested call.
+    /// - it can't be suspended, as the host never gets the instance
+    ///   of a start function that failed.
+    /// - a suspended call remains suspended.
+    fn run_start(&mut self, func_id: FuncId) -> Result<(), Error> {
+        let thread = &mut self.thread;
+        let num_values = thread.stack.len();
+        let num_frames = thread.frames.len();
+        let num_host_frames = thread.host_frames.len();
+        if num_frames == 0 {
+            self.begin_epoch();
+        }
+
+        // like a call from a host function, so it isn't a root call.
+        let thread = &mut self.thread;
+        thread.frames.push(None);
+        let result = self.run_func(func_id);
+
+        let thread = &mut self.thread;
+        thread.stack.truncate(num_values);
+        thread.frames.truncate(num_frames);
+        thread.host_frames.truncate(num_host_frames);
+        thread.trapped = false;
+        if num_frames == 0 {
+            self.debug.end_root_call();
+        }
+        return result;
+    }
+
+    pub fn extern_type(&self, ext: Extern) -> ExternType {
+        match ext {
+            Extern::Func(id) => ExternType::func(unsafe { &*self.funcs[id].get() }.ty),
+
+            Extern::Table(id) => {
+                let t = Table::new(&self.tables[id]);
+                ExternType::Table(wasm::TableType { ty: t.ty(), limits: t.limits() })
+            }
+
+            Extern::Memory(id) => {
+                let m = Memory::new(&self.memories[id]);
+                ExternType::Memory(wasm::MemoryType { limits: m.limits(), is64: m.is64() })
+            }
+
+            Extern::Global(id) => {
+                let g = Global::new(&self.globals[id]);
+                ExternType::Global(wasm::GlobalType { ty: g.ty(), mutable: g.mutable() })
+            }
+        }
+    }
+
     pub fn get_export(&self, instance_id: InstanceId, name: &str) -> Result<Extern, Error> {
         let inst = unsafe { &*self.instances[instance_id].get() };
 
         for export in inst.module.exports {
             if export.name == name {
-                return Ok(match export.kind {
-                    wasm::ExportKind::Func(idx)   => Extern::Func(unsafe { &*inst.funcs.inner()[idx as usize].get() }.id),
-                    wasm::ExportKind::Table(idx)  => Extern::Table(Table::new(&inst.tables.inner()[idx as usize]).id()),
-                    wasm::ExportKind::Memory(idx) => Extern::Memory(Memory::new(&inst.memories.inner()[idx as usize]).id()),
-                    wasm::ExportKind::Global(idx) => Extern::Global(Global::new(&inst.globals.inner()[idx as usize]).id()),
-                });
+                return Ok(inst.export_extern(export.kind));
             }
         }
 
         todo!()
     }
 
+    pub fn exports(&self, instance_id: InstanceId) -> impl Iterator<Item = (&str, Extern)> {
+        let inst = unsafe { &*self.instances[instance_id].get() };
+        inst.module.exports.iter().map(|export| (export.name, inst.export_extern(export.kind)))
+    }
+
+    /// the name of a function of the instance's module,
+    /// from the module's `name` section.
+    /// - `func` is the function's index in the module,
+    ///   like in `BacktraceFrame::Wasm`.
+    pub fn func_name(&self, instance_id: InstanceId, func: u32) -> Option<&str> {
+        let inst = unsafe { &*self.instances[instance_id].get() };
+        inst.module.names.func(func)
+    }
+
+    /// the source location of a code offset of the instance's module,
+    /// from the module's dwarf debug info.
+    /// - `offset` is a byte offset into the module,
+    ///   like in `BacktraceFrame::Wasm`.
+    #[cfg(feature = "dwarf")]
+    pub fn source_location(&self, instance_id: InstanceId, offset: u32) -> Option<crate::dwarf::SourceLocation<'_>> {
+        let inst = unsafe { &*self.instances[instance_id].get() };
+        inst.compiled.debug_info()?.location(offset as usize)
+    }
+
+    /// formats a backtrace, one frame per line.
+    /// - function names come from the dwarf debug info or the `name` section.
+    /// - source locations come from the dwarf debug info.
+    pub fn format_backtrace(&self, backtrace: &[BacktraceFrame]) -> std::string::String {
+        use core::fmt::Write;
+
+        let mut result = std::string::String::new();
+        for (i, frame) in backtrace.iter().enumerate() {
+            let _ = write!(result, "{i:4}: ");
+            match *frame {
+                BacktraceFrame::Wasm { instance, func, offset } => {
+                    #[cfg(feature = "dwarf")]
+                    let location = {
+                        // callers point just after the call.
+                        let offset = if i == 0 { offset } else { offset.saturating_sub(1) };
+                        self.source_location(instance, offset)
+                    };
+                    #[cfg(feature = "dwarf")]
+                    let name = location.and_then(|l| l.func).or_else(|| self.func_name(instance, func));
+                    #[cfg(not(feature = "dwarf"))]
+                    let name = self.func_name(instance, func);
+
+                    let _ = match name {
+                        Some(name) => write!(result, "{name}"),
+                        None => write!(result, "func[{func}]"),
+                    };
+                    let _ = write!(result, " @ {offset:#x}");
+
+                    #[cfg(feature = "dwarf")]
+                    if let Some(location) = location {
+                        if let Some(file) = location.file {
+                            let _ = match (location.line, location.column) {
+                                (0, _) => write!(result, " at {file}"),
+                                (line, 0) => write!(result, " at {file}:{line}"),
+                                (line, column) => write!(result, " at {file}:{line}:{column}"),
+                            };
+                        }
+                    }
+                }
+
+                BacktraceFrame::Host { func } => {
+                    let _ = write!(result, "<host {func:?}>");
+                }
+            }
+            result.push('\n');
+        }
+        return result;
+    }
+
     pub fn get_export_func_dyn(&self, instance_id: InstanceId, name: &str) -> Result<FuncId, Error> {
         let Extern::Func(func) = self.get_export(instance_id, name)? else {
             todo!()
@@ -498,6 +805,14 @@ impl Store {
         self.check_func_type(func)
     }
 
+    /// the function of a `funcref`.
+    /// - returns `None` for null.
+    #[inline]
+    pub fn func_ref_id(&self, r: FuncRef) -> Option<FuncId> {
+        let func = self.funcs.inner().get(r.0.to_option()? as usize)?;
+        Some(unsafe { &*func.get() }.id)
+    }
+
     pub fn get_export_table(&self, instance_id: InstanceId, name: &str) -> Result<TableId, Error> {
         let Extern::Table(tab) = self.get_export(instance_id, name)? else {
             todo!()
@@ -529,20 +844,20 @@ impl Store {
         //  module's arena).
         let ty = func.ty;
         if args.len() != ty.params.len() {
-            todo!()
+            return Err(Error::TypeMismatch);
         }
         for i in 0..args.len() {
             if args[i].ty() != ty.params[i] {
-                todo!()
+                return Err(Error::TypeMismatch);
             }
         }
 
         if !allow_rets_mismatch && rets.len() != ty.rets.len() {
-            todo!()
+            return Err(Error::TypeMismatch);
         }
 
 
-        let is_root = self.thread.frames.len() == 0;
+        let is_root = self.begin_call();
 
         // push args onto stack.
         let bp = self.thread.stack.len();
@@ -553,23 +868,58 @@ impl Store {
 
         if let Err(e) = self.run_func(func_id) {
             if is_root {
-                self.thread.stack.clear();
-                self.thread.frames.clear();
-                self.thread.trapped = false;
+                self.end_failed_call();
             }
             return Err(e);
         }
 
+        return Ok(self.pop_rets_dyn(ty, bp, rets));
+    }
+
+    fn pop_rets_dyn<'r>(&mut self, ty: wasm::FuncType, bp: usize, rets: &'r mut [Value]) -> &'r mut [Value] {
         debug_assert_eq!(self.thread.stack.len(), bp + ty.rets.len());
 
-        // pop rets from stack.
         let num_rets = ty.rets.len().min(rets.len());
         for i in 0..num_rets {
             rets[i] = self.thread.stack[bp + i].to_value(ty.rets[i]);
         }
         self.thread.stack.truncate(bp);
 
-        return Ok(&mut rets[..num_rets]);
+        return &mut rets[..num_rets];
+    }
+
+    /// returns whether this is a root call.
+    /// - a suspended call is discarded by the next root call.
+    fn begin_call(&mut self) -> bool {
+        if self.thread.suspended.take().is_some() {
+            self.thread.stack.clear();
+            self.thread.frames.clear();
+            self.thread.host_frames.clear();
+        }
+
+        let is_root = self.thread.frames.len() == 0;
+        if is_root {
+            self.begin_epoch();
+        }
+        return is_root;
+    }
+
+    /// later interrupts stop the root call that starts now.
+    #[inline]
+    pub(crate) fn begin_epoch(&mut self) {
+        self.call_epoch = self.interrupt_epoch.load(Ordering::Relaxed);
+    }
+
+    /// resets the thread after a root call failed,
+    /// unless the call was suspended.
+    fn end_failed_call(&mut self) {
+        if self.thread.suspended.is_none() {
+            self.thread.stack.clear();
+            self.thread.frames.clear();
+            self.thread.host_frames.clear();
+            self.thread.trapped = false;
+            self.debug.end_root_call();
+        }
     }
 
     pub fn call_dyn<'r>(&mut self, func_id: FuncId, args: &[Value], rets: &'r mut [Value]) -> Result<&'r mut [Value], Error> {
@@ -582,6 +932,8 @@ impl Store {
         debug_assert_eq!(func.ty.params, P::WASM_TYPES);
         debug_assert_eq!(func.ty.rets,   R::WASM_TYPES);
 
+        let is_root = self.begin_call();
+
         // push args onto stack.
         let stack = &mut self.thread.stack;
         let bp = stack.len();
@@ -591,16 +943,128 @@ impl Store {
             stack.set_len(bp + P::WASM_TYPES.len());
         }
 
-        self.run_func(func_id.func_id)?;
+        if let Err(e) = self.run_func(func_id.func_id) {
+            if is_root {
+                self.end_failed_call();
+            }
+            return Err(e);
+        }
 
+        return Ok(self.pop_rets(bp));
+    }
+
+    fn pop_rets<R: WasmTypes>(&mut self, bp: usize) -> R {
         let stack = &mut self.thread.stack;
         debug_assert_eq!(stack.len(), bp + R::WASM_TYPES.len());
 
-        // pop results from stack.
         let result = unsafe { R::from_stack_values(stack.as_mut_ptr().add(bp)) };
         stack.truncate(bp);
+        return result;
+    }
+
+
+    /// sets the remaining fuel.
+    /// - each executed instruction consumes one unit of fuel.
+    /// - when a call runs out of fuel, it fails with `Error::OutOfFuel`.
+    ///   if it was called by the host (not from a host function),
+    ///   it is suspended & can be continued with `resume` once more
+    ///   fuel has been added.
+    /// - by default, the fuel is unlimited (`u64::MAX`).
+    pub fn set_fuel(&mut self, fuel: u64) {
+        self.fuel = fuel;
+    }
+
+    #[inline]
+    pub fn fuel(&self) -> u64 {
+        self.fuel
+    }
+
+    /// the total fuel consumed by this store.
+    #[inline]
+    pub fn fuel_consumed(&self) -> u64 {
+        self.fuel_consumed
+    }
+
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle { epoch: self.interrupt_epoch.clone() }
+    }
+
+    /// limits the number of nested calls.
+    /// - exceeding the limit fails the call with `Error::TrapStackOverflow`.
+    /// - host functions & calls from host functions count towards the limit.
+    /// - the default is 10'000.
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    #[inline]
+    pub fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    /// limits the size of the value stack, in values.
+    /// - this includes the locals & operands of all active calls.
+    /// - exceeding the limit fails the call with `Error::TrapStackOverflow`.
+    /// - the default is 2^20 (8 MiB).
+    pub fn set_max_stack_size(&mut self, max_stack_size: usize) {
+        self.max_stack_size = max_stack_size;
+    }
+
+    #[inline]
+    pub fn max_stack_size(&self) -> usize {
+        self.max_stack_size
+    }
+
+    /// limits the number of elements a table can be grown to by wasm.
+    /// - `table.grow` returns -1 if the table would exceed the limit.
+    /// - `Table::grow` isn't limited.
+    /// - the default is 2^20.
+    pub fn set_max_table_elements(&mut self, max_table_elements: usize) {
+        self.max_table_elements = max_table_elements;
+    }
+
+    #[inline]
+    pub fn max_table_elements(&self) -> usize {
+        self.max_table_elements
+    }
+
+    /// whether a call ran out of fuel & can be resumed.
+    /// - the next call discards the suspended call.
+    #[inline]
+    pub fn is_suspended(&self) -> bool {
+        self.thread.suspended.is_some()
+    }
+
+    /// continues a call that ran out of fuel.
+    pub fn resume_dyn<'r>(&mut self, rets: &'r mut [Value]) -> Result<&'r mut [Value], Error> {
+        let Some(suspended) = &self.thread.suspended else {
+            return Err(Error::NotSuspended);
+        };
+        let bp = suspended.root_bp;
+        let ty = unsafe { &*self.funcs[suspended.root_func].get() }.ty;
+
+        if let Err(e) = self.resume_interp().0 {
+            self.end_failed_call();
+            return Err(e);
+        }
+
+        return Ok(self.pop_rets_dyn(ty, bp, rets));
+    }
+
+    /// continues a call that ran out of fuel.
+    pub fn resume<R: WasmTypes>(&mut self) -> Result<R, Error> {
+        let Some(suspended) = &self.thread.suspended else {
+            return Err(Error::NotSuspended);
+        };
+        let bp = suspended.root_bp;
+        debug_assert_eq!(unsafe { &*self.funcs[suspended.root_func].get() }.ty.rets, R::WASM_TYPES);
+
+        if let Err(e) = self.resume_interp().0 {
+            self.end_failed_call();
+            return Err(e);
+        }
 
-        return Ok(result);
+        return Ok(self.pop_rets(bp));
     }
 
     fn run_func(&mut self, id: FuncId) -> Result<(), Error> {
@@ -623,13 +1087,23 @@ impl Store {
             FuncKind::Host(f) => {
                 debug_assert!(stack.len() >= f.num_params as usize);
                 debug_assert!(stack.cap() >= stack.len() - f.num_params as usize + f.num_rets as usize);
-                (f.call)(&*f.data as *const _ as *const u8, self)
+
+                // so wasm called by the host function isn't a root call.
+                self.thread.frames.push(None);
+                self.thread.host_frames.push((self.thread.frames.len(), id));
+                let result = (f.call)(&*f.data as *const _ as *const u8, self)
+                    .map_err(|e| self.trap(e, None));
+                if !self.thread.trapped {
+                    self.thread.frames.pop();
+                    self.thread.host_frames.pop();
+                }
+                result
             }
 
             FuncKind::Var(_) => unreachable!()
         };
 
-        if !self.thread.trapped {
+        if !self.thread.trapped && self.thread.suspended.is_none() {
             debug_assert_eq!(self.thread.frames.len(), old_num_frames);
         }
 
@@ -657,15 +1131,63 @@ impl Store {
                     call: |this, store| {
                         H::call(unsafe { &*(this as *const H) }, store)
                     },
-                    // have impls for tuples up to len 16.
-                    num_params: P::WASM_TYPES.len().try_into().unwrap(),
-                    num_rets: R::WASM_TYPES.len().try_into().unwrap(),
+                    num_params: P::WASM_TYPES.len() as u32,
+                    num_rets: R::WASM_TYPES.len() as u32,
                 })
         })));
 
         return id;
     }
 
+    /// creates a host function with a signature that's only known at runtime.
+    /// - `f` receives the arguments & writes the results.
+    ///   `rets` is initialized with zero values of the result types.
+    /// - `call_dyn` checks the argument types.
+    ///   if `f` writes a result of the wrong type,
+    ///   the call fails with `Error::TypeMismatch`.
+    /// - `v128` params & results aren't supported (`Error::Unimplemented`).
+    pub fn new_host_func_dyn<F>(&mut self, ty: wasm::FuncType, f: F) -> Result<FuncId, Error>
+    where F: Fn(&mut Store, &[Value], &mut [Value]) -> Result<(), Error> + 'static {
+        if ty.params.iter().chain(ty.rets).any(|ty| *ty == wasm::ValueType::V128) {
+            return Err(Error::Unimplemented);
+        }
+
+        let id = self.funcs.next_key();
+
+        let mut types = Vec::from_slice(ty.params);
+        for ret in ty.rets {
+            types.push(*ret);
+        }
+
+        let data = Box::new(DynHostFunc { types, num_params: ty.params.len(), f });
+
+        // safety: `data` is owned by the `FuncData` & never modified.
+        let (params, rets) = data.types.split_at(data.num_params);
+        let ty = unsafe { wasm::FuncType {
+            params: core::mem::transmute::<&[wasm::ValueType], &'static [wasm::ValueType]>(params),
+            rets:   core::mem::transmute::<&[wasm::ValueType], &'static [wasm::ValueType]>(rets),
+        }};
+
+        self.funcs.push(Rc::new(UnsafeCell::new(FuncData {
+            id,
+            ty,
+            kind: FuncKind::Host(
+                HostFuncData {
+                    num_params: ty.params.len() as u32,
+                    num_rets: ty.rets.len() as u32,
+                    data: {
+                        let (data, alloc) = data.into_raw_parts();
+                        unsafe { Box::from_raw_parts(data as NonNull<dyn Any>, alloc) }
+                    },
+                    call: |this, store| {
+                        unsafe { &*(this as *const DynHostFunc<F>) }.call(store)
+                    },
+                })
+        })));
+
+        return Ok(id);
+    }
+
     pub fn new_func_var<P: WasmTypes, R: WasmTypes>(&mut self) -> TypedFuncId<P, R> {
         let id = TypedFuncId {
             func_id: self.funcs.next_key(),
@@ -717,8 +1239,17 @@ impl Store {
     }
 
     pub fn new_memory(&mut self, limits: wasm::Limits) -> Result<MemoryId, Error> {
+        self.new_memory_of_type(wasm::MemoryType { limits, is64: false })
+    }
+
+    /// creates a memory with `i64` addresses.
+    pub fn new_memory64(&mut self, limits: wasm::Limits) -> Result<MemoryId, Error> {
+        self.new_memory_of_type(wasm::MemoryType { limits, is64: true })
+    }
+
+    fn new_memory_of_type(&mut self, ty: wasm::MemoryType) -> Result<MemoryId, Error> {
         let id = self.memories.next_key();
-        let memory = MemoryData::new(id, limits)?;
+        let memory = MemoryData::new(id, ty.limits, ty.is64)?;
         let memory = Rc::new(UnsafeCell::new(memory));
         self.memories.push(memory);
         return Ok(id);
@@ -737,19 +1268,108 @@ impl Store {
     }
 
 
+    /// the current call stack, innermost frame first.
+    /// - for use in host functions.
+    pub fn backtrace(&self) -> std::vec::Vec<BacktraceFrame> {
+        self.capture_backtrace(None)
+    }
+
+    /// - `current` is the executing wasm function & instruction, if any.
+    pub(crate) fn capture_backtrace(&self, current: Option<(FuncId, *const u8)>) -> std::vec::Vec<BacktraceFrame> {
+        let wasm_frame = |func: FuncId, pc: *const u8| {
+            let func = unsafe { &*self.funcs[func].get() };
+            let FuncKind::Interp(f) = &func.kind else { unreachable!() };
+            let inst = unsafe { &*self.instances[f.instance].get() };
+            let offset = pc as usize - inst.compiled.wasm.as_ptr() as usize;
+            BacktraceFrame::Wasm { instance: f.instance, func: f.func_idx, offset: offset as u32 }
+        };
+
+        let mut result = std::vec::Vec::new();
+        if let Some((func, pc)) = current {
+            result.push(wasm_frame(func, pc));
+        }
+
+        let mut host_frames = self.thread.host_frames.iter().rev().peekable();
+        for (i, frame) in self.thread.frames.iter().enumerate().rev() {
+            while let Some((_, func)) = host_frames.next_if(|(depth, _)| *depth > i) {
+                result.push(BacktraceFrame::Host { func: *func });
+            }
+            if let Some(frame) = frame {
+                result.push(wasm_frame(frame.func, frame.pc.as_ptr()));
+            }
+        }
+        return result;
+    }
+
+    /// attaches a backtrace to traps.
+    pub(crate) fn trap(&self, e: Error, current: Option<(FuncId, *const u8)>) -> Error {
+        if !e.is_trap() {
+            return e;
+        }
+        let backtrace = self.capture_backtrace(current);
+        return Error::Trap(std::boxed::Box::new(Trap { error: e, backtrace }));
+    }
+
     pub fn caller_instance(&self) -> Result<InstanceId, Error> {
         // @speed: cache?
         let Some(Some(frame)) = self.thread.frames.last() else { return Err(Error::CallerNotWasm) };
         return Ok(frame.instance);
     }
 
+    /// the caller's memory 0.
+    #[inline]
     pub fn caller_memory<'a>(&'a self) -> Result<Memory<'a>, Error> {
+        self.caller_memory_at(0)
+    }
+
+    /// one of the caller's memories, by its index in the caller's module.
+    /// - imported memories come first.
+    pub fn caller_memory_at<'a>(&'a self, idx: wasm::MemoryIdx) -> Result<Memory<'a>, Error> {
         // @speed: cache?
         let Some(Some(frame)) = self.thread.frames.last() else { return Err(Error::CallerNotWasm) };
         let inst = unsafe { &*self.instances[frame.instance].get() };
-        let Some(mem) = inst.memories.inner().get(0) else { return Err(Error::CallerNoMemory) };
+        let Some(mem) = inst.memories.inner().get(idx as usize) else { return Err(Error::CallerNoMemory) };
         return Ok(Memory::new(mem));
     }
+
+    /// the caller's memory & the store's data.
+    /// - for host functions that need both at the same time.
+    /// - fails with `Error::NoData` if the data isn't a `T`.
+    pub fn caller_memory_and_data<'a, T: Any>(&'a mut self) -> Result<(Memory<'a>, &'a mut T), Error> {
+        let Some(Some(frame)) = self.thread.frames.last() else { return Err(Error::CallerNotWasm) };
+        let inst = unsafe { &*self.instances[frame.instance].get() };
+        let Some(mem) = inst.memories.inner().get(0) else { return Err(Error::CallerNoMemory) };
+        let Some(data) = self.data.as_mut().and_then(|data| data.downcast_mut()) else { return Err(Error::NoData) };
+        return Ok((Memory::new(mem), data));
+    }
+
+
+    /// sets the store's user data, replacing the previous data.
+    /// - host functions can access the data through the store,
+    ///   instead of capturing shared state.
+    pub fn set_data<T: Any>(&mut self, data: T) {
+        self.data = Some(std::boxed::Box::new(data));
+    }
+
+    /// the store's user data, if it is a `T`.
+    #[inline]
+    pub fn data<T: Any>(&self) -> Option<&T> {
+        self.data.as_ref()?.downcast_ref()
+    }
+
+    #[inline]
+    pub fn data_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.data.as_mut()?.downcast_mut()
+    }
+
+    /// removes the store's user data, if it is a `T`.
+    pub fn take_data<T: Any>(&mut self) -> Option<T> {
+        if !self.data.as_ref()?.is::<T>() {
+            return None;
+        }
+        let data = self.data.take()?.downcast().ok()?;
+        return Some(*data);
+    }
 }
 
 
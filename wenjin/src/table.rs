@@ -28,16 +28,18 @@ impl TableData {
             values: ManualVec::new(),
         };
 
-        this.grow(limits.min, default).map_err(|_| Error::OOM)?;
+        let min = u32::try_from(limits.min).map_err(|_| Error::OOM)?;
+        this.grow(min, default).map_err(|_| Error::OOM)?;
 
         return Ok(this);
     }
 
-    fn grow(&mut self, delta: u32, default: RefValue) -> Result<(), ()> {
-        let Some(new_len) = (self.values.len() as u32).checked_add(delta) else { return Err(()) };
+    fn grow(&mut self, delta: u32, default: RefValue) -> Result<u32, ()> {
+        let old_len = self.values.len() as u32;
+        let Some(new_len) = old_len.checked_add(delta) else { return Err(()) };
 
         if let Some(max_len) = self.limits.max {
-            if new_len > max_len {
+            if new_len as u64 > max_len {
                 return Err(());
             }
         }
@@ -47,7 +49,7 @@ impl TableData {
             self.values.push(default).unwrap_debug();
         }
 
-        return Ok(());
+        return Ok(old_len);
     }
 }
 
@@ -63,6 +65,30 @@ impl<'a> Table<'a> {
         unsafe { self.inner.as_ref().id }
     }
 
+    #[inline]
+    pub fn ty(&self) -> RefType {
+        unsafe { self.inner.as_ref().ty }
+    }
+
+    #[inline]
+    pub fn len(&self) -> u32 {
+        unsafe { self.inner.as_ref().values.len() as u32 }
+    }
+
+    /// the table's current limits.
+    /// - `min` is the current length.
+    #[inline]
+    pub fn limits(&self) -> Limits {
+        unsafe { Limits { min: self.len() as u64, max: self.inner.as_ref().limits.max } }
+    }
+
+    /// - returns the previous length.
+    #[inline]
+    pub fn grow(&mut self, delta: u32, init: RefValue) -> Result<u32, Error> {
+        unsafe { self.inner.as_mut().grow(delta, init) }
+        .map_err(|_| Error::OOM)
+    }
+
     #[inline]
     pub(crate) fn new(table: &UnsafeCell<TableData>) -> Self {
         Self { inner: NonNull::from(table).cast(), phantom: PhantomData }
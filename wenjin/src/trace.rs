@@ -0,0 +1,180 @@
+use std::io::Write;
+
+use crate::store::{Store, FuncKind, StackValue, InstanceId, FuncId};
+
+
+/// receives the execution events of a store.
+/// - set with `Store::set_tracer`.
+/// - traced calls run on a separate copy of the interpreter,
+///   so untraced calls don't pay for the hooks.
+/// - `offset`s are byte offsets into the module's binary,
+///   like in `BacktraceFrame::Wasm`.
+pub trait Tracer {
+    /// called before each wasm instruction.
+    /// - `operands` is the function's operand stack, bottom first.
+    #[allow(unused_variables)]
+    fn instruction(&mut self, func: FuncId, offset: u32, opcode: wasm::Opcode, operands: &[StackValue]) {}
+
+    /// called when wasm or a host function is entered.
+    /// - `args` has the types `ty.params`.
+    #[allow(unused_variables)]
+    fn call(&mut self, func: FuncId, ty: wasm::FuncType, args: &[StackValue]) {}
+
+    /// called when a function returns.
+    /// - not called for calls that trap.
+    /// - `rets` has the types `ty.rets`.
+    #[allow(unused_variables)]
+    fn ret(&mut self, func: FuncId, ty: wasm::FuncType, rets: &[StackValue]) {}
+}
+
+
+/// writes one line per event.
+/// - lines are indented by the call depth.
+/// - instructions show the top 3 operands, in hex, as their types aren't known.
+/// - write errors are ignored.
+pub struct TextTracer<W: Write> {
+    writer: W,
+    depth: usize,
+}
+
+impl<W: Write> TextTracer<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, depth: 0 }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn indent(&mut self) {
+        _ = write!(self.writer, "{:1$}", "", 2*self.depth);
+    }
+
+    fn values(&mut self, values: &[StackValue], types: &[wasm::ValueType]) {
+        _ = write!(self.writer, "(");
+        for (i, (value, ty)) in values.iter().zip(types).enumerate() {
+            if i > 0 {
+                _ = write!(self.writer, ", ");
+            }
+            _ = write!(self.writer, "{:?}", value.to_value(*ty));
+        }
+        _ = write!(self.writer, ")");
+    }
+}
+
+impl<W: Write> Tracer for TextTracer<W> {
+    fn instruction(&mut self, func: FuncId, offset: u32, opcode: wasm::Opcode, operands: &[StackValue]) {
+        self.indent();
+        _ = write!(self.writer, "{} @ {offset:#x}: {opcode:?}", func.inner());
+        if !operands.is_empty() {
+            let top = &operands[operands.len().saturating_sub(3)..];
+            _ = write!(self.writer, " [");
+            for (i, value) in top.iter().enumerate() {
+                if i > 0 {
+                    _ = write!(self.writer, ", ");
+                }
+                _ = write!(self.writer, "{:#x}", value.as_i64());
+            }
+            _ = write!(self.writer, "]");
+        }
+        _ = writeln!(self.writer);
+    }
+
+    fn call(&mut self, func: FuncId, ty: wasm::FuncType, args: &[StackValue]) {
+        self.indent();
+        _ = write!(self.writer, "call {} ", func.inner());
+        self.values(args, ty.params);
+        _ = writeln!(self.writer);
+        self.depth += 1;
+    }
+
+    fn ret(&mut self, func: FuncId, ty: wasm::FuncType, rets: &[StackValue]) {
+        self.depth = self.depth.saturating_sub(1);
+        self.indent();
+        _ = write!(self.writer, "ret {} ", func.inner());
+        self.values(rets, ty.rets);
+        _ = writeln!(self.writer);
+    }
+}
+
+
+impl Store {
+    /// traces the wasm calls that start after this.
+    /// - replaces the previous tracer.
+    pub fn set_tracer<T: Tracer + 'static>(&mut self, tracer: T) {
+        self.tracer = Some(std::boxed::Box::new(tracer));
+    }
+
+    pub fn clear_tracer(&mut self) {
+        self.tracer = None;
+    }
+
+
+    /// whether calls need to run on the tracing interpreter.
+    #[inline]
+    pub(crate) fn tracing(&self) -> bool {
+        self.tracer.is_some() || self.profiler.is_some() || self.coverage.is_some()
+            || self.debug.has_handler()
+    }
+
+    /// - `operands` is the range of the operand stack.
+    #[inline(never)]
+    pub(crate) fn trace_instruction(&mut self, instance: InstanceId, func: FuncId, pc: *const u8, opcode: wasm::Opcode, operands: (*const StackValue, *const StackValue)) {
+        if let Some(profiler) = &mut self.profiler {
+            profiler.instruction();
+        }
+        if let Some(coverage) = &mut self.coverage {
+            let FuncKind::Interp(f) = &unsafe { &*self.funcs[func].get() }.kind else { unreachable!() };
+            coverage.hit(func, pc as usize - f.code_begin() as usize, f.code_len);
+        }
+
+        let Some(tracer) = &mut self.tracer else { return };
+        let inst = unsafe { &*self.instances[instance].get() };
+        let offset = pc as usize - inst.compiled.wasm.as_ptr() as usize;
+        let operands = unsafe {
+            let (begin, end) = operands;
+            core::slice::from_raw_parts(begin, end.offset_from(begin) as usize)
+        };
+        tracer.instruction(func, offset as u32, opcode, operands);
+    }
+
+    /// - `args` points at the first argument.
+    /// - the function's frame must already be on the frame stack.
+    #[inline(never)]
+    pub(crate) fn trace_call(&mut self, func: FuncId, args: *const StackValue) {
+        if let Some(profiler) = &mut self.profiler {
+            profiler.call(func, self.thread.frames.len());
+        }
+
+        let Some(tracer) = &mut self.tracer else { return };
+        let ty = unsafe { &*self.funcs[func].get() }.ty;
+        let args = unsafe { core::slice::from_raw_parts(args, ty.params.len()) };
+        tracer.call(func, ty, args);
+    }
+
+    /// - `rets` points at the first result.
+    /// - the function's frame must still be on the frame stack.
+    #[inline(never)]
+    pub(crate) fn trace_ret(&mut self, func: FuncId, rets: *const StackValue) {
+        if let Some(profiler) = &mut self.profiler {
+            profiler.unwind(self.thread.frames.len());
+        }
+
+        let Some(tracer) = &mut self.tracer else { return };
+        let ty = unsafe { &*self.funcs[func].get() }.ty;
+        let rets = unsafe { core::slice::from_raw_parts(rets, ty.rets.len()) };
+        tracer.ret(func, ty, rets);
+    }
+
+    /// closes the profiler's frames of a call that ended,
+    /// including the frames that were unwound by a trap.
+    /// - `depth` is the depth of the call's root frame.
+    pub(crate) fn trace_end(&mut self, depth: usize) {
+        if self.thread.suspended.is_some() {
+            return;
+        }
+        if let Some(profiler) = &mut self.profiler {
+            profiler.unwind(depth);
+        }
+    }
+}
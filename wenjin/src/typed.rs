@@ -1,5 +1,5 @@
-use crate::Error;
-use crate::store::{Store, StackValue};
+use crate::{Error, FuncRef, ExternRef};
+use crate::store::{Store, StackValue, RefValue};
 
 
 
@@ -70,6 +70,26 @@ impl WasmType for f64 {
     fn from_stack_value(value: StackValue) -> Self { value.as_f64() }
 }
 
+impl WasmType for FuncRef {
+    const WASM_TYPE: wasm::ValueType = wasm::ValueType::FuncRef;
+
+    #[inline(always)]
+    fn to_stack_value(self) -> StackValue { StackValue::from_i32(self.0.id as i32) }
+
+    #[inline(always)]
+    fn from_stack_value(value: StackValue) -> Self { FuncRef(RefValue { id: value.as_i32() as u32 }) }
+}
+
+impl WasmType for ExternRef {
+    const WASM_TYPE: wasm::ValueType = wasm::ValueType::ExternRef;
+
+    #[inline(always)]
+    fn to_stack_value(self) -> StackValue { StackValue::from_i32(self.0.id as i32) }
+
+    #[inline(always)]
+    fn from_stack_value(value: StackValue) -> Self { ExternRef(RefValue { id: value.as_i32() as u32 }) }
+}
+
 
 
 pub trait WasmTypes {
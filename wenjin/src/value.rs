@@ -20,6 +20,62 @@ impl Value {
             ExternRef (_) => wasm::ValueType::ExternRef,
         }
     }
+
+    /// the default value of `ty`.
+    /// - zero for numbers, null for references.
+    pub(crate) fn zero(ty: wasm::ValueType) -> Value {
+        match ty {
+            wasm::ValueType::I32 => Value::I32(0),
+            wasm::ValueType::I64 => Value::I64(0),
+            wasm::ValueType::F32 => Value::F32(0.0),
+            wasm::ValueType::F64 => Value::F64(0.0),
+            // `new_host_func_dyn` rejects `v128`.
+            wasm::ValueType::V128 => unreachable!(),
+            wasm::ValueType::FuncRef => Value::FuncRef(crate::RefValue::NULL),
+            wasm::ValueType::ExternRef => Value::ExternRef(crate::RefValue::NULL),
+        }
+    }
+}
+
+
+
+
+/// a `funcref`, as a typed function parameter or result.
+/// - null, or a function of the store.
+///   `FuncId`s convert to references, `Store::func_ref_id` converts back.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FuncRef(pub(crate) crate::RefValue);
+
+/// an `externref`, as a typed function parameter or result.
+/// - null, or a host object of the store, see `Store::new_extern_ref`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExternRef(pub(crate) crate::RefValue);
+
+impl FuncRef {
+    pub const NULL: FuncRef = FuncRef(crate::RefValue::NULL);
+
+    #[inline]
+    pub fn is_null(self) -> bool { self.0.is_null() }
+}
+
+impl ExternRef {
+    pub const NULL: ExternRef = ExternRef(crate::RefValue::NULL);
+
+    #[inline]
+    pub fn is_null(self) -> bool { self.0.is_null() }
 }
 
+impl From<crate::FuncId> for FuncRef {
+    #[inline]
+    fn from(value: crate::FuncId) -> Self { FuncRef(crate::RefValue { id: value.inner() }) }
+}
 
+impl From<FuncRef> for Value {
+    #[inline]
+    fn from(value: FuncRef) -> Self { Value::FuncRef(value.0) }
+}
+
+impl From<ExternRef> for Value {
+    #[inline]
+    fn from(value: ExternRef) -> Self { Value::ExternRef(value.0) }
+}
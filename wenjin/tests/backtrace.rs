@@ -0,0 +1,71 @@
+use wenjin::{Store, Error, BacktraceFrame, FuncId};
+
+
+#[test]
+fn backtrace() {
+    let wasm = include_bytes!("backtrace.wasm");
+
+    let mut store = Store::new();
+
+    let host_backtrace = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let trap_var = std::rc::Rc::new(std::cell::Cell::new(None::<FuncId>));
+    let callback = store.new_host_func({
+        let host_backtrace = host_backtrace.clone();
+        let trap_var = trap_var.clone();
+        move |store: &mut Store| -> Result<(), Error> {
+            *host_backtrace.borrow_mut() = store.backtrace();
+            store.call_dyn(trap_var.get().unwrap(), &[], &mut [])?;
+            Ok(())
+        }});
+
+    let inst = store.new_instance(wasm, &[("host", "callback", callback.into())]).unwrap();
+    let trap = store.get_export_func_dyn(inst, "trap").unwrap();
+    let via_host = store.get_export_func_dyn(inst, "via_host").unwrap();
+    trap_var.set(Some(trap));
+
+    let wasm_frame = |frame: BacktraceFrame| {
+        let BacktraceFrame::Wasm { instance, func, offset } = frame else { panic!("{frame:?}") };
+        assert_eq!(instance, inst);
+        (func, offset as usize)
+    };
+
+    // wasm frames.
+    let Error::Trap(t) = store.call_dyn(trap, &[], &mut []).unwrap_err() else { panic!() };
+    assert!(matches!(t.error, Error::TrapUnreachable));
+    assert_eq!(t.backtrace.len(), 3);
+
+    // the innermost frame points at the trapping instruction,
+    // the others just after the call.
+    let (func, offset) = wasm_frame(t.backtrace[0]);
+    assert_eq!(func, 1);
+    assert_eq!(wasm[offset], 0x00);
+    assert_eq!(store.func_name(inst, func), Some("inner"));
+    let (func, offset) = wasm_frame(t.backtrace[1]);
+    assert_eq!(func, 2);
+    assert_eq!(&wasm[offset-2..offset], &[0x10, 1]);
+    let (func, offset) = wasm_frame(t.backtrace[2]);
+    assert_eq!(func, 3);
+    assert_eq!(&wasm[offset-2..offset], &[0x10, 2]);
+
+
+    // host frames.
+    let Error::Trap(t) = store.call_dyn(via_host, &[], &mut []).unwrap_err() else { panic!() };
+    assert!(matches!(t.error, Error::TrapUnreachable));
+    assert_eq!(t.backtrace.len(), 5);
+    assert_eq!(wasm_frame(t.backtrace[0]).0, 1);
+    assert_eq!(wasm_frame(t.backtrace[1]).0, 2);
+    assert_eq!(wasm_frame(t.backtrace[2]).0, 3);
+    assert_eq!(t.backtrace[3], BacktraceFrame::Host { func: callback.func_id() });
+    let (func, offset) = wasm_frame(t.backtrace[4]);
+    assert_eq!(func, 4);
+    assert_eq!(&wasm[offset-2..offset], &[0x10, 0]);
+
+    // `Store::backtrace` from a host function.
+    let host_backtrace = host_backtrace.borrow().clone();
+    assert_eq!(host_backtrace.len(), 2);
+    assert_eq!(host_backtrace[0], BacktraceFrame::Host { func: callback.func_id() });
+    assert_eq!(host_backtrace[1], t.backtrace[4]);
+
+    // no frames outside of calls.
+    assert!(store.backtrace().is_empty());
+}
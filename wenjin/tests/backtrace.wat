@@ -0,0 +1,15 @@
+(module
+    (import "host" "callback" (func $callback))
+
+    (func $inner
+        unreachable)
+
+    (func $middle
+        call $inner)
+
+    (func $trap (export "trap")
+        call $middle)
+
+    (func $via_host (export "via_host")
+        call $callback)
+)
@@ -0,0 +1,4 @@
+wat2wasm \
+    --debug-names \
+    -o backtrace.wasm \
+    backtrace.wat
@@ -0,0 +1,88 @@
+use wenjin::{Store, Error};
+
+
+#[test]
+fn bulk_memory() {
+    let mut store = Store::new();
+    let inst = store.new_instance(include_bytes!("bulk.wasm"), &[]).unwrap();
+    let load8 = store.get_export_func::<i32, i32>(inst, "load8").unwrap();
+    let memory_init = store.get_export_func::<(i32, i32, i32), ()>(inst, "memory_init").unwrap();
+    let data_drop = store.get_export_func::<(), ()>(inst, "data_drop").unwrap();
+
+    // the active segment.
+    assert_eq!(store.call(load8, 0).unwrap(), b'a' as i32);
+
+    store.call(memory_init, (100, 1, 4)).unwrap();
+    let bytes: Vec<u8> = (100..104).map(|i| store.call(load8, i).unwrap() as u8).collect();
+    assert_eq!(bytes, b"ello");
+
+    assert!(matches!(store.call(memory_init, (100, 3, 3)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+    assert!(matches!(store.call(memory_init, (65535, 0, 2)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+
+    // dropped segments are empty.
+    store.call(data_drop, ()).unwrap();
+    store.call(data_drop, ()).unwrap();
+    store.call(memory_init, (200, 0, 0)).unwrap();
+    assert!(matches!(store.call(memory_init, (200, 0, 1)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+}
+
+#[test]
+fn bulk_table() {
+    let mut store = Store::new();
+    let inst = store.new_instance(include_bytes!("bulk.wasm"), &[]).unwrap();
+    let table_init = store.get_export_func::<(i32, i32, i32), ()>(inst, "table_init").unwrap();
+    let elem_drop = store.get_export_func::<(), ()>(inst, "elem_drop").unwrap();
+    let call = store.get_export_func::<(i32, i32), i32>(inst, "call").unwrap();
+    let is_null = store.get_export_func::<i32, i32>(inst, "is_null").unwrap();
+    let mov = store.get_export_func::<(i32, i32), ()>(inst, "move").unwrap();
+    let grow = store.get_export_func::<i32, i32>(inst, "grow").unwrap();
+    let size = store.get_export_func::<(), i32>(inst, "size").unwrap();
+    let fill = store.get_export_func::<(i32, i32), ()>(inst, "fill").unwrap();
+    let copy = store.get_export_func::<(i32, i32, i32), ()>(inst, "copy").unwrap();
+
+    // the active segment.
+    assert_eq!(store.call(call, (0, 10)).unwrap(), 11);
+    assert_eq!(store.call(is_null, 1).unwrap(), 1);
+    assert!(matches!(store.call(is_null, 4).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapTableBounds)));
+
+    store.call(table_init, (1, 1, 2)).unwrap();
+    assert_eq!(store.call(call, (1, 10)).unwrap(), 12);
+    assert_eq!(store.call(call, (2, 10)).unwrap(), 13);
+    assert!(matches!(store.call(table_init, (3, 0, 2)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapTableBounds)));
+
+    // overlapping copy.
+    store.call(copy, (1, 0, 3)).unwrap();
+    assert_eq!(store.call(call, (1, 10)).unwrap(), 11);
+    assert_eq!(store.call(call, (2, 10)).unwrap(), 12);
+    assert_eq!(store.call(call, (3, 10)).unwrap(), 13);
+    assert!(matches!(store.call(copy, (2, 0, 3)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapTableBounds)));
+
+    assert_eq!(store.call(size, ()).unwrap(), 0);
+    store.call(mov, (3, 0)).unwrap();
+    assert_eq!(store.call(size, ()).unwrap(), 1);
+    assert_eq!(store.call(call, (0, 10)).unwrap(), 13);
+    assert!(matches!(store.call(call, (3, 10)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapCallIndirectRefNull)));
+
+    // the store's limit applies to `table.grow`.
+    store.set_max_table_elements(4);
+    assert_eq!(store.call(grow, 4).unwrap(), -1);
+    assert_eq!(store.call(grow, 3).unwrap(), 1);
+    store.set_max_table_elements(1 << 20);
+
+    // the table's max is 8.
+    assert_eq!(store.call(grow, 4).unwrap(), 4);
+    assert_eq!(store.call(grow, 1).unwrap(), -1);
+    assert_eq!(store.call(size, ()).unwrap(), 8);
+
+    store.call(fill, (0, 2)).unwrap();
+    assert_eq!(store.call(is_null, 0).unwrap(), 1);
+    assert_eq!(store.call(is_null, 1).unwrap(), 1);
+    assert_eq!(store.call(is_null, 2).unwrap(), 0);
+    assert!(matches!(store.call(fill, (3, 2)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapTableBounds)));
+    assert!(matches!(store.call(fill, (-1, 1)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapTableBounds)));
+
+    // dropped segments are empty.
+    store.call(elem_drop, ()).unwrap();
+    store.call(table_init, (4, 0, 0)).unwrap();
+    assert!(matches!(store.call(table_init, (0, 0, 1)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapTableBounds)));
+}
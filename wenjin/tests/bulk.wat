@@ -0,0 +1,56 @@
+(module
+    (type $i32_i32 (func (param i32) (result i32)))
+
+    (memory 1)
+    (data $hello "hello")
+    (data (i32.const 0) "abc")
+
+    (table $funcs 4 funcref)
+    (table $refs 0 8 funcref)
+    (elem $passive func $one $two $three)
+    (elem (i32.const 0) $one)
+
+    (func $one (param i32) (result i32) (i32.add (local.get 0) (i32.const 1)))
+    (func $two (param i32) (result i32) (i32.add (local.get 0) (i32.const 2)))
+    (func $three (param i32) (result i32) (i32.add (local.get 0) (i32.const 3)))
+
+    (func (export "load8") (param i32) (result i32)
+        (i32.load8_u (local.get 0)))
+
+    (func (export "memory_init") (param i32 i32 i32)
+        (memory.init $hello (local.get 0) (local.get 1) (local.get 2)))
+
+    (func (export "data_drop")
+        (data.drop $hello))
+
+    (func (export "table_init") (param i32 i32 i32)
+        (table.init $funcs $passive (local.get 0) (local.get 1) (local.get 2)))
+
+    (func (export "elem_drop")
+        (elem.drop $passive))
+
+    (func (export "call") (param i32 i32) (result i32)
+        (call_indirect $funcs (type $i32_i32) (local.get 1) (local.get 0)))
+
+    (func (export "is_null") (param i32) (result i32)
+        (ref.is_null (table.get $funcs (local.get 0))))
+
+    ;; copies a slot of $funcs through $refs.
+    (func (export "move") (param i32 i32)
+        (drop (table.grow $refs (ref.null func) (i32.const 1)))
+        (table.copy $refs $funcs (i32.const 0) (local.get 0) (i32.const 1))
+        (table.set $funcs (local.get 1) (table.get $refs (i32.const 0)))
+        (table.set $funcs (local.get 0) (ref.null func)))
+
+    (func (export "grow") (param i32) (result i32)
+        (table.grow $refs (ref.null func) (local.get 0)))
+
+    (func (export "size") (result i32)
+        (table.size $refs))
+
+    (func (export "fill") (param i32 i32)
+        (table.fill $funcs (local.get 0) (ref.null func) (local.get 1)))
+
+    (func (export "copy") (param i32 i32 i32)
+        (table.copy $funcs $funcs (local.get 0) (local.get 1) (local.get 2)))
+)
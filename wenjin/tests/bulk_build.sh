@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o bulk.wasm \
+    bulk.wat
@@ -0,0 +1,141 @@
+use wenjin::{Store, Error, BacktraceFrame, Value, wasm};
+
+
+#[test]
+fn coredump() {
+    let wasm = include_bytes!("coredump.wasm");
+
+    let mut store = Store::new();
+    let inst = store.new_instance(wasm, &[]).unwrap();
+    let run = store.get_export_func::<i32, i32>(inst, "run").unwrap();
+    let ok = store.get_export_func::<(), i32>(inst, "ok").unwrap();
+
+    // disabled.
+    assert!(store.call(run, 5).is_err());
+    assert!(store.take_coredump().is_none());
+
+    store.enable_coredumps("app.wasm");
+    assert_eq!(store.call(ok, ()).unwrap(), 1);
+    assert!(store.take_coredump().is_none());
+
+    let Error::Trap(trap) = store.call(run, 5).unwrap_err() else { panic!() };
+    assert!(matches!(trap.error, Error::TrapDivZero));
+    let dump = store.take_coredump().unwrap();
+    assert!(store.take_coredump().is_none());
+
+
+    let mut customs = Vec::new();
+    let mut sections = Vec::new();
+    let mut p = wasm::Parser::new(&dump);
+    p.parse_module_header().unwrap();
+    while !p.is_done() {
+        let section = p.parse_section().unwrap();
+        if section.kind == wasm::SectionKind::Custom {
+            let custom = p.sub_parser(section.sub).parse_custom_section().unwrap();
+            customs.push(custom.name);
+            // the contents, after the name.
+            let offset = section.sub.offset + section.sub.len - custom.data.len();
+            sections.push((section.kind, wasm::Parser::from_sub_section(&dump, wasm::SubSection { offset, len: custom.data.len() })));
+        }
+        else {
+            sections.push((section.kind, p.sub_parser(section.sub)));
+        }
+    }
+    assert_eq!(customs, ["core", "coremodules", "coreinstances", "corestack"]);
+    let kinds: Vec<_> = sections.iter().map(|(kind, _)| *kind).collect();
+    assert_eq!(kinds, [
+        wasm::SectionKind::Custom, wasm::SectionKind::Custom, wasm::SectionKind::Custom,
+        wasm::SectionKind::Memory, wasm::SectionKind::Global, wasm::SectionKind::Data,
+        wasm::SectionKind::Custom]);
+
+    // process info.
+    let p = &mut sections[0].1;
+    assert_eq!(p.next().unwrap(), 0);
+    assert_eq!(p.parse_string().unwrap(), "app.wasm");
+    assert!(p.is_done());
+
+    // the module name is from the `name` section.
+    let p = &mut sections[1].1;
+    assert_eq!(p.parse_u32().unwrap(), 1);
+    assert_eq!(p.next().unwrap(), 0);
+    assert_eq!(p.parse_string().unwrap(), "app");
+    assert!(p.is_done());
+
+    // instance 0 of module 0, with memory 0 & globals 0 & 1.
+    let p = &mut sections[2].1;
+    assert_eq!(p.parse_u32().unwrap(), 1);
+    assert_eq!(p.next().unwrap(), 0);
+    assert_eq!(p.parse_u32().unwrap(), 0);
+    assert_eq!(p.parse_u32().unwrap(), 1);
+    assert_eq!(p.parse_u32().unwrap(), 0);
+    assert_eq!(p.parse_u32().unwrap(), 2);
+    assert_eq!(p.parse_u32().unwrap(), 0);
+    assert_eq!(p.parse_u32().unwrap(), 1);
+    assert!(p.is_done());
+
+    // the stack, innermost frame first.
+    let p = &mut sections[6].1;
+    assert_eq!(p.next().unwrap(), 0);
+    assert_eq!(p.parse_string().unwrap(), "main");
+    assert_eq!(p.parse_u32().unwrap(), 2);
+
+    let frame = |p: &mut wasm::Parser| {
+        assert_eq!(p.next().unwrap(), 0);
+        let instance = p.parse_u32().unwrap();
+        let func = p.parse_u32().unwrap();
+        let offset = p.parse_u32().unwrap();
+        let mut values = || {
+            (0..p.parse_u32().unwrap()).map(|_| match p.next().unwrap() {
+                0x7f => Value::I32(p.parse_i32().unwrap()),
+                0x7e => Value::I64(p.parse_i64().unwrap()),
+                0x7d => Value::F32(p.parse_f32().unwrap()),
+                0x7c => Value::F64(p.parse_f64().unwrap()),
+                ty => panic!("{ty:#x}"),
+            }).collect::<Vec<_>>()
+        };
+        let locals = values();
+        let operands = values();
+        (instance, func, offset, locals, operands)
+    };
+
+    // the offsets are relative to the function bodies,
+    // which start with the local declarations.
+    let body_start = |frame: usize, offset: u32| {
+        let BacktraceFrame::Wasm { offset: abs, .. } = trap.backtrace[frame] else { panic!() };
+        abs as usize - offset as usize
+    };
+
+    // `divide`, at the `i32.div_u`, which popped its operands.
+    let (instance, func, offset, locals, operands) = frame(p);
+    assert_eq!((instance, func), (0, 0));
+    assert_eq!(wasm[body_start(0, offset) + offset as usize], 0x6e);
+    assert_eq!(wasm[body_start(0, offset)..][..3], [0x01, 0x01, 0x7e]);
+    assert_eq!(locals, [Value::I32(5), Value::I32(0), Value::I64(-3)]);
+    assert_eq!(operands, [Value::I32(1000)]);
+
+    // `run`, at the call, which is 2 bytes before the backtrace's offset.
+    let (instance, func, offset, locals, operands) = frame(p);
+    assert_eq!((instance, func), (0, 1));
+    let body = body_start(1, offset) - 2;
+    assert_eq!(wasm[body + offset as usize..][..2], [0x10, 0x00]);
+    assert_eq!(wasm[body], 0x00);
+    assert_eq!(locals, [Value::I32(5)]);
+    assert_eq!(operands, [Value::I32(7)]);
+    assert!(p.is_done());
+
+
+    // the dump is a valid module, with the memory & globals at the time of the trap.
+    let mut dump_store = Store::new();
+    let dump_inst = dump_store.new_instance(&dump, &[]).unwrap();
+    let counter = dump_store.instance_global(dump_inst, 0).unwrap();
+    let scale = dump_store.instance_global(dump_inst, 1).unwrap();
+    assert_eq!(dump_store.global(counter).get(), Value::I32(42));
+    assert!(dump_store.global(counter).mutable());
+    assert_eq!(dump_store.global(scale).get(), Value::F64(1.5));
+
+    let p = &mut sections[5].1;
+    assert_eq!(p.parse_u32().unwrap(), 1);
+    let wasm::Data { kind: wasm::DataKind::Active { mem: 0, offset: wasm::ConstExpr::I32(0) }, values } = p.parse_data().unwrap() else { panic!() };
+    assert_eq!(values.len(), wasm::PAGE_SIZE);
+    assert_eq!(&values[16..21], b"hello");
+}
@@ -0,0 +1,21 @@
+(module $app
+  (memory 1 2)
+  (data (i32.const 16) "hello")
+
+  (global $counter (mut i32) (i32.const 0))
+  (global $scale f64 (f64.const 1.5))
+
+  (func $divide (param $a i32) (param $b i32) (result i32)
+    (local $tmp i64)
+    (local.set $tmp (i64.const -3))
+    (global.set $counter (i32.const 42))
+    (i32.add (i32.const 1000)
+      (i32.div_u (local.get $a) (local.get $b))))
+
+  (func (export "run") (param $x i32) (result i32)
+    (i32.add (i32.const 7)
+      (call $divide (local.get $x) (i32.const 0))))
+
+  (func (export "ok") (result i32)
+    (i32.const 1))
+)
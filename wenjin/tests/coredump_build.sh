@@ -0,0 +1,5 @@
+
+wat2wasm \
+    --debug-names \
+    -o coredump.wasm \
+    coredump.wat
@@ -0,0 +1,53 @@
+; the debug info of this c code:
+;
+;   // coverage.c
+;   int clamp(int x) {
+;       if (x < 0) {
+;           return 0;
+;       }
+;       return x;
+;   }
+;
+;   int twice(int x) {
+;       return x * 2;
+;   }
+
+source_filename = "coverage.c"
+target datalayout = "e-m:e-p:32:32-i64:64-n32:64-S128"
+target triple = "wasm32-unknown-unknown"
+
+define i32 @clamp(i32 %x) #0 !dbg !10 {
+entry:
+  %c = icmp slt i32 %x, 0, !dbg !14
+  br i1 %c, label %neg, label %pos, !dbg !14
+neg:
+  ret i32 0, !dbg !15
+pos:
+  ret i32 %x, !dbg !16
+}
+
+define i32 @twice(i32 %x) #1 !dbg !17 {
+  %r = mul i32 %x, 2, !dbg !18
+  ret i32 %r, !dbg !19
+}
+
+attributes #0 = { noinline nounwind "wasm-export-name"="clamp" }
+attributes #1 = { noinline nounwind "wasm-export-name"="twice" }
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "clang", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)
+!1 = !DIFile(filename: "coverage.c", directory: "/src")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!10 = distinct !DISubprogram(name: "clamp", scope: !1, file: !1, line: 1, type: !11, scopeLine: 1, spFlags: DISPFlagDefinition, unit: !0)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !DILocation(line: 2, column: 11, scope: !10)
+!15 = !DILocation(line: 3, column: 9, scope: !10)
+!16 = !DILocation(line: 5, column: 5, scope: !10)
+!17 = distinct !DISubprogram(name: "twice", scope: !1, file: !1, line: 8, type: !11, scopeLine: 8, spFlags: DISPFlagDefinition, unit: !0)
+!18 = !DILocation(line: 9, column: 14, scope: !17)
+!19 = !DILocation(line: 9, column: 5, scope: !17)
@@ -0,0 +1,79 @@
+use wenjin::Store;
+
+
+#[test]
+fn coverage() {
+    let mut store = Store::new();
+
+    let tick = store.new_host_func(|| {});
+    let inst = store.new_instance(include_bytes!("profile.wasm"), &[("host", "tick", tick.into())]).unwrap();
+    let run = store.get_export_func::<i32, i32>(inst, "run").unwrap();
+
+    assert!(store.stop_coverage().is_none());
+
+    store.start_coverage();
+    assert_eq!(store.call(run, 5).unwrap(), 5);
+    let coverage = store.stop_coverage().unwrap();
+
+    // not recorded.
+    assert_eq!(store.call(run, 5).unwrap(), 5);
+
+    // `$fib`, `run` & `trap`.
+    let funcs = coverage.funcs();
+    assert_eq!(funcs.iter().map(|f| (f.func_idx, f.calls())).collect::<Vec<_>>(), [(1, 15), (2, 1), (3, 0)]);
+    assert!(funcs.iter().all(|f| f.instance == inst));
+
+    // fib(5) has 8 leaves.
+    // the blocks are the entry, the `then` & `else` bodies & the code after the `if`.
+    let fib = &funcs[0];
+    assert_eq!(fib.blocks.iter().map(|(_, hits)| *hits).collect::<Vec<_>>(), [15, 8, 7, 15]);
+    assert_eq!(fib.instructions.first(), fib.blocks.first());
+    assert!(fib.blocks.iter().all(|block| fib.instructions.contains(block)));
+    assert!(fib.instructions.windows(2).all(|w| w[0].0 < w[1].0));
+
+    let trap = &funcs[2];
+    assert_eq!(trap.instructions.len(), 2);
+    assert_eq!(trap.blocks.len(), 1);
+    assert!(trap.instructions.iter().all(|(_, hits)| *hits == 0));
+
+    #[cfg(feature = "dwarf")]
+    assert!(coverage.lcov(&store).is_none());
+}
+
+#[cfg(feature = "dwarf")]
+#[test]
+fn lcov() {
+    use wenjin::{Value, wasm};
+
+    let mut store = Store::new();
+    let memory = store.new_memory(wasm::Limits { min: 0, max: None }).unwrap();
+    let stack_pointer = store.new_global(true, Value::I32(0));
+    let inst = store.new_instance(include_bytes!("coverage.wasm"), &[
+        ("env", "__linear_memory", memory.into()),
+        ("env", "__stack_pointer", stack_pointer.into()),
+    ]).unwrap();
+    let clamp = store.get_export_func::<i32, i32>(inst, "clamp").unwrap();
+
+    store.start_coverage();
+    assert_eq!(store.call(clamp, 5).unwrap(), 5);
+    assert_eq!(store.call(clamp, 7).unwrap(), 7);
+    let coverage = store.stop_coverage().unwrap();
+
+    // functions start at their first instruction's line.
+    assert_eq!(coverage.lcov(&store).unwrap(), concat!(
+        "TN:\n",
+        "SF:/src/coverage.c\n",
+        "FN:2,clamp\n",
+        "FN:9,twice\n",
+        "FNDA:2,clamp\n",
+        "FNDA:0,twice\n",
+        "FNF:2\n",
+        "FNH:1\n",
+        "DA:2,2\n",
+        "DA:3,0\n",
+        "DA:5,2\n",
+        "DA:9,0\n",
+        "LF:4\n",
+        "LH:2\n",
+        "end_of_record\n"));
+}
@@ -0,0 +1,5 @@
+# a relocatable object, but llc resolves the code offsets in the debug info.
+# -O0 keeps the branches.
+llc -mtriple=wasm32 -filetype=obj -O0 \
+    -o coverage.wasm \
+    coverage.ll
@@ -0,0 +1,182 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use wenjin::{Store, Error, Value, StackValue, DebugEvent, DebugAction, DebugFrame, wasm};
+
+
+fn find(wasm: &[u8], pattern: &[u8]) -> u32 {
+    let mut matches = wasm.windows(pattern.len()).enumerate().filter(|(_, w)| *w == pattern);
+    let (offset, _) = matches.next().unwrap();
+    assert!(matches.next().is_none());
+    offset as u32
+}
+
+fn i32s(values: Vec<(StackValue, wasm::ValueType)>) -> Vec<i32> {
+    values.iter().map(|(v, ty)| {
+        assert_eq!(*ty, wasm::ValueType::I32);
+        v.as_i32()
+    }).collect()
+}
+
+
+#[test]
+fn debugger() {
+    let wasm = include_bytes!("debugger.wasm");
+
+    let mut store = Store::new();
+    let callback = store.new_host_func(|| {});
+    let inst = store.new_instance(wasm, &[("host", "callback", callback.into())]).unwrap();
+    let run = store.get_export_func::<i32, i32>(inst, "run").unwrap();
+    let via_host = store.get_export_func::<i32, i32>(inst, "via_host").unwrap();
+    let add = store.instance_func(inst, 1).unwrap();
+    let calls = store.instance_global(inst, 0).unwrap();
+
+    // `local.get $a; local.get $b; i32.add` in `$add`,
+    // `call $add; i32.const 2` in `run`.
+    let add_begin = find(wasm, &[0x20, 0, 0x20, 1, 0x6a]);
+    let add_add = add_begin + 4;
+    let run_ret = find(wasm, &[0x10, 1, 0x41, 2]) + 2;
+
+    // not stopped.
+    assert!(store.debug_frames().is_empty());
+    assert!(matches!(store.debug_locals(0), Err(Error::NotStopped)));
+    assert!(matches!(store.debug_set_operand(0, 0, Value::I32(0)), Err(Error::NotStopped)));
+
+    // breakpoints must be at instruction boundaries of wasm functions.
+    assert!(matches!(store.set_breakpoint(add, add_begin + 1), Err(Error::InvalidBreakpoint)));
+    assert!(matches!(store.set_breakpoint(add, run_ret), Err(Error::InvalidBreakpoint)));
+    assert!(matches!(store.set_breakpoint(callback.func_id(), 0), Err(Error::InvalidBreakpoint)));
+    store.set_breakpoint(add, add_add).unwrap();
+    assert!(store.clear_breakpoint(add, add_add));
+    assert!(!store.clear_breakpoint(add, add_add));
+
+    // no handler, no stops.
+    store.set_breakpoint(add, add_add).unwrap();
+    assert_eq!(store.call(run, 5).unwrap(), 112);
+
+
+    // inspect & modify at a breakpoint, then step.
+    let stops = Rc::new(RefCell::new(Vec::new()));
+    store.set_debug_handler({
+        let stops = stops.clone();
+        move |store: &mut Store, event| {
+            let frames = store.debug_frames();
+            let mut stops = stops.borrow_mut();
+            stops.push((event, frames.clone()));
+            match stops.len() {
+                1 => {
+                    assert_eq!(event, DebugEvent::Breakpoint);
+                    assert_eq!(frames.len(), 2);
+                    assert_eq!(frames[0], DebugFrame { instance: inst, func: add, func_idx: 1, offset: add_add });
+                    assert_eq!(frames[1], DebugFrame { instance: inst, func: run.func_id(), func_idx: 2, offset: run_ret });
+
+                    assert_eq!(i32s(store.debug_locals(0).unwrap()), [5, 1, 0]);
+                    assert_eq!(i32s(store.debug_operands(0).unwrap()), [5, 1]);
+                    assert_eq!(i32s(store.debug_locals(1).unwrap()), [5]);
+                    assert_eq!(i32s(store.debug_operands(1).unwrap()), [100]);
+                    assert!(matches!(store.debug_locals(2), Err(Error::InvalidIndex)));
+
+                    store.debug_set_operand(0, 1, Value::I32(10)).unwrap();
+                    assert!(matches!(store.debug_set_operand(0, 2, Value::I32(0)), Err(Error::InvalidIndex)));
+                    assert!(matches!(store.debug_set_operand(0, 0, Value::I64(0)), Err(Error::TypeMismatch)));
+                    Ok(DebugAction::Step)
+                }
+                2 => {
+                    assert_eq!(event, DebugEvent::Step);
+                    assert_eq!(frames[0].offset, add_add + 1);
+                    assert_eq!(i32s(store.debug_operands(0).unwrap()), [15]);
+                    Ok(DebugAction::StepOut)
+                }
+                3 => {
+                    assert_eq!(event, DebugEvent::Step);
+                    assert_eq!(frames, [DebugFrame { instance: inst, func: run.func_id(), func_idx: 2, offset: run_ret }]);
+                    assert_eq!(i32s(store.debug_operands(0).unwrap()), [100, 15]);
+                    Ok(DebugAction::Continue)
+                }
+                _ => unreachable!(),
+            }
+        }
+    });
+    assert_eq!(store.call(run, 5).unwrap(), 130);
+    assert_eq!(stops.borrow().len(), 3);
+    assert!(store.debug_frames().is_empty());
+
+
+    // locals & globals.
+    assert_eq!(store.global(calls).get(), Value::I32(2));
+    store.clear_breakpoints();
+    store.set_breakpoint(add, add_begin).unwrap();
+    store.set_debug_handler(move |store: &mut Store, _| {
+        store.debug_set_local(0, 1, Value::I32(7)).unwrap();
+        assert!(matches!(store.debug_set_local(0, 3, Value::I32(0)), Err(Error::InvalidIndex)));
+        store.set_global(calls, Value::I32(40)).unwrap();
+        assert!(matches!(store.set_global(calls, Value::F32(0.0)), Err(Error::TypeMismatch)));
+        Ok(DebugAction::Continue)
+    });
+    assert_eq!(store.call(run, 5).unwrap(), 124);
+    assert_eq!(store.global(calls).get(), Value::I32(41));
+
+
+    // stepping. `run` has 8 instructions, `$add` 10.
+    let trace = |store: &mut Store, action: DebugAction, f: &dyn Fn(&mut Store)| {
+        let stops = Rc::new(RefCell::new(Vec::new()));
+        store.set_debug_handler({
+            let stops = stops.clone();
+            move |store: &mut Store, event| {
+                assert_eq!(event, DebugEvent::Step);
+                stops.borrow_mut().push(store.debug_frames()[0].func_idx);
+                Ok(action)
+            }
+        });
+        f(store);
+        let result = stops.borrow().clone();
+        result
+    };
+    store.clear_breakpoints();
+
+    let stops = trace(&mut store, DebugAction::Step, &|store| {
+        store.break_next();
+        assert_eq!(store.call(run, 5).unwrap(), 112);
+    });
+    assert_eq!(stops, [[2; 4].as_slice(), &[1; 10], &[2; 4]].concat());
+
+    let stops = trace(&mut store, DebugAction::StepOver, &|store| {
+        store.break_next();
+        assert_eq!(store.call(run, 5).unwrap(), 112);
+    });
+    assert_eq!(stops, [2; 8]);
+
+    // stepping ends with the call.
+    let stops = trace(&mut store, DebugAction::StepOver, &|store| {
+        assert_eq!(store.call(run, 5).unwrap(), 112);
+    });
+    assert!(stops.is_empty());
+
+    // stepping out of the root frame.
+    let stops = trace(&mut store, DebugAction::StepOut, &|store| {
+        store.break_next();
+        assert_eq!(store.call(run, 5).unwrap(), 112);
+    });
+    assert_eq!(stops, [2]);
+
+
+    // frames end at host functions, the handler's calls don't stop.
+    store.set_breakpoint(add, add_add).unwrap();
+    store.set_debug_handler(move |store: &mut Store, _| {
+        let frames = store.debug_frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].func_idx, 3);
+        assert_eq!(store.call(run, 1).unwrap(), 104);
+        assert_eq!(store.debug_frames(), frames);
+        Ok(DebugAction::Continue)
+    });
+    assert_eq!(store.call(via_host, 3).unwrap(), 6);
+
+
+    // handler errors abort the call.
+    store.set_debug_handler(|_: &mut Store, _| Err(Error::TrapUnreachable));
+    assert!(matches!(store.call(run, 5).unwrap_err().inner(), Error::TrapUnreachable));
+    assert!(store.debug_frames().is_empty());
+    store.clear_debug_handler();
+    assert_eq!(store.call(run, 5).unwrap(), 112);
+}
@@ -0,0 +1,22 @@
+(module
+    (import "host" "callback" (func $callback))
+
+    (global $calls (mut i32) (i32.const 0))
+
+    (func $add (param $a i32) (param $b i32) (result i32)
+        (local $sum i32)
+        (local.set $sum (i32.add (local.get $a) (local.get $b)))
+        (global.set $calls (i32.add (global.get $calls) (i32.const 1)))
+        (local.get $sum))
+
+    (func (export "run") (param $x i32) (result i32)
+        (i32.add
+            (i32.const 100)
+            (i32.mul
+                (call $add (local.get $x) (i32.const 1))
+                (i32.const 2))))
+
+    (func (export "via_host") (param $x i32) (result i32)
+        (call $callback)
+        (call $add (local.get $x) (local.get $x)))
+)
@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o debugger.wasm \
+    debugger.wat
@@ -0,0 +1,50 @@
+; the debug info of this c code:
+;
+;   // include/math.h
+;   static int divide(int a, int b) {
+;       return a / b;
+;   }
+;
+;   // dwarf.c
+;   #include "include/math.h"
+;
+;   int run(int a, int b) {
+;       return divide(a, b) + 1;
+;   }
+
+source_filename = "dwarf.c"
+target datalayout = "e-m:e-p:32:32-i64:64-n32:64-S128"
+target triple = "wasm32-unknown-unknown"
+
+define internal i32 @divide(i32 %a, i32 %b) #0 !dbg !10 {
+  %q = sdiv i32 %a, %b, !dbg !14
+  ret i32 %q, !dbg !15
+}
+
+define i32 @run(i32 %a, i32 %b) #1 !dbg !16 {
+  %r = call i32 @divide(i32 %a, i32 %b), !dbg !17
+  %s = add i32 %r, 1, !dbg !18
+  ret i32 %s, !dbg !19
+}
+
+attributes #0 = { noinline nounwind }
+attributes #1 = { noinline nounwind "wasm-export-name"="run" }
+
+!llvm.dbg.cu = !{!0}
+!llvm.module.flags = !{!2, !3}
+
+!0 = distinct !DICompileUnit(language: DW_LANG_C99, file: !1, producer: "clang", isOptimized: false, runtimeVersion: 0, emissionKind: FullDebug)
+!1 = !DIFile(filename: "dwarf.c", directory: "/src")
+!2 = !{i32 7, !"Dwarf Version", i32 4}
+!3 = !{i32 2, !"Debug Info Version", i32 3}
+!9 = !DIFile(filename: "math.h", directory: "/src/include")
+!10 = distinct !DISubprogram(name: "divide", scope: !9, file: !9, line: 2, type: !11, scopeLine: 2, spFlags: DISPFlagDefinition | DISPFlagLocalToUnit, unit: !0)
+!11 = !DISubroutineType(types: !12)
+!12 = !{!13, !13, !13}
+!13 = !DIBasicType(name: "int", size: 32, encoding: DW_ATE_signed)
+!14 = !DILocation(line: 3, column: 14, scope: !10)
+!15 = !DILocation(line: 3, column: 5, scope: !10)
+!16 = distinct !DISubprogram(name: "run", scope: !1, file: !1, line: 4, type: !11, scopeLine: 4, spFlags: DISPFlagDefinition, unit: !0)
+!17 = !DILocation(line: 5, column: 12, scope: !16)
+!18 = !DILocation(line: 5, column: 25, scope: !16)
+!19 = !DILocation(line: 5, column: 5, scope: !16)
@@ -0,0 +1,63 @@
+#![cfg(feature = "dwarf")]
+
+use wenjin::{Store, Module, Error, BacktraceFrame, Value, wasm};
+
+
+fn check(wasm: &[u8]) {
+    let module = Module::new(wasm).unwrap();
+
+    let debug_info = module.debug_info().unwrap();
+    let mut files = debug_info.files().collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(files, ["/src/dwarf.c", "/src/include/math.h"]);
+
+    let mut store = Store::new();
+    let memory = store.new_memory(wasm::Limits { min: 0, max: None }).unwrap();
+    let stack_pointer = store.new_global(true, Value::I32(0));
+    let inst = store.instantiate(&module, &[
+        ("env", "__linear_memory", memory.into()),
+        ("env", "__stack_pointer", stack_pointer.into()),
+    ]).unwrap();
+    let run = store.get_export_func::<(i32, i32), i32>(inst, "run").unwrap();
+    assert_eq!(store.call(run, (10, 2)).unwrap(), 6);
+
+    let Error::Trap(t) = store.call(run, (1, 0)).unwrap_err() else { panic!() };
+    assert!(matches!(t.error, Error::TrapDivZero));
+    assert_eq!(t.backtrace.len(), 2);
+
+    // the trapping instruction.
+    let BacktraceFrame::Wasm { instance, offset, .. } = t.backtrace[0] else { panic!() };
+    let location = store.source_location(instance, offset).unwrap();
+    assert_eq!(location.file, Some("/src/include/math.h"));
+    assert_eq!((location.line, location.column), (3, 14));
+    assert_eq!(location.func, Some("divide"));
+
+    // the call, callers point just after it.
+    let BacktraceFrame::Wasm { instance, offset, .. } = t.backtrace[1] else { panic!() };
+    let location = store.source_location(instance, offset - 1).unwrap();
+    assert_eq!(location.file, Some("/src/dwarf.c"));
+    assert_eq!((location.line, location.column), (5, 12));
+    assert_eq!(location.func, Some("run"));
+
+    let backtrace = store.format_backtrace(&t.backtrace);
+    let lines = backtrace.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("   0: divide @ 0x"), "{backtrace}");
+    assert!(lines[0].ends_with(" at /src/include/math.h:3:14"), "{backtrace}");
+    assert!(lines[1].starts_with("   1: run @ 0x"), "{backtrace}");
+    assert!(lines[1].ends_with(" at /src/dwarf.c:5:12"), "{backtrace}");
+
+    // offsets outside of functions.
+    assert!(debug_info.location(0).is_none());
+    assert!(debug_info.location(wasm.len()).is_none());
+}
+
+#[test]
+fn dwarf() {
+    check(include_bytes!("dwarf4.wasm"));
+    check(include_bytes!("dwarf5.wasm"));
+
+    // no debug info.
+    let module = Module::new(include_bytes!("fuel.wasm")).unwrap();
+    assert!(module.debug_info().is_none());
+}
@@ -0,0 +1,8 @@
+# relocatable objects, but llc resolves the code offsets in the debug info.
+llc -mtriple=wasm32 -filetype=obj \
+    -o dwarf4.wasm \
+    dwarf.ll
+
+sed 's/"Dwarf Version", i32 4/"Dwarf Version", i32 5/' dwarf.ll | \
+llc -mtriple=wasm32 -filetype=obj \
+    -o dwarf5.wasm
@@ -0,0 +1,44 @@
+use wenjin::{Store, Error, Value, RefValue};
+
+
+#[test]
+fn elements() {
+    let mut store = Store::new();
+    let base = store.new_global(false, Value::I32(5));
+    let f = store.new_global(false, Value::FuncRef(RefValue::NULL));
+
+    let inst = store.new_instance(include_bytes!("elements.wasm"),
+        &[("env", "base", base.into()), ("env", "f", f.into())]).unwrap();
+    let call = store.get_export_func::<i32, i32>(inst, "call").unwrap();
+    let is_null = store.get_export_func::<i32, i32>(inst, "is_null").unwrap();
+    let init_p0 = store.get_export_func::<i32, ()>(inst, "init_p0").unwrap();
+    let init_p1 = store.get_export_func::<i32, ()>(inst, "init_p1").unwrap();
+    let set_g = store.get_export_func::<i32, ()>(inst, "set_g").unwrap();
+
+    let table = |store: &mut Store| -> Vec<i32> {
+        (0..8).map(|i| {
+            if store.call(is_null, i).unwrap() != 0 { 0 }
+            else { store.call(call, i).unwrap() }
+        }).collect()
+    };
+
+    // the active segments, the last one at `base`.
+    assert_eq!(table(&mut store), [1, 2, 3, 0, 0, 1, 0, 2]);
+    assert!(matches!(store.call(call, 6).unwrap_err().inner(), Error::TrapCallIndirectRefNull));
+
+    // passive segments, of function indices & of expressions.
+    store.call(init_p0, 3).unwrap();
+    store.call(init_p1, 0).unwrap();
+    assert_eq!(table(&mut store), [0, 1, 3, 2, 3, 1, 0, 2]);
+
+    // global initializers can refer to functions.
+    store.call(set_g, 6).unwrap();
+    assert_eq!(table(&mut store), [0, 1, 3, 2, 3, 1, 3, 2]);
+
+
+    // out of bounds segments trap.
+    let base = store.new_global(false, Value::I32(6));
+    let result = store.new_instance(include_bytes!("elements.wasm"),
+        &[("env", "base", base.into()), ("env", "f", f.into())]);
+    assert!(matches!(result.unwrap_err().inner(), Error::TrapTableBounds));
+}
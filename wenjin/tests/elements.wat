@@ -0,0 +1,38 @@
+(module
+    (type $i32 (func (result i32)))
+
+    (import "env" "base" (global $base i32))
+    (import "env" "f" (global $f funcref))
+
+    (table $t 8 funcref)
+    (global $g funcref (ref.func $c))
+
+    ;; the eight encodings.
+    (elem (i32.const 0) $a)
+    (elem $p0 func $b $c)
+    (elem (table $t) (i32.const 1) func $b)
+    (elem declare func $c)
+    (elem (i32.const 2) funcref (ref.func $c) (ref.null func))
+    (elem $p1 funcref (ref.null func) (ref.func $a))
+    (elem (table $t) (global.get $base) funcref (ref.func $a) (global.get $f) (ref.func $b))
+    (elem declare funcref (ref.func $a))
+
+    (func $a (result i32) (i32.const 1))
+    (func $b (result i32) (i32.const 2))
+    (func $c (result i32) (i32.const 3))
+
+    (func (export "call") (param i32) (result i32)
+        (call_indirect $t (type $i32) (local.get 0)))
+
+    (func (export "is_null") (param i32) (result i32)
+        (ref.is_null (table.get $t (local.get 0))))
+
+    (func (export "init_p0") (param i32)
+        (table.init $t $p0 (local.get 0) (i32.const 0) (i32.const 2)))
+
+    (func (export "init_p1") (param i32)
+        (table.init $t $p1 (local.get 0) (i32.const 0) (i32.const 2)))
+
+    (func (export "set_g") (param i32)
+        (table.set $t (local.get 0) (global.get $g)))
+)
@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o elements.wasm \
+    elements.wat
@@ -0,0 +1,109 @@
+use wenjin::{Store, Error, Value, TypedFuncId};
+
+
+#[test]
+fn fuel() {
+    let mut store = Store::new();
+
+    let sum_var = std::rc::Rc::new(std::cell::Cell::new(None::<TypedFuncId<i32, i32>>));
+    let host_sum = store.new_host_func({ let sum_var = sum_var.clone(); move |store: &mut Store, n: i32| {
+        store.call(sum_var.get().unwrap(), n)
+    }});
+
+    let inst = store.new_instance(include_bytes!("fuel.wasm"),
+        &[("host", "sum", host_sum.into())]).unwrap();
+    let sum = store.get_export_func::<i32, i32>(inst, "sum").unwrap();
+    let fib = store.get_export_func::<i32, i32>(inst, "fib").unwrap();
+    let sum_via_host = store.get_export_func::<i32, i32>(inst, "sum_via_host").unwrap();
+    sum_var.set(Some(sum));
+
+    // fuel is unlimited by default.
+    let consumed = store.fuel_consumed();
+    assert_eq!(store.call(fib, 20).unwrap(), 6765);
+    let fib_cost = store.fuel_consumed() - consumed;
+    assert!(fib_cost > 6765);
+
+
+    // running out of fuel suspends the call, even deep in the call stack.
+    let consumed = store.fuel_consumed();
+    store.set_fuel(1000);
+    assert!(matches!(store.call(fib, 20).unwrap_err(), Error::OutOfFuel));
+    assert!(store.is_suspended());
+    assert_eq!(store.fuel(), 0);
+    assert_eq!(store.fuel_consumed() - consumed, 1000);
+
+    let result = loop {
+        store.set_fuel(1000);
+        match store.resume::<i32>() {
+            Ok(result) => break result,
+            Err(Error::OutOfFuel) => (),
+            Err(e) => panic!("{e:?}"),
+        }
+    };
+    assert_eq!(result, 6765);
+    assert!(!store.is_suspended());
+    assert_eq!(store.fuel_consumed() - consumed, fib_cost);
+    assert!(matches!(store.resume::<i32>().unwrap_err(), Error::NotSuspended));
+
+    // same for dynamic calls.
+    store.set_fuel(100);
+    let mut rets = [Value::I32(0)];
+    assert!(matches!(store.call_dyn(sum.func_id(), &[Value::I32(100)], &mut rets).unwrap_err(), Error::OutOfFuel));
+    store.set_fuel(u64::MAX);
+    let rets = store.resume_dyn(&mut rets).unwrap();
+    assert!(matches!(rets, [Value::I32(5050)]));
+
+
+    // a new call discards the suspended call.
+    store.set_fuel(10);
+    assert!(matches!(store.call(sum, 100).unwrap_err(), Error::OutOfFuel));
+    assert!(store.is_suspended());
+    store.set_fuel(1000);
+    assert_eq!(store.call(sum, 3).unwrap(), 6);
+    assert!(!store.is_suspended());
+
+
+    // calls made by host functions can't be suspended.
+    store.set_fuel(50);
+    assert!(matches!(store.call(sum_via_host, 100).unwrap_err(), Error::OutOfFuel));
+    assert!(!store.is_suspended());
+
+    store.set_fuel(1000);
+    assert_eq!(store.call(sum_via_host, 10).unwrap(), 55);
+
+
+    // setting the fuel doesn't reset the consumed total.
+    let consumed = store.fuel_consumed();
+    store.set_fuel(u64::MAX);
+    store.set_fuel(u64::MAX);
+    assert_eq!(store.fuel_consumed(), consumed);
+    store.call(sum, 3).unwrap();
+    assert!(store.fuel_consumed() > consumed);
+}
+
+#[test]
+fn fuel_start() {
+    let mut store = Store::new();
+
+    let host_sum = store.new_host_func(|_: i32| 0);
+    let inst = store.new_instance(include_bytes!("fuel.wasm"),
+        &[("host", "sum", host_sum.into())]).unwrap();
+    let sum = store.get_export_func::<i32, i32>(inst, "sum").unwrap();
+
+    store.set_fuel(10);
+    assert!(matches!(store.call(sum, 100).unwrap_err(), Error::OutOfFuel));
+    assert!(store.is_suspended());
+
+    // start functions can't be suspended,
+    // & they don't discard the suspended call.
+    store.set_fuel(100);
+    assert!(matches!(store.new_instance(include_bytes!("fuel_start.wasm"), &[]).unwrap_err(), Error::OutOfFuel));
+    assert!(store.is_suspended());
+
+    store.set_fuel(u64::MAX);
+    assert_eq!(store.resume::<i32>().unwrap(), 5050);
+
+    store.set_fuel(100_000);
+    store.new_instance(include_bytes!("fuel_start.wasm"), &[]).unwrap();
+    assert!(!store.is_suspended());
+}
@@ -0,0 +1,23 @@
+(module
+  (import "host" "sum" (func $host_sum (param i32) (result i32)))
+
+  (func $sum (export "sum") (param $n i32) (result i32)
+    (local $acc i32)
+    (block
+      (loop
+        (br_if 1 (i32.eqz (local.get $n)))
+        (local.set $acc (i32.add (local.get $acc) (local.get $n)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br 0)))
+    (local.get $acc))
+
+  (func $fib (export "fib") (param $n i32) (result i32)
+    (if (result i32) (i32.lt_u (local.get $n) (i32.const 2))
+      (then (local.get $n))
+      (else
+        (i32.add
+          (call $fib (i32.sub (local.get $n) (i32.const 1)))
+          (call $fib (i32.sub (local.get $n) (i32.const 2)))))))
+
+  (func (export "sum_via_host") (param i32) (result i32)
+    (call $host_sum (local.get 0))))
@@ -0,0 +1,8 @@
+
+wat2wasm \
+    -o fuel.wasm \
+    fuel.wat
+
+wat2wasm \
+    -o fuel_start.wasm \
+    fuel_start.wat
@@ -0,0 +1,9 @@
+(module
+  (func $spin
+    (local $n i32)
+    (local.set $n (i32.const 1000))
+    (loop
+      (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+      (br_if 0 (local.get $n))))
+
+  (start $spin))
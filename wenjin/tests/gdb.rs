@@ -0,0 +1,178 @@
+#![cfg(feature = "gdb")]
+
+use std::io::{Read, Write, BufRead, BufReader};
+use std::net::{TcpListener, TcpStream};
+
+use wenjin::{Store, Error};
+use wenjin::gdb::GdbStub;
+
+
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    ack: bool,
+}
+
+impl Client {
+    fn connect(listener: &TcpListener) -> Client {
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        stream.set_nodelay(true).unwrap();
+        Client { writer: stream.try_clone().unwrap(), reader: BufReader::new(stream), ack: true }
+    }
+
+    fn send(&mut self, packet: &str) {
+        let checksum = packet.bytes().fold(0u8, |acc, b| acc.wrapping_add(b));
+        write!(self.writer, "${packet}#{checksum:02x}").unwrap();
+        if self.ack {
+            let mut ack = [0];
+            self.reader.read_exact(&mut ack).unwrap();
+            assert_eq!(ack[0], b'+');
+        }
+    }
+
+    fn recv(&mut self) -> String {
+        let mut buffer = Vec::new();
+        self.reader.read_until(b'$', &mut buffer).unwrap();
+        assert_eq!(buffer, b"$");
+
+        let mut packet = Vec::new();
+        self.reader.read_until(b'#', &mut packet).unwrap();
+        packet.pop();
+        let mut checksum = [0; 2];
+        self.reader.read_exact(&mut checksum).unwrap();
+        let checksum = u8::from_str_radix(std::str::from_utf8(&checksum).unwrap(), 16).unwrap();
+        assert_eq!(checksum, packet.iter().fold(0u8, |acc, b| acc.wrapping_add(*b)));
+
+        if self.ack {
+            self.writer.write_all(b"+").unwrap();
+        }
+        String::from_utf8(packet).unwrap()
+    }
+
+    fn request(&mut self, packet: &str) -> String {
+        self.send(packet);
+        self.recv()
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn find(wasm: &[u8], pattern: &[u8]) -> u64 {
+    let mut matches = wasm.windows(pattern.len()).enumerate().filter(|(_, w)| *w == pattern);
+    let (offset, _) = matches.next().unwrap();
+    assert!(matches.next().is_none());
+    offset as u64
+}
+
+const CODE: u64 = 1 << 62;
+
+
+#[test]
+fn gdb() {
+    let wasm = include_bytes!("gdb.wasm");
+
+    // `local.get $x` in `run`, `i32.add` in `$add`, the end of `run`.
+    let run_begin = CODE | find(wasm, &[0x20, 0, 0x41, 1]);
+    let add_add = CODE | (find(wasm, &[0x20, 1, 0x6a]) + 2);
+    let run_ret = CODE | (find(wasm, &[0x10, 0, 0x0b]) + 2);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let guest = std::thread::spawn({
+        let listener = listener.try_clone().unwrap();
+        move || {
+            let mut store = Store::new();
+            let inst = store.new_instance(include_bytes!("gdb.wasm"), &[]).unwrap();
+            let run = store.get_export_func::<i32, i32>(inst, "run").unwrap();
+            GdbStub::accept(&listener).unwrap().attach(&mut store);
+            let a = store.call(run, 5).unwrap();
+            let b = store.call(run, 2).unwrap();
+            let c = store.call(run, 3).unwrap();
+            (a, b, c)
+        }
+    });
+
+    let mut client = Client::connect(&listener);
+    assert!(client.request("qSupported:xmlRegisters=i386").contains("qXfer:libraries:read+"));
+    assert_eq!(client.request("QStartNoAckMode"), "OK");
+    client.ack = false;
+    assert!(client.request("qHostInfo").contains("arch:wasm32;"));
+    assert_eq!(client.request("qfThreadInfo"), "m1");
+    assert_eq!(client.request("vCont?"), "");
+
+    // stopped at the first instruction.
+    let stop = client.request("?");
+    assert!(stop.starts_with("T05thread:1;"));
+    assert!(stop.contains(&format!("thread-pcs:{run_begin:x};")));
+    assert!(stop.contains("reason:trace;"));
+    assert_eq!(client.request("qWasmCallStack:1"), hex(&run_begin.to_le_bytes()));
+    assert_eq!(client.request("p0"), hex(&run_begin.to_le_bytes()));
+    assert_eq!(client.request("qRegisterInfo1"), "E45");
+
+    // the module & memory.
+    assert_eq!(client.request("qXfer:libraries:read::0,1000"),
+        format!("l<library-list><library name=\"instance0.wasm\"><section address=\"0x{CODE:x}\"/></library></library-list>"));
+    assert_eq!(client.request("qXfer:libraries:read::0,5"), "m<libr");
+    assert_eq!(client.request(&format!("m{CODE:x},4")), "0061736d");
+    assert_eq!(client.request(&format!("m{:x},100", CODE | (wasm.len() as u64 - 2))), hex(&wasm[wasm.len()-2..]));
+    assert_eq!(client.request("m10,6"), hex(b"wenjin"));
+    assert_eq!(client.request("m10000,1"), "E03");
+    assert_eq!(client.request("qWasmMem:0;10;6"), hex(b"wenjin"));
+
+    // breakpoints.
+    assert_eq!(client.request(&format!("Z0,{:x},1", add_add - 1)), "E03");
+    assert_eq!(client.request(&format!("Z0,{add_add:x},1")), "OK");
+    assert_eq!(client.request("qWasmLocal:0;0"), "05000000");
+
+    client.send("c");
+    let stop = client.recv();
+    assert!(stop.contains(&format!("thread-pcs:{add_add:x};")));
+    assert!(stop.contains("reason:breakpoint;"));
+    assert_eq!(client.request("qWasmCallStack:1"),
+        hex(&[add_add.to_le_bytes(), run_ret.to_le_bytes()].concat()));
+    assert_eq!(client.request("qWasmLocal:0;1"), "01000000");
+    assert_eq!(client.request("qWasmStackValue:0;0"), "05000000");
+    assert_eq!(client.request("qWasmStackValue:0;1"), "01000000");
+    assert_eq!(client.request("qWasmStackValue:0;2"), "E03");
+    assert_eq!(client.request("qWasmLocal:1;0"), "05000000");
+    assert_eq!(client.request("qWasmGlobal:0;0"), "07000000");
+    assert_eq!(client.request("qWasmLocal:2;0"), "E03");
+
+    // step.
+    client.send("s");
+    let stop = client.recv();
+    assert!(stop.contains(&format!("thread-pcs:{:x};", add_add + 1)));
+    assert!(stop.contains("reason:trace;"));
+    assert_eq!(client.request("qWasmStackValue:0;0"), "06000000");
+
+    // the next call stops at the breakpoint.
+    client.send("c");
+    let stop = client.recv();
+    assert!(stop.contains("reason:breakpoint;"));
+    assert_eq!(client.request("qWasmLocal:0;0"), "02000000");
+
+    // detaching removes the breakpoints.
+    assert_eq!(client.request("D"), "OK");
+    assert_eq!(guest.join().unwrap(), (6, 3, 4));
+
+
+    // kill.
+    let guest = std::thread::spawn({
+        let listener = listener.try_clone().unwrap();
+        move || {
+            let mut store = Store::new();
+            let inst = store.new_instance(include_bytes!("gdb.wasm"), &[]).unwrap();
+            let run = store.get_export_func::<i32, i32>(inst, "run").unwrap();
+            GdbStub::accept(&listener).unwrap().attach(&mut store);
+            let result = store.call(run, 5);
+            assert!(matches!(result.unwrap_err().inner(), Error::Interrupted));
+            store.call(run, 5).unwrap()
+        }
+    });
+
+    let mut client = Client::connect(&listener);
+    assert!(client.request("?").starts_with("T05"));
+    client.send("k");
+    assert_eq!(guest.join().unwrap(), 6);
+}
@@ -0,0 +1,12 @@
+(module
+    (memory 1)
+    (data (i32.const 16) "wenjin")
+
+    (global $counter (mut i32) (i32.const 7))
+
+    (func $add (param $a i32) (param $b i32) (result i32)
+        (i32.add (local.get $a) (local.get $b)))
+
+    (func (export "run") (param $x i32) (result i32)
+        (call $add (local.get $x) (i32.const 1)))
+)
@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o gdb.wasm \
+    gdb.wat
@@ -0,0 +1,87 @@
+use wenjin::{Store, Error, Value, RefValue, wasm};
+
+
+#[test]
+fn host_func_dyn() {
+    use wasm::ValueType::*;
+
+    let mut store = Store::new();
+
+    // more params than the typed host functions support.
+    let many_params = [I32; 20];
+    let many = store.new_host_func_dyn(
+        wasm::FuncType { params: &many_params, rets: &[I64, F64] },
+        |_, args, rets| {
+            let mut sum = 0;
+            for arg in args {
+                let Value::I32(arg) = arg else { unreachable!() };
+                sum += *arg as i64;
+            }
+            rets[0] = Value::I64(sum);
+            rets[1] = Value::F64(args.len() as f64);
+            Ok(())
+        }).unwrap();
+
+    let nulls = std::rc::Rc::new(std::cell::Cell::new(0));
+    let select_ref = store.new_host_func_dyn(
+        wasm::FuncType { params: &[I32, ExternRef, ExternRef], rets: &[ExternRef] },
+        { let nulls = nulls.clone(); move |_, args, rets| {
+            let [Value::I32(cond), Value::ExternRef(a), Value::ExternRef(b)] = *args else { unreachable!() };
+            nulls.set(nulls.get() + a.is_null() as i32 + b.is_null() as i32);
+            // `rets` starts out null.
+            assert!(matches!(rets, [Value::ExternRef(r)] if r.is_null()));
+            rets[0] = Value::ExternRef(if cond != 0 { a } else { b });
+            Ok(())
+        }}).unwrap();
+
+    let inst = store.new_instance(include_bytes!("host_func_dyn.wasm"), &[
+        ("host", "many", many.into()),
+        ("host", "select_ref", select_ref.into()),
+    ]).unwrap();
+
+    let call_many = store.get_export_func::<(), (i64, f64)>(inst, "call_many").unwrap();
+    assert_eq!(store.call(call_many, ()).unwrap(), (210, 20.0));
+
+    let is_second_null = store.get_export_func_dyn(inst, "is_second_null").unwrap();
+    let mut rets = [Value::I32(0)];
+    store.call_dyn(is_second_null, &[Value::I32(0), Value::ExternRef(RefValue::NULL)], &mut rets).unwrap();
+    assert!(matches!(rets, [Value::I32(1)]));
+    assert_eq!(nulls.get(), 2);
+
+
+    // `call_dyn` checks argument types.
+    let mut rets = [Value::I32(0)];
+    assert!(matches!(store.call_dyn(select_ref, &[Value::I32(0)], &mut rets).unwrap_err(), Error::TypeMismatch));
+    let args = [Value::I64(0), Value::ExternRef(RefValue::NULL), Value::ExternRef(RefValue::NULL)];
+    assert!(matches!(store.call_dyn(select_ref, &args, &mut rets).unwrap_err(), Error::TypeMismatch));
+
+    // host functions can be called directly.
+    let mut rets = [Value::I64(0), Value::F64(0.0)];
+    store.call_dyn(many, &[Value::I32(1); 20], &mut rets).unwrap();
+    assert!(matches!(rets, [Value::I64(20), Value::F64(n)] if n == 20.0));
+
+
+    // results of the wrong type fail the call.
+    let bad = store.new_host_func_dyn(
+        wasm::FuncType { params: &[], rets: &[I32] },
+        |_, _, rets| {
+            rets[0] = Value::F32(1.0);
+            Ok(())
+        }).unwrap();
+    let mut rets = [Value::I32(0)];
+    assert!(matches!(store.call_dyn(bad, &[], &mut rets).unwrap_err(), Error::TypeMismatch));
+
+    // errors propagate.
+    let fails = store.new_host_func_dyn(
+        wasm::FuncType { params: &[], rets: &[] },
+        |_, _, _| Err(Error::TrapUnreachable)).unwrap();
+    assert!(matches!(store.call_dyn(fails, &[], &mut []).unwrap_err().inner(), Error::TrapUnreachable));
+
+    // `v128` isn't supported.
+    let e = store.new_host_func_dyn(
+        wasm::FuncType { params: &[V128], rets: &[] },
+        |_, _, _| Ok(())).unwrap_err();
+    assert!(matches!(e, Error::Unimplemented));
+
+    assert_eq!(store.call(call_many, ()).unwrap(), (210, 20.0));
+}
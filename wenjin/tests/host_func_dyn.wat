@@ -0,0 +1,21 @@
+(module
+    (import "host" "many" (func $many
+        (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)
+        (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)
+        (result i64 f64)))
+    (import "host" "select_ref" (func $select_ref (param i32 externref externref) (result externref)))
+
+    (func (export "call_many") (result i64 f64)
+        i32.const 1   i32.const 2   i32.const 3   i32.const 4   i32.const 5
+        i32.const 6   i32.const 7   i32.const 8   i32.const 9   i32.const 10
+        i32.const 11  i32.const 12  i32.const 13  i32.const 14  i32.const 15
+        i32.const 16  i32.const 17  i32.const 18  i32.const 19  i32.const 20
+        call $many)
+
+    (func (export "is_second_null") (param i32 externref) (result i32)
+        local.get 0
+        ref.null extern
+        local.get 1
+        call $select_ref
+        ref.is_null)
+)
@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o host_func_dyn.wasm \
+    host_func_dyn.wat
@@ -0,0 +1,28 @@
+use wenjin::{Store, Error};
+
+
+#[test]
+fn interrupt() {
+    let mut store = Store::new();
+
+    let inst = store.new_instance(include_bytes!("interrupt.wasm"), &[]).unwrap();
+    let spin = store.get_export_func::<(), ()>(inst, "spin").unwrap();
+    let add = store.get_export_func::<(i32, i32), i32>(inst, "add").unwrap();
+
+    // interrupt from another thread.
+    let handle = store.interrupt_handle();
+    let watchdog = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        handle.interrupt();
+    });
+    assert!(matches!(store.call(spin, ()).unwrap_err(), Error::Interrupted));
+    watchdog.join().unwrap();
+
+    // the store is still usable.
+    assert_eq!(store.call(add, (1, 2)).unwrap(), 3);
+
+    // interrupting while idle doesn't stop the next call.
+    store.interrupt_handle().interrupt();
+    assert_eq!(store.call(add, (1, 2)).unwrap(), 3);
+    assert_eq!(store.call(add, (3, 4)).unwrap(), 7);
+}
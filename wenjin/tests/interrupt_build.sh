@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o interrupt.wasm \
+    interrupt.wat
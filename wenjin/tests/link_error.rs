@@ -0,0 +1,73 @@
+use wenjin::{Store, Error, Extern, ExternType, LinkError, Value, wasm};
+
+
+#[test]
+fn link_error() {
+    let mut store = Store::new();
+
+    let func = store.new_host_func(|x: i32| x as i64);
+    let global = store.new_global(true, Value::I32(0));
+    let table = store.new_table(wasm::RefType::FuncRef, wasm::Limits { min: 1, max: None }).unwrap();
+    let memory = store.new_memory(wasm::Limits { min: 1, max: Some(2) }).unwrap();
+
+    let imports = [
+        ("env", "func", func.into()),
+        ("env", "global", global.into()),
+        ("env", "table", table.into()),
+        ("env", "memory", memory.into()),
+    ];
+
+    fn link(store: &mut Store, imports: &[(&str, &str, Extern)]) -> LinkError {
+        match store.new_instance(include_bytes!("link_error.wasm"), imports) {
+            Err(Error::Link(e)) => *e,
+            Err(e) => panic!("expected link error, got {e:?}"),
+            Ok(_) => panic!("expected link error"),
+        }
+    }
+
+    let with = |name: &str, ext: Extern| {
+        let mut imports = imports;
+        imports.iter_mut().find(|(_, n, _)| *n == name).unwrap().2 = ext;
+        imports
+    };
+
+
+    // missing.
+    let e = link(&mut store, &imports[1..]);
+    assert_eq!((e.module.as_str(), e.name.as_str()), ("env", "func"));
+    assert!(matches!(&e.expected, ExternType::Func { params, rets }
+        if params == &[wasm::ValueType::I32] && rets == &[wasm::ValueType::I64]));
+    assert!(e.found.is_none());
+
+    // wrong kind.
+    let e = link(&mut store, &with("func", global.into()));
+    assert_eq!(e.name, "func");
+    assert!(matches!(e.found, Some(ExternType::Global(wasm::GlobalType { ty: wasm::ValueType::I32, mutable: true }))));
+
+    // wrong signature.
+    let other_func = store.new_host_func(|x: i32| x);
+    let e = link(&mut store, &with("func", other_func.into()));
+    assert!(matches!(&e.found, Some(ExternType::Func { params, rets })
+        if params == &[wasm::ValueType::I32] && rets == &[wasm::ValueType::I32]));
+
+    // wrong mutability.
+    let const_global = store.new_global(false, Value::I32(0));
+    let e = link(&mut store, &with("global", const_global.into()));
+    assert_eq!(e.name, "global");
+    assert!(matches!(e.expected, ExternType::Global(wasm::GlobalType { mutable: true, .. })));
+    assert!(matches!(e.found, Some(ExternType::Global(wasm::GlobalType { mutable: false, .. }))));
+
+    // table too small.
+    let small_table = store.new_table(wasm::RefType::FuncRef, wasm::Limits { min: 0, max: None }).unwrap();
+    let e = link(&mut store, &with("table", small_table.into()));
+    assert!(matches!(e.found, Some(ExternType::Table(wasm::TableType { limits: wasm::Limits { min: 0, max: None }, .. }))));
+
+    // memory without a maximum.
+    let unbounded_memory = store.new_memory(wasm::Limits { min: 1, max: None }).unwrap();
+    let e = link(&mut store, &with("memory", unbounded_memory.into()));
+    assert!(matches!(e.expected, ExternType::Memory(wasm::MemoryType { limits: wasm::Limits { min: 1, max: Some(2) }, is64: false })));
+    assert!(matches!(e.found, Some(ExternType::Memory(wasm::MemoryType { limits: wasm::Limits { min: 1, max: None }, is64: false }))));
+
+
+    store.new_instance(include_bytes!("link_error.wasm"), &imports).unwrap();
+}
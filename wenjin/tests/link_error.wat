@@ -0,0 +1,5 @@
+(module
+  (import "env" "func" (func (param i32) (result i64)))
+  (import "env" "global" (global (mut i32)))
+  (import "env" "table" (table 1 funcref))
+  (import "env" "memory" (memory 1 2)))
@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o link_error.wasm \
+    link_error.wat
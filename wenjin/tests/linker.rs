@@ -0,0 +1,39 @@
+use wenjin::{Store, Linker, Extern, Error};
+
+
+#[test]
+fn linker() {
+    let mut store = Store::new();
+    let mut linker = Linker::new();
+
+    let logged = std::rc::Rc::new(std::cell::Cell::new(0));
+    let log = store.new_host_func({ let logged = logged.clone(); move |x: i32| {
+        logged.set(x);
+    }});
+    linker.define("host", "log", log);
+
+    // the main module can't be instantiated before `lib` is registered.
+    let Err(Error::Link(e)) = linker.instantiate(&mut store, include_bytes!("linker.wasm")) else { panic!() };
+    assert_eq!((e.module.as_str(), e.name.as_str()), ("lib", "memory"));
+
+    let lib = linker.instantiate(&mut store, include_bytes!("linker_lib.wasm")).unwrap();
+    linker.define_instance(&store, "lib", lib);
+    assert!(matches!(linker.get("lib", "next"), Some(Extern::Func(_))));
+    assert!(matches!(linker.get("lib", "counter"), Some(Extern::Global(_))));
+    assert!(linker.get("lib", "nope").is_none());
+    assert!(linker.get("nope", "next").is_none());
+
+    let a = linker.instantiate(&mut store, include_bytes!("linker.wasm")).unwrap();
+    let b = linker.instantiate(&mut store, include_bytes!("linker.wasm")).unwrap();
+    let a_run = store.get_export_func::<(), i32>(a, "run").unwrap();
+    let b_run = store.get_export_func::<(), i32>(b, "run").unwrap();
+
+    // both instances share `lib`'s counter.
+    assert_eq!(store.call(a_run, ()).unwrap(), 10);
+    assert_eq!(logged.get(), 1);
+    assert_eq!(store.call(b_run, ()).unwrap(), 20);
+    assert_eq!(logged.get(), 2);
+
+    let lib_next = store.get_export_func::<(), i32>(lib, "next").unwrap();
+    assert_eq!(store.call(lib_next, ()).unwrap(), 3);
+}
@@ -0,0 +1,10 @@
+(module
+  (import "host" "log" (func $log (param i32)))
+  (import "lib" "memory" (memory 1))
+  (import "lib" "counter" (global $counter (mut i32)))
+  (import "lib" "next" (func $next (result i32)))
+
+  (func (export "run") (result i32)
+    (call $log (call $next))
+    (i32.store (i32.const 0) (i32.mul (global.get $counter) (i32.const 10)))
+    (i32.load (i32.const 0))))
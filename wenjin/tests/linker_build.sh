@@ -0,0 +1,7 @@
+wat2wasm \
+    -o linker_lib.wasm \
+    linker_lib.wat
+
+wat2wasm \
+    -o linker.wasm \
+    linker.wat
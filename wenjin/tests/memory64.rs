@@ -0,0 +1,79 @@
+use wenjin::{Store, Error, WasmPtr64, WasmSize64, WasmSlice64, wasm};
+
+
+#[test]
+fn memory64() {
+    let mut store = Store::new();
+
+    let sum = store.new_host_func(|store: &mut Store, ptr: WasmPtr64<u8>, len: WasmSize64| -> Result<i64, Error> {
+        let heap = store.caller_memory()?;
+        assert!(heap.is64());
+        let mut sum = 0;
+        for i in 0..len.0 {
+            sum += heap.read64(ptr.add(i))? as i64;
+        }
+        Ok(sum)
+    });
+    let heap = store.new_memory64(wasm::Limits { min: 1, max: Some(4) }).unwrap();
+
+    // memories only match memory imports of the same address type.
+    let heap32 = store.new_memory(wasm::Limits { min: 1, max: Some(4) }).unwrap();
+    let e = store.new_instance(include_bytes!("memory64.wasm"),
+        &[("host", "sum", sum.into()), ("host", "heap", heap32.into())]).unwrap_err();
+    assert!(matches!(e, Error::Link(_)));
+
+    let inst = store.new_instance(include_bytes!("memory64.wasm"),
+        &[("host", "sum", sum.into()), ("host", "heap", heap.into())]).unwrap();
+    let load8 = store.get_export_func::<i64, i32>(inst, "load8").unwrap();
+    let load8_offset = store.get_export_func::<i64, i32>(inst, "load8_offset").unwrap();
+    let store64 = store.get_export_func::<(i64, i64), ()>(inst, "store64").unwrap();
+    let size = store.get_export_func::<(), i64>(inst, "size").unwrap();
+    let grow = store.get_export_func::<i64, i64>(inst, "grow").unwrap();
+    let fill = store.get_export_func::<(i64, i32, i64), ()>(inst, "fill").unwrap();
+    let copy = store.get_export_func::<(i64, i64, i64), ()>(inst, "copy").unwrap();
+    let init = store.get_export_func::<i64, ()>(inst, "init").unwrap();
+    let call_sum = store.get_export_func::<(i64, i64), i64>(inst, "sum").unwrap();
+
+    // the active segment has an `i64` offset.
+    assert_eq!(store.call(load8, 16).unwrap(), b'w' as i32);
+
+    // addresses & offsets don't wrap at 4 GiB.
+    assert!(matches!(store.call(load8, 0x1_0000_0010).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+    assert!(matches!(store.call(load8_offset, 16).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+    assert!(matches!(store.call(load8, -1).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+
+    store.call(store64, (65528, 0x0102030405060708)).unwrap();
+    assert_eq!(store.call(load8, 65528).unwrap(), 8);
+    assert_eq!(store.call(load8, 65535).unwrap(), 1);
+    assert!(matches!(store.call(store64, (65529, 0)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+
+    store.call(fill, (32, b'x' as i32, 2)).unwrap();
+    store.call(init, 34).unwrap();
+    assert!(matches!(store.call(fill, (65535, 0, 2)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+
+    // through the host.
+    store.call(copy, (100, 16, 6)).unwrap();
+    store.call(copy, (200, 32, 6)).unwrap();
+    assert_eq!(store.memory(heap).read64(WasmPtr64::<[u8; 6]>::new(200)).unwrap(), *b"xxinit");
+    assert!(matches!(store.memory(heap).read64(WasmPtr64::<u8>::new(0x1_0000_0000)).unwrap_err(), Error::TrapMemoryBounds));
+    assert!(matches!(store.memory(heap).write64(WasmPtr64::<u32>::new(u64::MAX - 1), 0).unwrap_err(), Error::TrapMemoryBounds));
+
+    // slices don't wrap at 4 GiB either.
+    let mut bytes = [0u8; 6];
+    let slice = |addr| WasmSlice64 { ptr: WasmPtr64::new(addr), len: WasmSize64(6) };
+    unsafe { store.memory(heap).read_slice64(slice(200), bytes.as_mut_ptr()).unwrap() };
+    assert_eq!(bytes, *b"xxinit");
+    let e = unsafe { store.memory(heap).read_slice64(slice(0x1_0000_0000 + 200), bytes.as_mut_ptr()).unwrap_err() };
+    assert!(matches!(e, Error::TrapMemoryBounds));
+    assert_eq!(store.call(call_sum, (100, 6)).unwrap(), b"wasm64".iter().map(|b| *b as i64).sum::<i64>());
+    assert!(matches!(store.call(copy, (0, 0, 0x1_0000_0000)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+
+    // the heap's max is 4 pages.
+    assert_eq!(store.call(size, ()).unwrap(), 1);
+    assert_eq!(store.call(grow, 2).unwrap(), 1);
+    assert_eq!(store.memory(heap).grow64(1).unwrap(), 3);
+    assert_eq!(store.call(grow, 1).unwrap(), -1);
+    assert_eq!(store.call(grow, 0x1_0000_0000).unwrap(), -1);
+    assert_eq!(store.call(size, ()).unwrap(), 4);
+    assert_eq!(store.memory(heap).size_pages64(), 4);
+}
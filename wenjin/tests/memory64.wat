@@ -0,0 +1,35 @@
+(module
+    (import "host" "sum" (func $sum (param i64 i64) (result i64)))
+    (import "host" "heap" (memory $heap i64 1 4))
+
+    (memory $mem i64 1)
+    (data (memory $mem) (i64.const 16) "wasm64")
+    (data $passive "init")
+
+    (func (export "load8") (param i64) (result i32)
+        (i32.load8_u $mem (local.get 0)))
+
+    (func (export "load8_offset") (param i64) (result i32)
+        (i32.load8_u $mem offset=0x100000000 (local.get 0)))
+
+    (func (export "store64") (param i64 i64)
+        (i64.store $mem (local.get 0) (local.get 1)))
+
+    (func (export "size") (result i64)
+        (memory.size $heap))
+
+    (func (export "grow") (param i64) (result i64)
+        (memory.grow $heap (local.get 0)))
+
+    (func (export "fill") (param i64 i32 i64)
+        (memory.fill $mem (local.get 0) (local.get 1) (local.get 2)))
+
+    (func (export "copy") (param i64 i64 i64)
+        (memory.copy $heap $mem (local.get 0) (local.get 1) (local.get 2)))
+
+    (func (export "init") (param i64)
+        (memory.init $mem $passive (local.get 0) (i32.const 0) (i32.const 4)))
+
+    (func (export "sum") (param i64 i64) (result i64)
+        (call $sum (local.get 0) (local.get 1)))
+)
@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o memory64.wasm \
+    memory64.wat
@@ -0,0 +1,44 @@
+use wenjin::{Store, Module, ExternType, wasm};
+
+
+#[test]
+fn module() {
+    let lib = Module::new(include_bytes!("linker_lib.wasm")).unwrap();
+
+    let exports = Vec::from_iter(lib.exports());
+    assert_eq!(exports.len(), 3);
+    assert!(matches!(exports[0], ("memory", ExternType::Memory(wasm::MemoryType { limits: wasm::Limits { min: 1, max: None }, is64: false }))));
+    assert!(matches!(exports[1], ("counter", ExternType::Global(wasm::GlobalType { ty: wasm::ValueType::I32, mutable: true }))));
+    assert!(matches!(&exports[2], ("next", ExternType::Func { params, rets })
+        if params.is_empty() && rets == &[wasm::ValueType::I32]));
+    assert_eq!(lib.imports().count(), 0);
+
+    let main = Module::new(include_bytes!("linker.wasm")).unwrap();
+    let imports = Vec::from_iter(main.imports().map(|(module, name, _)| (module, name)));
+    assert_eq!(imports, [("host", "log"), ("lib", "memory"), ("lib", "counter"), ("lib", "next")]);
+
+
+    // each instance has its own state.
+    let mut store = Store::new();
+    let a = store.instantiate(&lib, &[]).unwrap();
+    let b = store.instantiate(&lib, &[]).unwrap();
+    let a_next = store.get_export_func::<(), i32>(a, "next").unwrap();
+    let b_next = store.get_export_func::<(), i32>(b, "next").unwrap();
+    assert_eq!(store.call(a_next, ()).unwrap(), 1);
+    assert_eq!(store.call(a_next, ()).unwrap(), 2);
+    assert_eq!(store.call(b_next, ()).unwrap(), 1);
+
+    // modules aren't tied to a store.
+    let mut other = Store::new();
+    let c = other.instantiate(&lib.clone(), &[]).unwrap();
+    let c_next = other.get_export_func::<(), i32>(c, "next").unwrap();
+    assert_eq!(other.call(c_next, ()).unwrap(), 1);
+
+    // the instances keep the module alive.
+    drop(lib);
+    assert_eq!(store.call(a_next, ()).unwrap(), 3);
+    assert_eq!(other.call(c_next, ()).unwrap(), 2);
+
+
+    let Err(wenjin::Error::Wasm(_)) = Module::new(b"\0asm") else { panic!() };
+}
@@ -0,0 +1,67 @@
+use wenjin::{Store, Error, WasmPtr, WasmSize};
+
+
+#[test]
+fn multi_memory() {
+    let mut store = Store::new();
+    store.set_data(Vec::<String>::new());
+
+    let log = store.new_host_func(|store: &mut Store, ptr: WasmPtr<u8>, len: WasmSize| -> Result<(), Error> {
+        let scratch = store.caller_memory_at(1)?;
+        let mut entry = String::new();
+        for i in 0..len.0 {
+            entry.push(scratch.read(ptr.add(i))? as char);
+        }
+        store.data_mut::<Vec<String>>().unwrap().push(entry);
+
+        assert!(matches!(store.caller_memory_at(2).unwrap_err(), Error::CallerNoMemory));
+        Ok(())
+    });
+
+    let inst = store.new_instance(include_bytes!("multi_memory.wasm"),
+        &[("host", "log", log.into())]).unwrap();
+    let heap_load = store.get_export_func::<i32, i32>(inst, "heap_load").unwrap();
+    let scratch_load = store.get_export_func::<i32, i32>(inst, "scratch_load").unwrap();
+    let scratch_store = store.get_export_func::<(i32, i32), ()>(inst, "scratch_store").unwrap();
+    let scratch_size = store.get_export_func::<(), i32>(inst, "scratch_size").unwrap();
+    let scratch_grow = store.get_export_func::<i32, i32>(inst, "scratch_grow").unwrap();
+    let copy_to_heap = store.get_export_func::<(i32, i32, i32), ()>(inst, "copy_to_heap").unwrap();
+    let fill_scratch = store.get_export_func::<(i32, i32, i32), ()>(inst, "fill_scratch").unwrap();
+    let init_scratch = store.get_export_func::<i32, ()>(inst, "init_scratch").unwrap();
+    let log_scratch = store.get_export_func::<(i32, i32), ()>(inst, "log").unwrap();
+
+    // the active segments.
+    assert_eq!(store.call(heap_load, 0).unwrap(), b'h' as i32);
+    assert_eq!(store.call(scratch_load, 0).unwrap(), b'c' as i32);
+
+    // the host shares the scratch memory, the heap stays apart.
+    let scratch = store.get_export_memory(inst, "scratch").unwrap();
+    store.memory(scratch).write(WasmPtr::<u8>::new(100), b'x').unwrap();
+    assert_eq!(store.call(scratch_load, 99).unwrap(), b'x' as i32);
+    assert_eq!(store.call(heap_load, 100).unwrap(), 0);
+
+    store.call(scratch_store, (200, 0x6f6c6c65)).unwrap();
+    assert_eq!(store.memory(scratch).read(WasmPtr::<u32>::new(200)).unwrap(), 0x6f6c6c65);
+    store.call(log_scratch, (0, 7)).unwrap();
+    store.call(log_scratch, (200, 4)).unwrap();
+    assert_eq!(store.data::<Vec<String>>().unwrap(), &["scratch", "ello"]);
+
+    store.call(copy_to_heap, (10, 0, 7)).unwrap();
+    let bytes: Vec<u8> = (10..17).map(|i| store.call(heap_load, i).unwrap() as u8).collect();
+    assert_eq!(bytes, b"scratch");
+    assert!(matches!(store.call(copy_to_heap, (0, 65535, 2)).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+
+    store.call(fill_scratch, (1, b'z' as i32, 2)).unwrap();
+    store.call(init_scratch, 3).unwrap();
+    store.call(log_scratch, (0, 7)).unwrap();
+    assert_eq!(store.data::<Vec<String>>().unwrap()[2], "szzhich");
+
+    // the scratch memory's max is 2 pages.
+    assert!(matches!(store.call(scratch_load, 65535).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+    assert_eq!(store.call(scratch_size, ()).unwrap(), 1);
+    assert_eq!(store.call(scratch_grow, 1).unwrap(), 1);
+    assert_eq!(store.call(scratch_grow, 1).unwrap(), -1);
+    assert_eq!(store.call(scratch_size, ()).unwrap(), 2);
+    assert_eq!(store.call(scratch_load, 65535).unwrap(), 0);
+    assert!(matches!(store.call(heap_load, 65536).unwrap_err(), Error::Trap(t) if matches!(t.error, Error::TrapMemoryBounds)));
+}
@@ -0,0 +1,36 @@
+(module
+    (import "host" "log" (func $log (param i32 i32)))
+
+    (memory $heap 1)
+    (memory $scratch (export "scratch") 1 2)
+    (data (memory $heap) (i32.const 0) "heap")
+    (data (memory $scratch) (i32.const 0) "scratch")
+    (data $hi "hi")
+
+    (func (export "heap_load") (param i32) (result i32)
+        (i32.load8_u $heap (local.get 0)))
+
+    (func (export "scratch_load") (param i32) (result i32)
+        (i32.load8_u $scratch offset=1 (local.get 0)))
+
+    (func (export "scratch_store") (param i32 i32)
+        (i32.store $scratch (local.get 0) (local.get 1)))
+
+    (func (export "scratch_size") (result i32)
+        (memory.size $scratch))
+
+    (func (export "scratch_grow") (param i32) (result i32)
+        (memory.grow $scratch (local.get 0)))
+
+    (func (export "copy_to_heap") (param i32 i32 i32)
+        (memory.copy $heap $scratch (local.get 0) (local.get 1) (local.get 2)))
+
+    (func (export "fill_scratch") (param i32 i32 i32)
+        (memory.fill $scratch (local.get 0) (local.get 1) (local.get 2)))
+
+    (func (export "init_scratch") (param i32)
+        (memory.init $scratch $hi (local.get 0) (i32.const 0) (i32.const 2)))
+
+    (func (export "log") (param i32 i32)
+        (call $log (local.get 0) (local.get 1)))
+)
@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o multi_memory.wasm \
+    multi_memory.wat
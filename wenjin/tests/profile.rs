@@ -0,0 +1,88 @@
+use wenjin::{Store, Error, ProfileWeight};
+
+
+#[test]
+fn profile() {
+    let mut store = Store::new();
+
+    let tick = store.new_host_func(|| {});
+    let inst = store.new_instance(include_bytes!("profile.wasm"), &[("host", "tick", tick.into())]).unwrap();
+    let run = store.get_export_func::<i32, i32>(inst, "run").unwrap();
+    let trap = store.get_export_func::<(), ()>(inst, "trap").unwrap();
+
+    assert!(store.stop_profiling().is_none());
+
+    store.start_profiling();
+    let consumed = store.fuel_consumed();
+    assert_eq!(store.call(run, 5).unwrap(), 5);
+    assert!(matches!(store.call(trap, ()).unwrap_err().inner(), Error::TrapUnreachable));
+    assert_eq!(store.call(run, 1).unwrap(), 1);
+    let instructions = store.fuel_consumed() - consumed;
+    let profile = store.stop_profiling().unwrap();
+
+    // not profiled.
+    assert_eq!(store.call(run, 2).unwrap(), 1);
+
+    let func = |name: &str| profile.funcs().iter().find(|f| f.name == name).unwrap();
+    let (run, fib, tick, trap) = (func("func[2]"), func("fib"), func("host[0]"), func("func[3]"));
+    assert_eq!(profile.funcs().len(), 4);
+
+    // fib(5) makes 15 calls, 8 of them leaves. fib(1) is 1 more leaf.
+    assert_eq!((run.calls, fib.calls, tick.calls, trap.calls), (2, 16, 9, 1));
+    assert_eq!(tick.instructions, 0);
+    assert_eq!(trap.instructions, 1);
+    assert!(run.total_time >= fib.total_time);
+    assert!(fib.total_time >= fib.self_time);
+
+    // instructions match the fuel.
+    let total: u64 = profile.funcs().iter().map(|f| f.instructions).sum();
+    assert_eq!(total, instructions);
+
+    // folded stacks.
+    let stacks = profile.folded_stacks(ProfileWeight::Instructions);
+    let mut weight = 0;
+    for line in stacks.lines() {
+        let (stack, w) = line.rsplit_once(' ').unwrap();
+        let frames: Vec<&str> = stack.split(';').collect();
+        assert!(frames[0] == "func[2]" || frames == ["func[3]"]);
+        assert!(frames[1..].iter().all(|f| *f == "fib"));
+        assert!(frames.len() <= 6);
+        weight += w.parse::<u64>().unwrap();
+    }
+    assert!(stacks.contains("func[2];fib;fib;fib;fib;fib "));
+    assert!(stacks.contains("func[3] 1\n"));
+    assert_eq!(weight, total);
+
+    let stacks = profile.folded_stacks(ProfileWeight::Nanoseconds);
+    assert!(stacks.lines().all(|line| line.starts_with("func[") || line.starts_with("host[")));
+
+    let summary = profile.summary();
+    assert_eq!(summary.lines().count(), 5);
+    assert!(summary.lines().any(|line| line.ends_with("  fib") && line.trim_start().starts_with("16 ")));
+}
+
+
+#[test]
+fn profile_resume() {
+    let mut store = Store::new();
+
+    let tick = store.new_host_func(|| {});
+    let inst = store.new_instance(include_bytes!("profile.wasm"), &[("host", "tick", tick.into())]).unwrap();
+    let run = store.get_export_func::<i32, i32>(inst, "run").unwrap();
+    let trap = store.get_export_func::<(), ()>(inst, "trap").unwrap();
+
+    store.start_profiling();
+    store.set_fuel(0);
+    assert!(matches!(store.call(trap, ()).unwrap_err(), Error::OutOfFuel));
+    store.set_fuel(u64::MAX);
+    assert!(matches!(store.resume::<()>().unwrap_err().inner(), Error::TrapUnreachable));
+
+    // the frames of a resumed call are closed when it traps,
+    // so they aren't the parents of the next call.
+    assert_eq!(store.call(run, 1).unwrap(), 1);
+    let profile = store.stop_profiling().unwrap();
+    let stacks = profile.folded_stacks(ProfileWeight::Instructions);
+    assert!(stacks.contains("func[3] 1\n"));
+    assert!(stacks.lines().any(|line| line.starts_with("func[2]")));
+    assert!(!stacks.lines().any(|line| line.starts_with("func[3];")));
+}
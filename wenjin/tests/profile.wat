@@ -0,0 +1,19 @@
+(module
+    (import "host" "tick" (func $tick))
+
+    (func $fib (param $n i32) (result i32)
+        (if (result i32) (i32.lt_u (local.get $n) (i32.const 2))
+            (then
+                (call $tick)
+                (local.get $n))
+            (else
+                (i32.add
+                    (call $fib (i32.sub (local.get $n) (i32.const 1)))
+                    (call $fib (i32.sub (local.get $n) (i32.const 2)))))))
+
+    (func (export "run") (param i32) (result i32)
+        (call $fib (local.get 0)))
+
+    (func (export "trap")
+        (unreachable))
+)
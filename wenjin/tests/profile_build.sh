@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o profile.wasm \
+    profile.wat
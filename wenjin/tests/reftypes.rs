@@ -0,0 +1,88 @@
+use wenjin::{Store, Error, Value, FuncRef, ExternRef};
+
+
+#[test]
+fn reftypes() {
+    let mut store = Store::new();
+
+    let name_len = store.new_host_func(|store: &mut Store, r: ExternRef| {
+        store.extern_ref::<String>(r).map_or(-1, |name| name.len() as i32)
+    });
+    let apply = store.new_host_func(|store: &mut Store, f: FuncRef, x: i32| {
+        let Some(f) = store.func_ref_id(f) else { return Err(Error::TrapCallIndirectRefNull) };
+        let f = store.check_func_type::<i32, i32>(f)?;
+        store.call(f, x)
+    });
+
+    let inst = store.new_instance(include_bytes!("reftypes.wasm"),
+        &[("env", "name_len", name_len.into()), ("env", "apply", apply.into())]).unwrap();
+    let double = store.get_export_func_dyn(inst, "double").unwrap();
+    let square = store.get_export_func_dyn(inst, "square").unwrap();
+    let put = store.get_export_func::<(i32, ExternRef), ()>(inst, "store").unwrap();
+    let load = store.get_export_func::<i32, ExternRef>(inst, "load").unwrap();
+    let name_len_at = store.get_export_func::<i32, i32>(inst, "name_len_at").unwrap();
+    let pick = store.get_export_func::<i32, FuncRef>(inst, "pick").unwrap();
+    let call_ref = store.get_export_func::<(FuncRef, i32), i32>(inst, "call_ref").unwrap();
+    let apply_picked = store.get_export_func::<(i32, i32), i32>(inst, "apply_picked").unwrap();
+
+
+    // host objects pass through wasm unchanged.
+    let alice = store.new_extern_ref(String::from("alice")).unwrap();
+    let bob = store.new_extern_ref(String::from("bob")).unwrap();
+    assert_eq!(store.num_extern_refs(), 2);
+
+    store.call(put, (0, alice)).unwrap();
+    store.call(put, (1, bob)).unwrap();
+    assert_eq!(store.call(load, 1).unwrap(), bob);
+    assert!(store.call(load, 2).unwrap().is_null());
+    assert_eq!(store.call(name_len_at, 0).unwrap(), 5);
+    assert_eq!(store.call(name_len_at, 1).unwrap(), 3);
+    assert_eq!(store.call(name_len_at, 2).unwrap(), -1);
+
+    store.extern_ref_mut::<String>(bob).unwrap().push_str("by");
+    assert_eq!(store.call(name_len_at, 1).unwrap(), 5);
+
+    // dropped objects no longer resolve, even when wasm still holds them.
+    assert_eq!(store.take_extern_ref::<String>(alice).as_deref(), Some("alice"));
+    assert!(!store.drop_extern_ref(alice));
+    assert_eq!(store.call(name_len_at, 0).unwrap(), -1);
+    // the slot is reused, but not the reference.
+    let carol = store.new_extern_ref(String::from("carol")).unwrap();
+    assert_ne!(carol, alice);
+    assert_eq!(store.call(name_len_at, 0).unwrap(), -1);
+    assert!(store.extern_ref::<String>(alice).is_none());
+    assert_eq!(store.extern_ref::<String>(carol).map(|s| s.as_str()), Some("carol"));
+    assert!(!store.drop_extern_ref(alice));
+
+    // slots are retired before their generations wrap around.
+    let mut stale = Vec::new();
+    for i in 0..300 {
+        let r = store.new_extern_ref(i).unwrap();
+        assert!(!stale.contains(&r));
+        assert!(store.drop_extern_ref(r));
+        stale.push(r);
+    }
+    assert!(stale.iter().all(|r| store.extern_ref::<i32>(*r).is_none()));
+
+    assert!(store.take_extern_ref::<i32>(bob).is_none());
+    assert!(store.drop_extern_ref(bob));
+    assert_eq!(store.num_extern_refs(), 1);
+
+
+    // function references.
+    let r = store.call(pick, 1).unwrap();
+    assert_eq!(store.func_ref_id(r), Some(square));
+    assert_eq!(store.func_ref_id(FuncRef::NULL), None);
+
+    assert_eq!(store.call(call_ref, (r, 7)).unwrap(), 49);
+    assert_eq!(store.call(call_ref, (double.into(), 7)).unwrap(), 14);
+    assert!(matches!(store.call(call_ref, (FuncRef::NULL, 7)).unwrap_err().inner(), Error::TrapCallIndirectRefNull));
+
+    assert_eq!(store.call(apply_picked, (0, 5)).unwrap(), 10);
+    assert_eq!(store.call(apply_picked, (1, 5)).unwrap(), 25);
+
+    // as dynamic values.
+    let mut rets = [Value::I32(0)];
+    store.call_dyn(pick.func_id(), &[Value::I32(0)], &mut rets).unwrap();
+    assert_eq!(rets[0], FuncRef::from(double).into());
+}
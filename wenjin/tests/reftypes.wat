@@ -0,0 +1,37 @@
+(module
+    (type $i32_i32 (func (param i32) (result i32)))
+
+    (import "env" "name_len" (func $name_len (param externref) (result i32)))
+    (import "env" "apply" (func $apply (param funcref i32) (result i32)))
+
+    (table $objs 4 externref)
+    (table $funcs 1 funcref)
+
+    (func $double (export "double") (param i32) (result i32)
+        (i32.mul (local.get 0) (i32.const 2)))
+
+    (func $square (export "square") (param i32) (result i32)
+        (i32.mul (local.get 0) (local.get 0)))
+
+    (func (export "store") (param i32 externref)
+        (table.set $objs (local.get 0) (local.get 1)))
+
+    (func (export "load") (param i32) (result externref)
+        (table.get $objs (local.get 0)))
+
+    (func (export "name_len_at") (param i32) (result i32)
+        (call $name_len (table.get $objs (local.get 0))))
+
+    (func $pick (export "pick") (param i32) (result funcref)
+        (if (result funcref) (local.get 0)
+            (then (ref.func $square))
+            (else (ref.func $double))))
+
+    ;; calls a function reference through the table.
+    (func (export "call_ref") (param funcref i32) (result i32)
+        (table.set $funcs (i32.const 0) (local.get 0))
+        (call_indirect $funcs (type $i32_i32) (local.get 1) (i32.const 0)))
+
+    (func (export "apply_picked") (param i32 i32) (result i32)
+        (call $apply (call $pick (local.get 0)) (local.get 1)))
+)
@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o reftypes.wasm \
+    reftypes.wat
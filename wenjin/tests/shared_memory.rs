@@ -0,0 +1,47 @@
+use wenjin::{Store, Error, wasm};
+
+
+#[test]
+fn shared_memory() {
+    let mut store = Store::new();
+
+    let memory = store.new_memory(wasm::Limits { min: 1, max: Some(4) }).unwrap();
+    let table = store.new_table(wasm::RefType::FuncRef, wasm::Limits { min: 2, max: None }).unwrap();
+
+    let imports = &[
+        ("host", "memory", memory.into()),
+        ("host", "table", table.into()),
+    ];
+    let a = store.new_instance(include_bytes!("shared_memory.wasm"), imports).unwrap();
+    let b = store.new_instance(include_bytes!("shared_memory.wasm"), imports).unwrap();
+
+    let a_load  = store.get_export_func::<i32, i32>(a, "load").unwrap();
+    let a_store = store.get_export_func::<(i32, i32), ()>(a, "store").unwrap();
+    let a_size  = store.get_export_func::<(), i32>(a, "size").unwrap();
+    let a_call  = store.get_export_func::<i32, i32>(a, "call").unwrap();
+    let b_load  = store.get_export_func::<i32, i32>(b, "load").unwrap();
+    let b_grow  = store.get_export_func::<(i32, i32), i32>(b, "grow_and_store").unwrap();
+
+    store.call(a_store, (16, 7)).unwrap();
+    assert_eq!(store.call(b_load, 16).unwrap(), 7);
+
+    // growing through one importer must be visible to the other.
+    assert_eq!(store.call(b_grow, (65536 + 4, 9)).unwrap(), 1);
+    assert_eq!(store.call(a_size, ()).unwrap(), 2);
+    assert_eq!(store.call(a_load, 65536 + 4).unwrap(), 9);
+    assert_eq!(store.memory(memory).size_pages(), 2);
+
+    // both instances wrote their element into the shared table.
+    assert_eq!(store.call(a_call, 1).unwrap(), 42);
+    assert!(matches!(store.call(a_call, 0).unwrap_err().inner(), Error::TrapCallIndirectRefNull));
+
+
+    // the memory is smaller than the import's minimum.
+    let small = store.new_memory(wasm::Limits { min: 0, max: None }).unwrap();
+    let result = store.new_instance(include_bytes!("shared_memory.wasm"), &[
+        ("host", "memory", small.into()),
+        ("host", "table", table.into()),
+    ]);
+    let Err(Error::Link(e)) = result else { panic!() };
+    assert_eq!(e.name, "memory");
+}
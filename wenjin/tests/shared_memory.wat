@@ -0,0 +1,27 @@
+(module
+  (import "host" "memory" (memory 1))
+  (import "host" "table" (table 2 funcref))
+
+  (func (export "load") (param i32) (result i32)
+    (i32.load (local.get 0)))
+
+  (func (export "store") (param i32 i32)
+    (i32.store (local.get 0) (local.get 1)))
+
+  (func (export "grow_and_store") (param i32 i32) (result i32)
+    (local $old i32)
+    (local.set $old (memory.grow (i32.const 1)))
+    (i32.store (local.get 0) (local.get 1))
+    (local.get $old))
+
+  (func (export "size") (result i32)
+    (memory.size))
+
+  (func $answer (result i32)
+    (i32.const 42))
+
+  (func (export "call") (param i32) (result i32)
+    (call_indirect (result i32) (local.get 0)))
+
+  (elem (i32.const 1) $answer)
+)
@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o shared_memory.wasm \
+    shared_memory.wat
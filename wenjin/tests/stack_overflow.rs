@@ -0,0 +1,33 @@
+use wenjin::{Store, Error};
+
+
+#[test]
+fn stack_overflow() {
+    let mut store = Store::new();
+
+    let inst = store.new_instance(include_bytes!("stack_overflow.wasm"), &[]).unwrap();
+    let runaway = store.get_export_func::<(), ()>(inst, "runaway").unwrap();
+    let depth = store.get_export_func::<i32, i32>(inst, "depth").unwrap();
+    let big_frame = store.get_export_func::<i32, i32>(inst, "big_frame").unwrap();
+
+    // unbounded recursion traps, the store is still usable.
+    assert!(matches!(store.call(runaway, ()).unwrap_err().inner(), Error::TrapStackOverflow));
+    assert_eq!(store.call(depth, 1000).unwrap(), 1000);
+
+    // call depth.
+    store.set_max_call_depth(100);
+    assert_eq!(store.call(depth, 50).unwrap(), 50);
+    assert!(matches!(store.call(depth, 100).unwrap_err().inner(), Error::TrapStackOverflow));
+    assert_eq!(store.call(depth, 50).unwrap(), 50);
+
+    // the limit applies to the root call too.
+    store.set_max_call_depth(0);
+    assert!(matches!(store.call(depth, 0).unwrap_err().inner(), Error::TrapStackOverflow));
+    store.set_max_call_depth(10_000);
+
+    // stack size.
+    store.set_max_stack_size(1000);
+    assert_eq!(store.call(depth, 100).unwrap(), 100);
+    assert!(matches!(store.call(big_frame, 100).unwrap_err().inner(), Error::TrapStackOverflow));
+    assert_eq!(store.call(big_frame, 10).unwrap(), 10);
+}
@@ -0,0 +1,34 @@
+(module
+    (func $runaway (export "runaway")
+        call $runaway)
+
+    (func $depth (export "depth") (param i32) (result i32)
+        local.get 0
+        i32.eqz
+        if (result i32)
+            i32.const 0
+        else
+            local.get 0
+            i32.const 1
+            i32.sub
+            call $depth
+            i32.const 1
+            i32.add
+        end)
+
+    (func $big_frame (export "big_frame") (param i32) (result i32)
+        (local i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64)
+        (local i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64)
+        local.get 0
+        i32.eqz
+        if (result i32)
+            i32.const 0
+        else
+            local.get 0
+            i32.const 1
+            i32.sub
+            call $big_frame
+            i32.const 1
+            i32.add
+        end)
+)
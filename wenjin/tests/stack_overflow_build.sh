@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o stack_overflow.wasm \
+    stack_overflow.wat
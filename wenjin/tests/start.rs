@@ -0,0 +1,38 @@
+use wenjin::{Store, Error, WasmPtr};
+
+
+#[test]
+fn start() {
+    let mut store = Store::new();
+
+    let value = std::rc::Rc::new(std::cell::Cell::new(41));
+
+    let init = store.new_host_func({ let value = value.clone(); move |store: &mut Store| {
+        // data segments are applied before the start function runs.
+        let memory = store.caller_memory()?;
+        assert_eq!(memory.read(WasmPtr::<u8>::new(0)).unwrap(), 1);
+        match value.get() {
+            -1 => Err(Error::Unimplemented),
+            v => Ok(v),
+        }
+    }});
+    let imports = &[("host", "init", init.into())];
+
+    let inst = store.new_instance(include_bytes!("start.wasm"), imports).unwrap();
+    let get = store.get_export_func::<(), i32>(inst, "get").unwrap();
+    assert_eq!(store.call(get, ()).unwrap(), 42);
+
+    value.set(0);
+    let result = store.new_instance(include_bytes!("start.wasm"), imports);
+    assert!(matches!(result.unwrap_err().inner(), Error::TrapUnreachable));
+
+    value.set(-1);
+    let result = store.new_instance(include_bytes!("start.wasm"), imports);
+    assert!(matches!(result.unwrap_err(), Error::Unimplemented));
+
+    // the store is still usable.
+    value.set(1);
+    let inst = store.new_instance(include_bytes!("start.wasm"), imports).unwrap();
+    let get = store.get_export_func::<(), i32>(inst, "get").unwrap();
+    assert_eq!(store.call(get, ()).unwrap(), 2);
+}
@@ -0,0 +1,18 @@
+(module
+  (import "host" "init" (func $init (result i32)))
+
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\01")
+
+  (func $start
+    (local $v i32)
+    (local.set $v (call $init))
+    (if (i32.eqz (local.get $v))
+      (then unreachable))
+    (i32.store (i32.const 4)
+      (i32.add (local.get $v) (i32.load8_u (i32.const 0)))))
+
+  (start $start)
+
+  (func (export "get") (result i32)
+    (i32.load (i32.const 4))))
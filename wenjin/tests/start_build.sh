@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o start.wasm \
+    start.wat
@@ -0,0 +1,52 @@
+use wenjin::{Store, Error, WasmPtr, WasmSize};
+
+
+struct Log {
+    entries: Vec<String>,
+}
+
+#[test]
+fn store_data() {
+    let mut store = Store::new();
+    assert!(store.data::<Log>().is_none());
+
+    store.set_data(Log { entries: vec![] });
+    assert!(store.data::<i32>().is_none());
+    assert!(store.take_data::<i32>().is_none());
+    assert_eq!(store.data::<Log>().unwrap().entries.len(), 0);
+
+    let log = store.new_host_func(|store: &mut Store, ptr: WasmPtr<u8>, len: WasmSize| -> Result<(), Error> {
+        let (mem, log) = store.caller_memory_and_data::<Log>()?;
+        let mut entry = String::new();
+        for i in 0..len.0 {
+            entry.push(mem.read(ptr.add(i))? as char);
+        }
+        log.entries.push(entry);
+        Ok(())
+    });
+
+    let inst = store.new_instance(include_bytes!("store_data.wasm"),
+        &[("host", "log", log.into())]).unwrap();
+    let run = store.get_export_func::<(), ()>(inst, "run").unwrap();
+
+    store.call(run, ()).unwrap();
+    assert_eq!(store.data::<Log>().unwrap().entries, ["hello", "world"]);
+
+    store.data_mut::<Log>().unwrap().entries.clear();
+    store.call(run, ()).unwrap();
+    assert_eq!(store.data::<Log>().unwrap().entries.len(), 2);
+
+    // the host function needs a wasm caller.
+    assert!(matches!(store.call(log, (WasmPtr::new(16), WasmSize(5))).unwrap_err(), Error::CallerNotWasm));
+
+    // and data of the right type.
+    let log = store.take_data::<Log>().unwrap();
+    assert_eq!(log.entries, ["hello", "world"]);
+    assert!(store.data::<Log>().is_none());
+    assert!(matches!(store.call(run, ()).unwrap_err(), Error::NoData));
+
+    store.set_data(42_i32);
+    assert!(matches!(store.call(run, ()).unwrap_err(), Error::NoData));
+    *store.data_mut::<i32>().unwrap() += 1;
+    assert_eq!(store.take_data::<i32>(), Some(43));
+}
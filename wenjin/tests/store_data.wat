@@ -0,0 +1,15 @@
+(module
+    (import "host" "log" (func $log (param i32 i32)))
+
+    (memory (export "memory") 1)
+    (data (i32.const 16) "hello")
+    (data (i32.const 32) "world")
+
+    (func (export "run")
+        i32.const 16
+        i32.const 5
+        call $log
+        i32.const 32
+        i32.const 5
+        call $log)
+)
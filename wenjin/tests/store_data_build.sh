@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o store_data.wasm \
+    store_data.wat
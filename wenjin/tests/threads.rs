@@ -0,0 +1,30 @@
+use wenjin::{Store, Module};
+
+
+#[test]
+fn threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Module>();
+
+    // clones share the compiled code.
+    let lib = Module::new(include_bytes!("linker_lib.wasm")).unwrap();
+
+    let threads = Vec::from_iter((0..4).map(|i| {
+        let lib = lib.clone();
+        std::thread::spawn(move || {
+            let mut store = Store::new();
+            let inst = store.instantiate(&lib, &[]).unwrap();
+            let next = store.get_export_func::<(), i32>(inst, "next").unwrap();
+
+            let mut result = 0;
+            for _ in 0..=i {
+                result = store.call(next, ()).unwrap();
+            }
+            result
+        })
+    }));
+
+    for (i, thread) in threads.into_iter().enumerate() {
+        assert_eq!(thread.join().unwrap(), i as i32 + 1);
+    }
+}
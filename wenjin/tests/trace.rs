@@ -0,0 +1,55 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use wenjin::{Store, TextTracer};
+
+
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl std::io::Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+
+#[test]
+fn trace() {
+    let mut store = Store::new();
+
+    let log = store.new_host_func(|_: i32| {});
+    let inst = store.new_instance(include_bytes!("trace.wasm"), &[("host", "log", log.into())]).unwrap();
+    let run = store.get_export_func::<i32, i32>(inst, "run").unwrap();
+
+    let output = Output::default();
+    store.set_tracer(TextTracer::new(output.clone()));
+    assert_eq!(store.call(run, 3).unwrap(), 9);
+
+    let output = String::from_utf8(output.0.take()).unwrap();
+    assert_eq!(output, concat!(
+        "call 2 (I32(3))\n",
+        "  2 @ 0x3d: LocalGet\n",
+        "  2 @ 0x3f: Call [0x3]\n",
+        "  call 0 (I32(3))\n",
+        "  ret 0 ()\n",
+        "  2 @ 0x41: LocalGet\n",
+        "  2 @ 0x43: Call [0x3]\n",
+        "  call 1 (I32(3))\n",
+        "    1 @ 0x35: LocalGet\n",
+        "    1 @ 0x37: LocalGet [0x3]\n",
+        "    1 @ 0x39: I32Mul [0x3, 0x3]\n",
+        "    1 @ 0x3a: End [0x9]\n",
+        "  ret 1 (I32(9))\n",
+        "  2 @ 0x45: End [0x9]\n",
+        "ret 2 (I32(9))\n"));
+
+    // untraced.
+    store.clear_tracer();
+    assert_eq!(store.call(run, 4).unwrap(), 16);
+}
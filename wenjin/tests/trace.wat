@@ -0,0 +1,10 @@
+(module
+    (import "host" "log" (func $log (param i32)))
+
+    (func $square (param $x i32) (result i32)
+        (i32.mul (local.get $x) (local.get $x)))
+
+    (func (export "run") (param $x i32) (result i32)
+        (call $log (local.get $x))
+        (call $square (local.get $x)))
+)
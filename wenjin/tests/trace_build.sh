@@ -0,0 +1,4 @@
+
+wat2wasm \
+    -o trace.wasm \
+    trace.wat